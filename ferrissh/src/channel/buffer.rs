@@ -5,10 +5,16 @@
 //!
 //! For large outputs (e.g., full BGP tables), this is critical for performance.
 
+use std::ops::Range;
+use std::sync::Arc;
+
 use bytes::BytesMut;
 use regex::bytes::Regex;
 use vte::{Parser, Perform};
 
+use super::patterns::PromptMatcher;
+use super::screen::Screen;
+
 /// Buffer for accumulating output and efficiently searching for patterns.
 ///
 /// Uses scrapli's optimization of only searching the tail of the buffer
@@ -17,6 +23,11 @@ use vte::{Parser, Perform};
 /// Holds a reusable [`vte::Parser`] so ANSI escape stripping does not
 /// allocate on every call and correctly handles sequences split across
 /// SSH data messages.
+///
+/// With a [`Screen`] set (see [`set_screen()`](Self::set_screen)), output
+/// is applied to the virtual screen instead, and the buffer holds the
+/// rendered text: the lines that scrolled off the screen, then what is on
+/// it.
 pub struct PatternBuffer {
     /// The accumulated output buffer.
     buffer: BytesMut,
@@ -27,6 +38,13 @@ pub struct PatternBuffer {
 
     /// Reusable VTE parser for ANSI escape stripping.
     parser: Parser,
+
+    /// Virtual screen the output is rendered on, if emulating one.
+    screen: Option<Screen>,
+
+    /// Length of the scrollback text at the start of `buffer`; the rest
+    /// is the rendered screen.
+    scrollback_len: usize,
 }
 
 impl std::fmt::Debug for PatternBuffer {
@@ -34,6 +52,7 @@ impl std::fmt::Debug for PatternBuffer {
         f.debug_struct("PatternBuffer")
             .field("buffer", &self.buffer)
             .field("search_depth", &self.search_depth)
+            .field("screen", &self.screen.as_ref().map(Screen::size))
             .finish_non_exhaustive()
     }
 }
@@ -70,6 +89,30 @@ impl PatternBuffer {
             buffer: BytesMut::with_capacity(4096),
             search_depth,
             parser: Parser::new(),
+            screen: None,
+            scrollback_len: 0,
+        }
+    }
+
+    /// Render output on `screen` instead of stripping escape codes, or go
+    /// back to stripping with `None`.
+    ///
+    /// Clears the buffer.
+    pub fn set_screen(&mut self, screen: Option<Screen>) {
+        self.screen = screen;
+        self.clear();
+    }
+
+    /// The virtual screen, if output is rendered on one.
+    pub fn screen(&self) -> Option<&Screen> {
+        self.screen.as_ref()
+    }
+
+    /// Resize the virtual screen, if there is one.
+    pub fn resize_screen(&mut self, width: usize, height: usize) {
+        if let Some(screen) = &mut self.screen {
+            screen.resize(width, height);
+            self.render();
         }
     }
 
@@ -78,12 +121,40 @@ impl PatternBuffer {
     /// The VTE parser is reused across calls, so escape sequences that
     /// span multiple SSH data messages are handled correctly.
     pub fn extend(&mut self, data: &[u8]) {
+        match &mut self.screen {
+            Some(screen) => {
+                self.parser.advance(screen, data);
+                self.render();
+            }
+            None => self.extend_stripped(data),
+        }
+    }
+
+    /// Extend the buffer with new data, stripping ANSI escape codes even
+    /// when a screen is set.
+    ///
+    /// For output handed on as it arrives, which later output cannot
+    /// redraw.
+    pub fn extend_stripped(&mut self, data: &[u8]) {
         let mut stripper = AnsiStripper {
             out: &mut self.buffer,
         };
         self.parser.advance(&mut stripper, data);
     }
 
+    /// Rebuild the buffer from the screen: new scrollback is kept, the
+    /// rendered screen replaced.
+    fn render(&mut self) {
+        let Some(screen) = &mut self.screen else {
+            return;
+        };
+        self.buffer.truncate(self.scrollback_len);
+        self.buffer
+            .extend_from_slice(screen.take_scrollback().as_bytes());
+        self.scrollback_len = self.buffer.len();
+        self.buffer.extend_from_slice(screen.render().as_bytes());
+    }
+
     /// Search only the tail of the buffer for the pattern.
     ///
     /// This is the key optimization from scrapli - we only search the
@@ -91,26 +162,27 @@ impl PatternBuffer {
     ///
     /// Returns the match if found, with byte offsets relative to the
     /// start of the search region (not the full buffer).
-    pub fn search_tail(&self, pattern: &Regex) -> Option<regex::bytes::Match<'_>> {
-        let start = self.buffer.len().saturating_sub(self.search_depth);
-        let tail = &self.buffer[start..];
-        pattern.find(tail)
+    pub fn search_tail(&self, pattern: &dyn PromptMatcher) -> Option<Range<usize>> {
+        pattern.find_prompt(self.tail())
     }
 
-    /// Search the tail for any of the given patterns (short-circuits on first match).
+    /// Search the tail for any of the given matchers (short-circuits on first match).
     ///
     /// Avoids the need for a combined mega-regex — each pattern's NFA is small
     /// and cache-friendly, and prompt patterns are mutually exclusive so at
-    /// most one will match.
-    pub fn search_tail_any(&self, patterns: &[Regex]) -> Option<regex::bytes::Match<'_>> {
+    /// most one will match. Matchers are tried in order, so put the most
+    /// specific first.
+    pub fn search_tail_any(&self, matchers: &[Arc<dyn PromptMatcher>]) -> Option<Range<usize>> {
+        let tail = self.tail();
+        matchers
+            .iter()
+            .find_map(|matcher| matcher.find_prompt(tail))
+    }
+
+    /// The last `search_depth` bytes.
+    fn tail(&self) -> &[u8] {
         let start = self.buffer.len().saturating_sub(self.search_depth);
-        let tail = &self.buffer[start..];
-        for pattern in patterns {
-            if let Some(m) = pattern.find(tail) {
-                return Some(m);
-            }
-        }
-        None
+        &self.buffer[start..]
     }
 
     /// Search the entire buffer for a pattern.
@@ -121,12 +193,16 @@ impl PatternBuffer {
     }
 
     /// Check if the tail contains a pattern match.
-    pub fn tail_contains(&self, pattern: &Regex) -> bool {
+    pub fn tail_contains(&self, pattern: &dyn PromptMatcher) -> bool {
         self.search_tail(pattern).is_some()
     }
 
     /// Take ownership of the buffer contents and reset.
+    ///
+    /// A virtual screen is cleared too, so the next output starts at its
+    /// top left.
     pub fn take(&mut self) -> BytesMut {
+        self.reset_screen();
         std::mem::take(&mut self.buffer)
     }
 
@@ -152,9 +228,36 @@ impl PatternBuffer {
 
     /// Clear the buffer.
     pub fn clear(&mut self) {
+        self.reset_screen();
         self.buffer.clear();
     }
 
+    /// Drop everything from byte `len` on.
+    ///
+    /// On a virtual screen, the text is erased from the screen and the
+    /// cursor moved to where it started.
+    pub fn truncate(&mut self, len: usize) {
+        match &mut self.screen {
+            Some(screen) if len >= self.scrollback_len => {
+                screen.truncate(len - self.scrollback_len);
+                self.render();
+            }
+            Some(screen) => {
+                screen.clear();
+                self.scrollback_len = len;
+                self.buffer.truncate(len);
+            }
+            None => self.buffer.truncate(len),
+        }
+    }
+
+    fn reset_screen(&mut self) {
+        if let Some(screen) = &mut self.screen {
+            screen.clear();
+        }
+        self.scrollback_len = 0;
+    }
+
     /// Get the search depth setting.
     pub fn search_depth(&self) -> usize {
         self.search_depth
@@ -279,6 +382,20 @@ mod tests {
         assert!(buffer.tail_contains(&pattern));
     }
 
+    #[test]
+    fn test_search_tail_any_in_order() {
+        use crate::channel::patterns::{LiteralMatcher, regex_matchers};
+
+        let mut buffer = PatternBuffer::new(100);
+        buffer.extend(b"output\nrouter# ");
+        let mut matchers = regex_matchers(&[Regex::new(r"#\s*$").unwrap()]);
+        assert_eq!(buffer.search_tail_any(&matchers), Some(13..15));
+
+        matchers.insert(0, Arc::new(LiteralMatcher::new("router#")));
+        assert_eq!(buffer.search_tail_any(&matchers), Some(7..14));
+        assert_eq!(buffer.search_tail_any(&[]), None);
+    }
+
     #[test]
     fn test_clear() {
         let mut buffer = PatternBuffer::new(100);
@@ -304,4 +421,23 @@ mod tests {
         assert!(s.contains("hello"));
         assert!(s.contains("world"));
     }
+
+    #[test]
+    fn test_screen_renders_redrawn_output() {
+        let mut buffer = PatternBuffer::new(100);
+        buffer.set_screen(Some(Screen::new(80, 2)));
+        buffer.extend(b"copy 10%\r");
+        buffer.extend(b"copy 100%\r\nline 2\r\n\x1b[1m");
+        buffer.extend(b"router# ");
+        // "copy 100%" has scrolled off into the scrollback.
+        assert_eq!(buffer.as_slice(), b"copy 100%\nline 2\nrouter# ");
+
+        buffer.truncate(b"copy 100%\nline 2\n".len());
+        buffer.extend(b"line 3\r\n");
+        assert_eq!(buffer.as_slice(), b"copy 100%\nline 2\nline 3\n");
+
+        assert_eq!(&buffer.take()[..], b"copy 100%\nline 2\nline 3\n");
+        buffer.extend(b"next");
+        assert_eq!(buffer.as_slice(), b"next");
+    }
 }
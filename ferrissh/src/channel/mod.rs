@@ -1,12 +1,18 @@
 //! Channel layer for pattern matching and PTY operations.
 //!
 //! This module handles the interactive session management,
-//! including pattern-based prompt detection and ANSI stripping.
+//! including pattern-based prompt detection, ANSI stripping, pager
+//! prompts and screen emulation.
 
 mod buffer;
+mod pager;
 mod patterns;
 mod pty;
+mod screen;
 
 pub use buffer::PatternBuffer;
-pub use patterns::PromptMatcher;
+pub use pager::Pager;
+pub(crate) use patterns::regex_matchers;
+pub use patterns::{LearnedPrompt, LiteralMatcher, PromptMatcher};
 pub use pty::{PtyChannel, PtyConfig};
+pub use screen::Screen;
@@ -0,0 +1,203 @@
+//! Pager prompt handling (`--More--`, `Press any key to continue`).
+//!
+//! When paging cannot be turned off, a device stops after each screenful
+//! and waits for a key. The channel answers the prompt, removes it from the
+//! output, and drops the sequence the device prints to erase it: typically
+//! backspaces or a carriage return, spaces over the prompt, then backspaces
+//! or a carriage return again (or an `ESC [ K` line clear).
+
+use std::borrow::Cow;
+
+use regex::bytes::Regex;
+
+/// Pager prompts to recognise and the key that answers them.
+#[derive(Debug, Clone)]
+pub struct Pager {
+    /// Patterns matching a pager prompt at the end of the output.
+    patterns: Vec<Regex>,
+    /// Bytes sent to show the next page.
+    response: Vec<u8>,
+}
+
+impl Pager {
+    /// A pager answering any of `patterns` with `response`.
+    ///
+    /// Patterns are searched for in the tail of the output and should be
+    /// anchored to its end (e.g. `--More--\s*$`), so that a page ending
+    /// in text that looks like a pager prompt is not mistaken for one.
+    pub fn new(patterns: Vec<Regex>, response: impl Into<Vec<u8>>) -> Self {
+        Self {
+            patterns,
+            response: response.into(),
+        }
+    }
+
+    /// Where the pager prompt in `tail` starts, if `tail` ends with one.
+    pub fn find(&self, tail: &[u8]) -> Option<usize> {
+        self.patterns
+            .iter()
+            .find_map(|pattern| pattern.find(tail))
+            .map(|m| m.start())
+    }
+
+    /// The bytes to send to answer a pager prompt.
+    pub fn response(&self) -> &[u8] {
+        &self.response
+    }
+
+    /// Whether there are any patterns to look for.
+    pub fn is_enabled(&self) -> bool {
+        !self.patterns.is_empty()
+    }
+}
+
+/// Drops the erase sequence a device prints after a pager prompt is
+/// answered.
+///
+/// Works on raw output, before ANSI stripping loses the carriage returns
+/// and backspaces that tell erasing spaces apart from indentation: only
+/// spaces followed by another erase control are dropped.
+#[derive(Debug, Default)]
+pub(crate) struct EraseFilter {
+    /// Whether an erase sequence may still be coming.
+    pending: bool,
+    /// Spaces at the end of the previous data that may or may not be part
+    /// of the erase sequence.
+    held_spaces: usize,
+}
+
+impl EraseFilter {
+    /// Expect an erase sequence at the start of the next output.
+    pub fn arm(&mut self) {
+        self.pending = true;
+        self.held_spaces = 0;
+    }
+
+    /// Remove a leading erase sequence from `data`.
+    pub fn filter<'a>(&mut self, data: &'a [u8]) -> Cow<'a, [u8]> {
+        if !self.pending || data.is_empty() {
+            return Cow::Borrowed(data);
+        }
+
+        // Scan the leading run of erase controls and spaces, remembering
+        // where the last control ended.
+        let mut pos = 0;
+        let mut erased = None;
+        while pos < data.len() {
+            match data[pos] {
+                b'\r' | 0x08 => {
+                    pos += 1;
+                    erased = Some(pos);
+                }
+                b' ' => pos += 1,
+                0x1b => match csi_len(&data[pos..]) {
+                    Some(len) => {
+                        pos += len;
+                        erased = Some(pos);
+                    }
+                    // Not a sequence we know, or split across reads
+                    None => break,
+                },
+                _ => break,
+            }
+        }
+
+        if pos == data.len() && pos > 0 && data[pos - 1] == b' ' {
+            // The run may go on in the next data; hold the trailing spaces.
+            let kept = erased.unwrap_or(0);
+            self.held_spaces = match erased {
+                Some(_) => pos - kept,
+                None => self.held_spaces + pos,
+            };
+            return Cow::Owned(Vec::new());
+        }
+
+        self.pending = pos == data.len();
+        match erased {
+            Some(end) => {
+                self.held_spaces = 0;
+                Cow::Borrowed(&data[end..])
+            }
+            None if self.held_spaces == 0 => Cow::Borrowed(data),
+            None => {
+                // The held spaces were indentation after all.
+                let mut out = vec![b' '; std::mem::take(&mut self.held_spaces)];
+                out.extend_from_slice(data);
+                Cow::Owned(out)
+            }
+        }
+    }
+}
+
+/// Length of the CSI sequence (`ESC [ params final`) at the start of
+/// `data`, if it is one and complete.
+fn csi_len(data: &[u8]) -> Option<usize> {
+    if data.get(1) != Some(&b'[') {
+        return None;
+    }
+    let end = data[2..]
+        .iter()
+        .position(|b| !matches!(b, b'0'..=b'9' | b';' | b'?'))?;
+    matches!(data[2 + end], 0x40..=0x7e).then_some(end + 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn armed() -> EraseFilter {
+        let mut filter = EraseFilter::default();
+        filter.arm();
+        filter
+    }
+
+    #[test]
+    fn test_pager_find() {
+        let pager = Pager::new(vec![Regex::new(r"--More--\s*$").unwrap()], " ");
+        assert_eq!(pager.find(b"line 1\n --More-- "), Some(8));
+        assert_eq!(pager.find(b"--More-- in the middle\n"), None);
+        assert!(!Pager::new(Vec::new(), " ").is_enabled());
+    }
+
+    #[test]
+    fn test_erase_backspaces_keeps_indentation() {
+        let mut filter = armed();
+        let data = b"\x08\x08\x08\x08    \x08\x08\x08\x08  indented\n";
+        assert_eq!(&filter.filter(data)[..], b"  indented\n");
+        // Only the output right after the answer is filtered.
+        assert_eq!(&filter.filter(b"\r\n")[..], b"\r\n");
+    }
+
+    #[test]
+    fn test_erase_carriage_return_and_line_clear() {
+        let mut filter = armed();
+        assert_eq!(&filter.filter(b"\r          \rnext\n")[..], b"next\n");
+
+        let mut filter = armed();
+        assert_eq!(&filter.filter(b"\r\x1b[Knext\n")[..], b"next\n");
+    }
+
+    #[test]
+    fn test_erase_split_across_reads() {
+        let mut filter = armed();
+        assert_eq!(&filter.filter(b"\r      ")[..], b"");
+        assert_eq!(&filter.filter(b"  ")[..], b"");
+        assert_eq!(&filter.filter(b"\rnext")[..], b"next");
+
+        // Spaces not followed by a control are indentation.
+        let mut filter = armed();
+        assert_eq!(&filter.filter(b"\r")[..], b"");
+        assert_eq!(&filter.filter(b"  ")[..], b"");
+        assert_eq!(&filter.filter(b"x")[..], b"  x");
+    }
+
+    #[test]
+    fn test_no_erase_sequence() {
+        let mut filter = armed();
+        assert_eq!(&filter.filter(b"next\n")[..], b"next\n");
+        assert_eq!(&filter.filter(b"\rmore")[..], b"\rmore");
+
+        let mut filter = EraseFilter::default();
+        assert_eq!(&filter.filter(b"\r  \rdata")[..], b"\r  \rdata");
+    }
+}
@@ -1,12 +1,32 @@
 //! Pattern matching utilities for prompt detection.
+//!
+//! Prompts are found through [`PromptMatcher`], implemented for regexes
+//! (the platforms' privilege-level patterns), fixed strings
+//! ([`LiteralMatcher`]) and prompts learned from the device
+//! ([`LearnedPrompt`]). Implement it for anything else.
+
+use std::ops::Range;
+use std::sync::Arc;
 
 use regex::bytes::Regex;
 
 /// Trait for prompt matching - regex by default, extensible for custom parsers.
 pub trait PromptMatcher: Send + Sync {
     /// Returns byte offset where match ends, or None if no match.
+    ///
+    /// `data` is the tail of the output read so far; a match means the
+    /// device is waiting at a prompt, so it should be anchored to the end.
     fn find_match(&self, data: &[u8]) -> Option<usize>;
 
+    /// Returns the byte range of the prompt in `data`, or None if no match.
+    ///
+    /// Defaults to the line [`find_match()`](Self::find_match) ends on;
+    /// override it if the prompt is only part of that line.
+    fn find_prompt(&self, data: &[u8]) -> Option<Range<usize>> {
+        let end = self.find_match(data)?;
+        Some(end - last_line(&data[..end]).len()..end)
+    }
+
     /// Check if the data matches the pattern.
     fn is_match(&self, data: &[u8]) -> bool {
         self.find_match(data).is_some()
@@ -18,6 +38,118 @@ impl PromptMatcher for Regex {
     fn find_match(&self, data: &[u8]) -> Option<usize> {
         self.find(data).map(|m| m.end())
     }
+
+    fn find_prompt(&self, data: &[u8]) -> Option<Range<usize>> {
+        self.find(data).map(|m| m.range())
+    }
+}
+
+/// Matches when the output ends with a fixed string, such as a known
+/// hostname prompt (`core-rtr-01#`).
+///
+/// Trailing spaces and tabs after the string are allowed, as most prompts
+/// end with one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiteralMatcher {
+    literal: Vec<u8>,
+}
+
+impl LiteralMatcher {
+    /// Match output ending with `literal`.
+    pub fn new(literal: impl Into<Vec<u8>>) -> Self {
+        Self {
+            literal: literal.into(),
+        }
+    }
+
+    /// The string matched.
+    pub fn literal(&self) -> &[u8] {
+        &self.literal
+    }
+}
+
+impl PromptMatcher for LiteralMatcher {
+    fn find_match(&self, data: &[u8]) -> Option<usize> {
+        self.find_prompt(data).map(|range| range.end)
+    }
+
+    fn find_prompt(&self, data: &[u8]) -> Option<Range<usize>> {
+        let end = trim_blanks_end(data);
+        (!self.literal.is_empty() && data[..end].ends_with(&self.literal))
+            .then(|| end - self.literal.len()..end)
+    }
+}
+
+/// The exact prompt a device showed, matched as the whole last line of
+/// the output.
+///
+/// Unlike a platform's generic patterns (`[$]\s*$` matches any line
+/// ending in `$`), a learned prompt includes the hostname and user, so
+/// output that merely ends with a prompt character is not mistaken for
+/// one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LearnedPrompt {
+    prompt: Vec<u8>,
+}
+
+impl LearnedPrompt {
+    /// Match `prompt` (leading and trailing whitespace ignored) as the
+    /// last line of the output.
+    pub fn new(prompt: impl AsRef<[u8]>) -> Self {
+        Self {
+            prompt: prompt.as_ref().trim_ascii().to_vec(),
+        }
+    }
+
+    /// Learn the prompt from output that ends at one: its last line.
+    ///
+    /// Returns `None` if the last line is blank.
+    pub fn from_output(data: &[u8]) -> Option<Self> {
+        let line = last_line(data).trim_ascii();
+        (!line.is_empty()).then(|| Self::new(line))
+    }
+
+    /// The learned prompt.
+    pub fn prompt(&self) -> &[u8] {
+        &self.prompt
+    }
+}
+
+impl PromptMatcher for LearnedPrompt {
+    fn find_match(&self, data: &[u8]) -> Option<usize> {
+        self.find_prompt(data).map(|range| range.end)
+    }
+
+    fn find_prompt(&self, data: &[u8]) -> Option<Range<usize>> {
+        let end = trim_blanks_end(data);
+        let line = last_line(&data[..end]);
+        let start = end - line.len();
+        let indent = line.len() - line.trim_ascii_start().len();
+        (line[indent..] == self.prompt[..] && !self.prompt.is_empty()).then(|| start + indent..end)
+    }
+}
+
+/// Wrap a platform's regex patterns as matchers.
+pub(crate) fn regex_matchers(patterns: &[Regex]) -> Vec<Arc<dyn PromptMatcher>> {
+    patterns
+        .iter()
+        .map(|pattern| Arc::new(pattern.clone()) as Arc<dyn PromptMatcher>)
+        .collect()
+}
+
+/// Length of `data` without trailing spaces and tabs.
+fn trim_blanks_end(data: &[u8]) -> usize {
+    data.iter()
+        .rposition(|&b| b != b' ' && b != b'\t')
+        .map_or(0, |pos| pos + 1)
+}
+
+/// The text after the last line break (`\n` or `\r`).
+fn last_line(data: &[u8]) -> &[u8] {
+    match memchr::memrchr2(b'\n', b'\r', data) {
+        Some(pos) => &data[pos + 1..],
+        None => data,
+    }
 }
 
 #[cfg(test)]
@@ -30,5 +162,53 @@ mod tests {
         assert!(pattern.is_match(b"router# "));
         assert!(pattern.is_match(b"some output\nrouter#"));
         assert!(!pattern.is_match(b"router> "));
+        assert_eq!(pattern.find_prompt(b"out\nrouter# "), Some(4..12));
+    }
+
+    #[test]
+    fn test_default_find_prompt() {
+        /// Only implements `find_match`, like matchers written before
+        /// `find_prompt` existed.
+        struct EndsWithHash;
+
+        impl PromptMatcher for EndsWithHash {
+            fn find_match(&self, data: &[u8]) -> Option<usize> {
+                data.ends_with(b"#").then_some(data.len())
+            }
+        }
+
+        assert_eq!(
+            EndsWithHash.find_prompt(
+                b"out
+router#"
+            ),
+            Some(4..11)
+        );
+        assert_eq!(EndsWithHash.find_prompt(b"router#"), Some(0..7));
+        assert_eq!(EndsWithHash.find_prompt(b"router>"), None);
+    }
+
+    #[test]
+    fn test_literal_matcher() {
+        let matcher = LiteralMatcher::new("core-rtr-01#");
+        assert_eq!(matcher.find_prompt(b"out\ncore-rtr-01# "), Some(4..16));
+        assert!(matcher.is_match(b"core-rtr-01#"));
+        assert!(!matcher.is_match(b"core-rtr-01#\nmore output"));
+        assert!(!matcher.is_match(b"core-rtr-01>"));
+        assert!(!LiteralMatcher::new("").is_match(b"anything"));
+    }
+
+    #[test]
+    fn test_learned_prompt() {
+        let prompt = LearnedPrompt::from_output(b"\r\nuser@host:~$ ").unwrap();
+        assert_eq!(prompt.prompt(), b"user@host:~$");
+
+        assert_eq!(prompt.find_prompt(b"out\nuser@host:~$ "), Some(4..16));
+        // A line that only ends like the prompt is output.
+        assert!(!prompt.is_match(b"price: 5$"));
+        assert!(!prompt.is_match(b"root@user@host:~$"));
+        assert!(!prompt.is_match(b"user@host:~$\nmore"));
+
+        assert!(LearnedPrompt::from_output(b"output\n  ").is_none());
     }
 }
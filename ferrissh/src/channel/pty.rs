@@ -1,56 +1,118 @@
 //! PTY channel abstraction for interactive sessions.
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use bytes::BytesMut;
-use regex::bytes::Regex;
-use russh::Channel;
-use russh::ChannelMsg;
-use russh::client::Msg;
 
 use log::trace;
 
 use super::buffer::PatternBuffer;
+use super::pager::{EraseFilter, Pager};
+use super::patterns::PromptMatcher;
+use super::screen::Screen;
 use crate::error::{ChannelError, Result};
+use crate::transport::{ShellChannel, ShellOutput, Sig};
 
 /// Configuration for PTY channel behavior.
 #[derive(Debug, Clone)]
 pub struct PtyConfig {
     /// Search depth for pattern matching.
     pub search_depth: usize,
+
+    /// Pager prompts to answer automatically.
+    pub pager: Option<Pager>,
 }
 
 impl Default for PtyConfig {
     fn default() -> Self {
-        Self { search_depth: 1000 }
+        Self {
+            search_depth: 1000,
+            pager: None,
+        }
     }
 }
 
 /// High-level PTY channel for interactive device sessions.
 ///
-/// This wraps a russh channel and provides pattern-based
-/// read operations with timeout handling.
+/// This wraps a transport's shell (an SSH PTY channel or a Telnet
+/// connection) and provides pattern-based read operations with timeout
+/// handling.
 pub struct PtyChannel {
-    /// The underlying russh channel.
-    channel: Channel<Msg>,
+    /// The underlying shell.
+    channel: Box<dyn ShellChannel>,
 
     /// Pattern buffer for accumulating output.
     buffer: PatternBuffer,
+
+    /// Pager prompts to answer, if any.
+    pager: Option<Pager>,
+
+    /// Drops the pager's erase sequence after each answer.
+    erase: EraseFilter,
 }
 
 impl PtyChannel {
-    /// Create a new PTY channel wrapping a russh channel.
-    pub fn new(channel: Channel<Msg>, config: PtyConfig) -> Self {
+    /// Create a new PTY channel wrapping a transport's shell.
+    pub fn new(channel: Box<dyn ShellChannel>, config: PtyConfig) -> Self {
         Self {
             channel,
             buffer: PatternBuffer::new(config.search_depth),
+            pager: config.pager.filter(Pager::is_enabled),
+            erase: EraseFilter::default(),
+        }
+    }
+
+    /// Tell the device the terminal is now `width` x `height`.
+    ///
+    /// A virtual screen is resized to match.
+    pub async fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.channel.resize(width, height).await?;
+        self.buffer.resize_screen(width as usize, height as usize);
+        Ok(())
+    }
+
+    /// Render output on a virtual screen of `width` x `height`, or strip
+    /// escape codes from it with `None` (the default).
+    ///
+    /// Reads then return the text as a terminal would show it, for
+    /// output that moves the cursor to redraw itself. Clears the buffer.
+    pub fn set_screen(&mut self, size: Option<(u32, u32)>) {
+        let screen = size.map(|(width, height)| Screen::new(width as usize, height as usize));
+        self.buffer.set_screen(screen);
+    }
+
+    /// Whether output is rendered on a virtual screen.
+    pub fn has_screen(&self) -> bool {
+        self.buffer.screen().is_some()
+    }
+
+    /// Send a break (see [`ShellChannel::send_break()`]).
+    pub async fn send_break(&mut self) -> Result<()> {
+        self.channel.send_break().await
+    }
+
+    /// Deliver a signal to the running command.
+    pub async fn signal(&mut self, signal: Sig) -> Result<()> {
+        self.channel.signal(signal).await
+    }
+
+    /// Discard output until none has arrived for `quiet`.
+    pub async fn drain(&mut self, quiet: Duration) -> Result<()> {
+        self.buffer.clear();
+        loop {
+            match tokio::time::timeout(quiet, self.channel.read()).await {
+                Err(_) => return Ok(()),
+                Ok(Some(ShellOutput::Data(_) | ShellOutput::Stderr(_))) => {}
+                Ok(Some(ShellOutput::Eof)) => return Err(ChannelError::Eof.into()),
+                Ok(None) => return Err(ChannelError::Disconnected.into()),
+            }
         }
     }
 
     /// Send data to the channel.
     pub async fn write(&mut self, data: &[u8]) -> Result<()> {
-        self.channel.data(data).await.map_err(ChannelError::Ssh)?;
-        Ok(())
+        self.channel.write(data).await
     }
 
     /// Send a command (with newline).
@@ -65,7 +127,7 @@ impl PtyChannel {
     /// Read until pattern matches (with timeout).
     pub async fn read_until_pattern(
         &mut self,
-        pattern: &Regex,
+        pattern: &dyn PromptMatcher,
         timeout: Duration,
     ) -> Result<BytesMut> {
         let deadline = tokio::time::Instant::now() + timeout;
@@ -75,79 +137,125 @@ impl PtyChannel {
                 _ = tokio::time::sleep_until(deadline) => {
                     return Err(ChannelError::PatternTimeout(timeout).into());
                 }
-                msg = self.channel.wait() => {
+                msg = self.channel.read() => {
                     match msg {
-                        Some(ChannelMsg::Data { data }) => {
-                            self.buffer.extend(&data);
+                        Some(ShellOutput::Data(data)) => {
+                            self.buffer.extend(&self.erase.filter(&data));
                             if self.buffer.search_tail(pattern).is_some() {
                                 trace!("prompt pattern matched after {} bytes", self.buffer.as_slice().len());
                                 return Ok(self.buffer.take());
                             }
                         }
-                        Some(ChannelMsg::ExtendedData { data, ext: 1 }) => {
+                        Some(ShellOutput::Stderr(data)) => {
                             // stderr - also add to buffer
                             self.buffer.extend(&data);
                         }
-                        Some(ChannelMsg::Eof) => {
+                        Some(ShellOutput::Eof) => {
                             return Err(ChannelError::Eof.into());
                         }
                         None => {
                             return Err(ChannelError::Disconnected.into());
                         }
-                        _ => {}
                     }
                 }
             }
         }
     }
 
-    /// Read until any of the given patterns matches (with timeout).
+    /// Read until any of the given matchers matches (with timeout).
     ///
     /// Like [`PtyChannel::read_until_pattern`] but checks multiple individual patterns
     /// instead of one combined regex, avoiding combined-NFA memory overhead.
+    /// Matchers are tried in order.
+    ///
+    /// Pager prompts are answered along the way and left out of the
+    /// returned data.
     pub async fn read_until_any_pattern(
         &mut self,
-        patterns: &[Regex],
+        matchers: &[Arc<dyn PromptMatcher>],
+        timeout: Duration,
+    ) -> Result<BytesMut> {
+        self.read_until_prompt(matchers, &[], Duration::ZERO, timeout)
+            .await
+    }
+
+    /// Read until a prompt, trusting `fallback` matchers less than
+    /// `matchers` (with timeout).
+    ///
+    /// A match by `matchers` ends the read at once. A match by `fallback`
+    /// only does once no more output has arrived for `quiet`, so output
+    /// that merely ends like a loose fallback pattern (a line ending in
+    /// `$` or `>`) does not cut the read short.
+    ///
+    /// Pager prompts are answered along the way and left out of the
+    /// returned data.
+    pub async fn read_until_prompt(
+        &mut self,
+        matchers: &[Arc<dyn PromptMatcher>],
+        fallback: &[Arc<dyn PromptMatcher>],
+        quiet: Duration,
         timeout: Duration,
     ) -> Result<BytesMut> {
         let deadline = tokio::time::Instant::now() + timeout;
+        // When a fallback match is accepted, if no output comes first.
+        let mut settle = None;
 
         loop {
             tokio::select! {
-                _ = tokio::time::sleep_until(deadline) => {
+                _ = tokio::time::sleep_until(settle.unwrap_or(deadline).min(deadline)) => {
+                    if settle.is_some_and(|at| at <= tokio::time::Instant::now()) {
+                        trace!("fallback prompt pattern matched after {} bytes", self.buffer.as_slice().len());
+                        return Ok(self.buffer.take());
+                    }
                     return Err(ChannelError::PatternTimeout(timeout).into());
                 }
-                msg = self.channel.wait() => {
+                msg = self.channel.read() => {
                     match msg {
-                        Some(ChannelMsg::Data { data }) => {
-                            self.buffer.extend(&data);
-                            if self.buffer.search_tail_any(patterns).is_some() {
+                        Some(ShellOutput::Data(data)) => {
+                            self.buffer.extend(&self.erase.filter(&data));
+                            if self.buffer.search_tail_any(matchers).is_some() {
                                 trace!("prompt pattern matched after {} bytes", self.buffer.as_slice().len());
                                 return Ok(self.buffer.take());
                             }
+                            let tail_start = self.buffer.len().saturating_sub(self.buffer.search_depth());
+                            if let Some(start) = self.find_pager_prompt(&self.buffer.as_slice()[tail_start..]) {
+                                self.buffer.truncate(tail_start + start);
+                                self.answer_pager().await?;
+                            }
+                            settle = self
+                                .buffer
+                                .search_tail_any(fallback)
+                                .map(|_| tokio::time::Instant::now() + quiet);
                         }
-                        Some(ChannelMsg::ExtendedData { data, ext: 1 }) => {
+                        Some(ShellOutput::Stderr(data)) => {
                             // stderr - also add to buffer
                             self.buffer.extend(&data);
                         }
-                        Some(ChannelMsg::Eof) => {
+                        Some(ShellOutput::Eof) => {
                             return Err(ChannelError::Eof.into());
                         }
                         None => {
                             return Err(ChannelError::Disconnected.into());
                         }
-                        _ => {}
                     }
                 }
             }
         }
     }
 
-    /// Read one batch of SSH data without waiting for a prompt pattern.
+    /// Read one batch of data without waiting for a prompt pattern.
     ///
     /// Returns ANSI-stripped data after receiving the first `Data` message.
     /// Accumulates `ExtendedData` (stderr) but keeps waiting for stdout.
     /// This is the low-level primitive used by [`CommandStream`](crate::driver::stream::CommandStream).
+    ///
+    /// Data is always stripped rather than rendered on a virtual screen,
+    /// since a batch cannot be redrawn once it has been returned.
+    ///
+    /// Pager prompts are not detected here, since one may be split across
+    /// batches: the caller looks for them with
+    /// [`find_pager_prompt()`](Self::find_pager_prompt) and answers with
+    /// [`answer_pager()`](Self::answer_pager).
     pub async fn read_chunk(&mut self, timeout: Duration) -> Result<BytesMut> {
         let deadline = tokio::time::Instant::now() + timeout;
         loop {
@@ -155,28 +263,52 @@ impl PtyChannel {
                 _ = tokio::time::sleep_until(deadline) => {
                     return Err(ChannelError::PatternTimeout(timeout).into());
                 }
-                msg = self.channel.wait() => {
+                msg = self.channel.read() => {
                     match msg {
-                        Some(ChannelMsg::Data { data }) => {
-                            self.buffer.extend(&data);
+                        Some(ShellOutput::Data(data)) => {
+                            self.buffer.extend_stripped(&self.erase.filter(&data));
+                            if self.buffer.is_empty() {
+                                // Nothing but the pager's erase sequence
+                                continue;
+                            }
                             return Ok(self.buffer.take());
                         }
-                        Some(ChannelMsg::ExtendedData { data, ext: 1 }) => {
-                            self.buffer.extend(&data);
+                        Some(ShellOutput::Stderr(data)) => {
+                            self.buffer.extend_stripped(&data);
                         }
-                        Some(ChannelMsg::Eof) => {
+                        Some(ShellOutput::Eof) => {
                             return Err(ChannelError::Eof.into());
                         }
                         None => {
                             return Err(ChannelError::Disconnected.into());
                         }
-                        _ => {}
                     }
                 }
             }
         }
     }
 
+    /// Where the pager prompt in `tail` starts, if `tail` ends with one.
+    ///
+    /// Always `None` when the platform defines no pager patterns.
+    pub fn find_pager_prompt(&self, tail: &[u8]) -> Option<usize> {
+        self.pager.as_ref()?.find(tail)
+    }
+
+    /// Answer a pager prompt to get the next page.
+    ///
+    /// The sequence the device prints to erase the prompt is dropped from
+    /// the output that follows.
+    pub async fn answer_pager(&mut self) -> Result<()> {
+        let Some(pager) = &self.pager else {
+            return Ok(());
+        };
+        trace!("answering pager prompt");
+        let response = pager.response().to_vec();
+        self.erase.arm();
+        self.write(&response).await
+    }
+
     /// Get current buffer contents without clearing.
     pub fn peek_buffer(&self) -> &[u8] {
         self.buffer.as_slice()
@@ -0,0 +1,566 @@
+//! Virtual terminal screen for cursor-addressed output.
+//!
+//! Stripping escape sequences turns output that redraws itself — progress
+//! bars rewritten with `\r`, `top`-style full-screen refreshes, CLIs that
+//! move the cursor up to repaint a line — into a jumble of every frame.
+//! A [`Screen`] instead applies the output to a grid of cells the way a
+//! terminal would, so the rendered text is what a user would have seen.
+//!
+//! Lines scrolled off the top of the grid move to the scrollback and can
+//! no longer change.
+
+use vte::{Params, Perform};
+
+/// Columns between tab stops.
+const TAB_WIDTH: usize = 8;
+
+/// One row of the grid.
+#[derive(Debug, Clone)]
+struct Row {
+    cells: Vec<char>,
+    /// Whether the text continues on the next row (an automatic wrap
+    /// rather than a line break).
+    wrapped: bool,
+}
+
+impl Row {
+    fn new(width: usize) -> Self {
+        Self {
+            cells: vec![' '; width],
+            wrapped: false,
+        }
+    }
+
+    fn clear(&mut self, from: usize, to: usize) {
+        let to = to.min(self.cells.len());
+        if from < to {
+            self.cells[from..to].fill(' ');
+        }
+        if to == self.cells.len() {
+            self.wrapped = false;
+        }
+    }
+
+    /// Number of cells up to the last non-blank one.
+    fn text_len(&self) -> usize {
+        self.cells
+            .iter()
+            .rposition(|&c| c != ' ')
+            .map_or(0, |pos| pos + 1)
+    }
+}
+
+/// A virtual terminal screen: a grid of `width` x `height` cells plus the
+/// scrollback above it, driven by a [`vte::Parser`].
+///
+/// Handles printing with automatic wrap, carriage return, backspace, tabs,
+/// cursor movement (`CUU`/`CUD`/`CUF`/`CUB`/`CUP`/`CHA`/`VPA` and their
+/// relatives), erasing (`ED`, `EL`, `ECH`), inserting and deleting
+/// characters and lines, scrolling, and saving and restoring the cursor.
+/// Colours and other attributes are ignored. A line feed also returns the
+/// cursor to the first column, as with output post-processed by a PTY
+/// (`onlcr`).
+///
+/// # Example
+///
+/// ```rust
+/// use ferrissh::channel::Screen;
+///
+/// let mut parser = vte::Parser::new();
+/// let mut screen = Screen::new(80, 24);
+/// parser.advance(&mut screen, b"Progress: 10%\rProgress: 100%\r\ndone\r\n");
+/// assert_eq!(screen.render(), "Progress: 100%\ndone\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Screen {
+    width: usize,
+    height: usize,
+    rows: Vec<Row>,
+    /// Cursor row, `0..height`.
+    row: usize,
+    /// Cursor column, `0..width`.
+    col: usize,
+    /// A character was printed in the last column; the next one wraps.
+    wrap_pending: bool,
+    /// Cursor position saved by `DECSC` or `SCOSC`.
+    saved: (usize, usize),
+    /// Text of the rows scrolled off the top, one `\n`-terminated line
+    /// each (wrapped rows are joined).
+    scrollback: String,
+}
+
+impl Screen {
+    /// An empty screen of `width` columns and `height` rows (at least one
+    /// of each).
+    pub fn new(width: usize, height: usize) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+        Self {
+            width,
+            height,
+            rows: vec![Row::new(width); height],
+            row: 0,
+            col: 0,
+            wrap_pending: false,
+            saved: (0, 0),
+            scrollback: String::new(),
+        }
+    }
+
+    /// The screen size as `(width, height)`.
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// The cursor position as `(row, column)`, both from zero.
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    /// Change the screen size.
+    ///
+    /// Rows are cut or padded on the right; when the screen gets shorter,
+    /// rows above the cursor move to the scrollback to keep it on screen.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let width = width.max(1);
+        let height = height.max(1);
+        for row in &mut self.rows {
+            row.cells.resize(width, ' ');
+        }
+        while self.rows.len() > height {
+            if self.row > 0 {
+                self.scroll_off_top();
+                self.row -= 1;
+            } else {
+                self.rows.pop();
+            }
+        }
+        self.rows.resize(height, Row::new(width));
+        self.width = width;
+        self.height = height;
+        self.col = self.col.min(width - 1);
+        self.saved = (self.saved.0.min(height - 1), self.saved.1.min(width - 1));
+        self.wrap_pending = false;
+    }
+
+    /// Clear the grid and the scrollback and home the cursor.
+    pub fn clear(&mut self) {
+        for row in &mut self.rows {
+            row.clear(0, self.width);
+        }
+        self.scrollback.clear();
+        self.row = 0;
+        self.col = 0;
+        self.wrap_pending = false;
+        self.saved = (0, 0);
+    }
+
+    /// Text of the rows that scrolled off the top.
+    pub fn scrollback(&self) -> &str {
+        &self.scrollback
+    }
+
+    /// Take the text of the rows that scrolled off the top, leaving the
+    /// scrollback empty.
+    pub fn take_scrollback(&mut self) -> String {
+        std::mem::take(&mut self.scrollback)
+    }
+
+    /// The text on the grid: one line per row (wrapped rows joined), with
+    /// trailing blanks and blank rows below the cursor left out.
+    ///
+    /// The cursor's row keeps its blanks up to the cursor, so a prompt
+    /// still ends with the space after it.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.render_into(&mut out);
+        out
+    }
+
+    /// The scrollback followed by the rendered grid.
+    pub fn contents(&self) -> String {
+        let mut out = self.scrollback.clone();
+        self.render_into(&mut out);
+        out
+    }
+
+    fn render_into(&self, out: &mut String) {
+        let last = self
+            .rows
+            .iter()
+            .rposition(|row| row.text_len() > 0)
+            .map_or(self.row, |last| last.max(self.row));
+        for (index, row) in self.rows[..=last].iter().enumerate() {
+            let mut len = row.text_len();
+            if index == self.row {
+                len = len.max(self.col);
+            }
+            if row.wrapped && index < last {
+                len = self.width;
+            }
+            out.extend(&row.cells[..len]);
+            if !row.wrapped && index < last {
+                out.push('\n');
+            }
+        }
+    }
+
+    /// Erase everything from the `offset`th byte of
+    /// [`render()`](Self::render)'s text on, and move the cursor there.
+    pub fn truncate(&mut self, offset: usize) {
+        let rendered = self.render();
+        let Some(before) = rendered.get(..offset) else {
+            return;
+        };
+        let row = before.matches('\n').count();
+        let line_start = before.rfind('\n').map_or(0, |pos| pos + 1);
+        let chars = before[line_start..].chars().count();
+
+        // Wrapped rows render as one line; find the row the text ends on.
+        let mut row_index = 0;
+        let mut lines = 0;
+        while lines < row && row_index < self.height {
+            if !self.rows[row_index].wrapped {
+                lines += 1;
+            }
+            row_index += 1;
+        }
+        let row_index = (row_index + chars / self.width).min(self.height - 1);
+        let col = chars % self.width;
+
+        self.rows[row_index].clear(col, self.width);
+        for row in &mut self.rows[row_index + 1..] {
+            row.clear(0, self.width);
+        }
+        self.row = row_index;
+        self.col = col;
+        self.wrap_pending = false;
+    }
+
+    fn print_char(&mut self, c: char) {
+        if self.wrap_pending {
+            self.rows[self.row].wrapped = true;
+            self.line_feed();
+            self.wrap_pending = false;
+        }
+        self.rows[self.row].cells[self.col] = c;
+        if self.col + 1 < self.width {
+            self.col += 1;
+        } else {
+            self.wrap_pending = true;
+        }
+    }
+
+    /// Move down a row, scrolling at the bottom, and back to column 0.
+    fn line_feed(&mut self) {
+        if self.row + 1 < self.height {
+            self.row += 1;
+        } else {
+            self.scroll_up(1);
+        }
+        self.col = 0;
+        self.wrap_pending = false;
+    }
+
+    /// Move up a row, scrolling down at the top.
+    fn reverse_index(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+        } else {
+            self.scroll_down(1);
+        }
+        self.wrap_pending = false;
+    }
+
+    /// Scroll the grid up `n` rows, into the scrollback.
+    fn scroll_up(&mut self, n: usize) {
+        for _ in 0..n.min(self.height) {
+            self.scroll_off_top();
+            self.rows.push(Row::new(self.width));
+        }
+    }
+
+    /// Move the top row into the scrollback.
+    fn scroll_off_top(&mut self) {
+        let row = self.rows.remove(0);
+        let len = if row.wrapped {
+            self.width
+        } else {
+            row.text_len()
+        };
+        self.scrollback.extend(&row.cells[..len]);
+        if !row.wrapped {
+            self.scrollback.push('\n');
+        }
+    }
+
+    /// Scroll the grid down `n` rows, dropping the bottom ones.
+    fn scroll_down(&mut self, n: usize) {
+        self.insert_rows(0, n);
+    }
+
+    /// Insert `n` blank rows at `at`, pushing the rows below down.
+    fn insert_rows(&mut self, at: usize, n: usize) {
+        let n = n.min(self.height - at);
+        self.rows.truncate(self.height - n);
+        for _ in 0..n {
+            self.rows.insert(at, Row::new(self.width));
+        }
+    }
+
+    /// Delete `n` rows at `at`, pulling the rows below up.
+    fn delete_rows(&mut self, at: usize, n: usize) {
+        let n = n.min(self.height - at);
+        self.rows.drain(at..at + n);
+        self.rows.resize(self.height, Row::new(self.width));
+    }
+
+    fn move_to(&mut self, row: usize, col: usize) {
+        self.row = row.min(self.height - 1);
+        self.col = col.min(self.width - 1);
+        self.wrap_pending = false;
+    }
+
+    /// `ED`: erase in display.
+    fn erase_display(&mut self, mode: u16) {
+        match mode {
+            0 => {
+                let (row, col) = (self.row, self.col);
+                self.rows[row].clear(col, self.width);
+                for row in &mut self.rows[row + 1..] {
+                    row.clear(0, self.width);
+                }
+            }
+            1 => {
+                let (row, col) = (self.row, self.col);
+                for row in &mut self.rows[..row] {
+                    row.clear(0, self.width);
+                }
+                self.rows[row].clear(0, col + 1);
+            }
+            2 | 3 => {
+                for row in &mut self.rows {
+                    row.clear(0, self.width);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `EL`: erase in line.
+    fn erase_line(&mut self, mode: u16) {
+        let (width, col) = (self.width, self.col);
+        let row = &mut self.rows[self.row];
+        match mode {
+            0 => row.clear(col, width),
+            1 => row.clear(0, col + 1),
+            2 => row.clear(0, width),
+            _ => {}
+        }
+    }
+}
+
+impl Perform for Screen {
+    fn print(&mut self, c: char) {
+        self.print_char(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' | 0x0b | 0x0c => self.line_feed(),
+            b'\r' => {
+                self.col = 0;
+                self.wrap_pending = false;
+            }
+            0x08 => {
+                self.col = self.col.saturating_sub(1);
+                self.wrap_pending = false;
+            }
+            b'\t' => {
+                let next = (self.col / TAB_WIDTH + 1) * TAB_WIDTH;
+                self.col = next.min(self.width - 1);
+            }
+            _ => {}
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        if ignore {
+            return;
+        }
+        let mut values = params.iter().map(|param| param[0]);
+        let first = values.next().unwrap_or(0);
+        let second = values.next().unwrap_or(0);
+        // Count parameters default to 1, and 0 means 1 too.
+        let n = usize::from(first.max(1));
+        let (row, col) = (self.row, self.col);
+
+        match (intermediates, action) {
+            ([], 'A') => self.move_to(row.saturating_sub(n), col),
+            ([], 'B' | 'e') => self.move_to(row + n, col),
+            ([], 'C' | 'a') => self.move_to(row, col + n),
+            ([], 'D') => self.move_to(row, col.saturating_sub(n)),
+            ([], 'E') => self.move_to(row + n, 0),
+            ([], 'F') => self.move_to(row.saturating_sub(n), 0),
+            ([], 'G' | '`') => self.move_to(row, n - 1),
+            ([], 'd') => self.move_to(n - 1, col),
+            ([], 'H' | 'f') => self.move_to(n - 1, usize::from(second.max(1)) - 1),
+            ([] | [b'?'], 'J') => self.erase_display(first),
+            ([] | [b'?'], 'K') => self.erase_line(first),
+            ([], 'X') => {
+                let width = self.width;
+                self.rows[row].cells[col..(col + n).min(width)].fill(' ');
+            }
+            ([], 'P') => {
+                let cells = &mut self.rows[row].cells;
+                let n = n.min(cells.len() - col);
+                cells.drain(col..col + n);
+                cells.resize(self.width, ' ');
+            }
+            ([], '@') => {
+                let cells = &mut self.rows[row].cells;
+                let n = n.min(cells.len() - col);
+                cells.splice(col..col, std::iter::repeat_n(' ', n));
+                cells.truncate(self.width);
+            }
+            ([], 'L') => self.insert_rows(row, n),
+            ([], 'M') => self.delete_rows(row, n),
+            ([], 'S') => self.scroll_up(n),
+            ([], 'T') => self.scroll_down(n),
+            ([], 's') => self.saved = (row, col),
+            ([], 'u') => self.move_to(self.saved.0, self.saved.1),
+            _ => {}
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        if ignore || !intermediates.is_empty() {
+            return;
+        }
+        match byte {
+            b'7' => self.saved = (self.row, self.col),
+            b'8' => self.move_to(self.saved.0, self.saved.1),
+            b'D' | b'E' => {
+                let col = self.col;
+                self.line_feed();
+                if byte == b'D' {
+                    self.col = col;
+                }
+            }
+            b'M' => self.reverse_index(),
+            b'c' => {
+                self.erase_display(2);
+                self.move_to(0, 0);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vte::Parser;
+
+    fn run(width: usize, height: usize, data: &[u8]) -> Screen {
+        let mut screen = Screen::new(width, height);
+        Parser::new().advance(&mut screen, data);
+        screen
+    }
+
+    #[test]
+    fn test_carriage_return_overwrites() {
+        let screen = run(80, 24, b"Progress: 10%\rProgress: 55%\rProgress: 100%\r\n");
+        assert_eq!(screen.render(), "Progress: 100%\n");
+    }
+
+    #[test]
+    fn test_cursor_up_redraws_line() {
+        let screen = run(
+            80,
+            24,
+            b"eth0: 0 pps\r\neth1: 0 pps\r\n\x1b[2A\x1b[2Keth0: 7 pps\r\n",
+        );
+        assert_eq!(screen.render(), "eth0: 7 pps\neth1: 0 pps");
+    }
+
+    #[test]
+    fn test_clear_screen_keeps_last_frame() {
+        let screen = run(
+            80,
+            24,
+            b"\x1b[H\x1b[2Jload 0.10\r\ntasks 12\x1b[H\x1b[2Jload 0.25\r\ntasks 13",
+        );
+        assert_eq!(screen.render(), "load 0.25\ntasks 13");
+    }
+
+    #[test]
+    fn test_backspace_and_erase() {
+        let screen = run(80, 24, b"abc\x08\x08X\x1b[K");
+        assert_eq!(screen.render(), "aX");
+        assert_eq!(screen.cursor(), (0, 2));
+    }
+
+    #[test]
+    fn test_cursor_row_keeps_trailing_space() {
+        let screen = run(80, 24, b"out\r\nrouter# ");
+        assert_eq!(screen.render(), "out\nrouter# ");
+    }
+
+    #[test]
+    fn test_scrollback() {
+        let mut screen = run(80, 2, b"one\r\ntwo\r\nthree\r\nfour");
+        assert_eq!(screen.scrollback(), "one\ntwo\n");
+        assert_eq!(screen.render(), "three\nfour");
+        assert_eq!(screen.contents(), "one\ntwo\nthree\nfour");
+        assert_eq!(screen.take_scrollback(), "one\ntwo\n");
+        assert_eq!(screen.scrollback(), "");
+    }
+
+    #[test]
+    fn test_wrap_joins_rows() {
+        let screen = run(4, 3, b"abcdefgh\r\nx");
+        assert_eq!(screen.render(), "abcdefgh\nx");
+
+        // Wrapped rows stay joined in the scrollback.
+        let screen = run(4, 2, b"abcdef\r\nx\r\ny");
+        assert_eq!(screen.scrollback(), "abcdef\n");
+        assert_eq!(screen.render(), "x\ny");
+    }
+
+    #[test]
+    fn test_cursor_position_and_save_restore() {
+        let screen = run(20, 5, b"\x1b[3;5Hmid\x1b7\x1b[1;1Htop\x1b8!");
+        assert_eq!(screen.render(), "top\n\n    mid!");
+    }
+
+    #[test]
+    fn test_insert_and_delete() {
+        let screen = run(20, 5, b"abcdef\r\x1b[2C\x1b[2P");
+        assert_eq!(screen.render(), "abef");
+        let screen = run(20, 5, b"abcdef\r\x1b[2C\x1b[2@");
+        assert_eq!(screen.render(), "ab  cdef");
+        let screen = run(20, 5, b"one\r\ntwo\r\nthree\x1b[2;1H\x1b[M");
+        assert_eq!(screen.render(), "one\nthree");
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut screen = run(80, 24, b"line 1\r\nline 2\r\n--More--");
+        screen.truncate("line 1\nline 2\n".len());
+        assert_eq!(screen.render(), "line 1\nline 2\n");
+        assert_eq!(screen.cursor(), (2, 0));
+
+        Parser::new().advance(&mut screen, b"line 3\r\n");
+        assert_eq!(screen.render(), "line 1\nline 2\nline 3\n");
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut screen = run(80, 3, b"one\r\ntwo\r\nthree");
+        screen.resize(80, 2);
+        assert_eq!(screen.scrollback(), "one\n");
+        assert_eq!(screen.render(), "two\nthree");
+        assert_eq!(screen.size(), (80, 2));
+    }
+}
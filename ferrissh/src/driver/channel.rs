@@ -18,16 +18,30 @@ use secrecy::{ExposeSecret, SecretString};
 
 use super::config_session::GenericConfigSession;
 use super::interactive::{InteractiveEvent, InteractiveResult, InteractiveStep};
+use super::options::CommandOptions;
 use super::payload::Payload;
 use super::privilege::PrivilegeManager;
 use super::response::Response;
 use super::stream::{CommandStream, StreamConfig};
-use crate::channel::PtyChannel;
+use crate::channel::{LearnedPrompt, PromptMatcher, PtyChannel, regex_matchers};
 use crate::error::{ChannelError, DisconnectReason, DriverError, Error, Result, TransportError};
 use crate::platform::PlatformDefinition;
+use crate::reconnect::ReconnectEvent;
 use crate::session::Session;
+use crate::transport::{Sig, TerminalOptions};
 use log::{debug, trace, warn};
 
+/// Ctrl-C.
+const INTERRUPT: u8 = 0x03;
+
+/// How long output must stop for after an interrupt before the channel is
+/// considered back at a settled prompt.
+const RESYNC_QUIET: Duration = Duration::from_millis(100);
+
+/// How long output must stop for before a prompt matched only by the
+/// prompt patterns is accepted, when the channel has learned its prompt.
+pub(crate) const FALLBACK_PROMPT_QUIET: Duration = Duration::from_millis(500);
+
 /// The state of a channel's PTY shell.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChannelState {
@@ -62,6 +76,25 @@ pub struct Channel {
     /// Individual prompt patterns (avoids combined-NFA memory overhead).
     prompt_patterns: Vec<Regex>,
 
+    /// Matchers added with `add_prompt_matcher()`, tried before the
+    /// prompt patterns.
+    custom_matchers: Vec<Arc<dyn PromptMatcher>>,
+
+    /// Every matcher prompts are detected with, in order: the custom ones,
+    /// the learned prompt, then the prompt patterns.
+    prompt_matchers: Vec<Arc<dyn PromptMatcher>>,
+
+    /// How many of `prompt_matchers` are trusted outright; the rest are a
+    /// fallback, trusted once output stops.
+    exact_matchers: usize,
+
+    /// The device's exact prompt, if learned.
+    learned_prompt: Option<LearnedPrompt>,
+
+    /// True once the privilege level changed under a learned prompt, so it
+    /// is learned again before the next command.
+    prompt_stale: bool,
+
     /// Whether to normalize command output.
     normalize: bool,
 
@@ -79,6 +112,12 @@ pub struct Channel {
 
     /// Password for privilege escalation (extracted from auth config).
     auth_password: Option<SecretString>,
+
+    /// True once `close()` ran, so a closed channel is never reconnected.
+    closed: bool,
+
+    /// Terminal settings, reused (with the current size) on reconnect.
+    terminal: TerminalOptions,
 }
 
 impl Channel {
@@ -89,10 +128,13 @@ impl Channel {
         timeout: Duration,
         prompt_patterns: Vec<Regex>,
         normalize: bool,
-        disconnect_rx: watch::Receiver<Option<DisconnectReason>>,
         auth_password: Option<SecretString>,
+        terminal: TerminalOptions,
     ) -> Self {
         let privilege_manager = PrivilegeManager::new(session.privilege_base().clone());
+        let disconnect_rx = session.disconnect_rx().clone();
+        let prompt_matchers = regex_matchers(&prompt_patterns);
+        let exact_matchers = prompt_matchers.len();
 
         Self {
             session,
@@ -100,12 +142,19 @@ impl Channel {
             privilege_manager,
             timeout,
             prompt_patterns,
+            custom_matchers: Vec::new(),
+            prompt_matchers,
+            exact_matchers,
+            learned_prompt: None,
+            prompt_stale: false,
             normalize,
             state: ChannelState::Ready,
             disconnect_rx,
             last_command_at: None,
             stream_dirty: false,
             auth_password,
+            closed: false,
+            terminal,
         }
     }
 
@@ -124,21 +173,139 @@ impl Channel {
             debug!("initial privilege level: {:?}", level_name);
         }
 
+        if self.session.learns_prompt() {
+            self.learn_prompt().await?;
+        }
+
         // Execute on_open commands
         let on_open = self.session.platform().on_open_commands.clone();
         if !on_open.is_empty() {
             debug!("executing {} on-open commands", on_open.len());
         }
         for cmd in &on_open {
-            self.send_command(cmd).await?;
+            self.run_command(cmd).await?;
         }
 
         Ok(())
     }
 
+    /// Reopen this channel on a reconnected session.
+    ///
+    /// Reconnects the session (see [`Session::reconnect()`]), opens a new
+    /// shell, re-runs `on_open_commands` and returns to the privilege level
+    /// the channel was at before. Called automatically before the next
+    /// command when the session has a
+    /// [`ReconnectPolicy`](crate::ReconnectPolicy) and the connection was
+    /// lost; call it directly to reconnect on demand.
+    pub async fn reconnect(&mut self) -> Result<()> {
+        let result = self.reopen().await;
+        if result.is_err() {
+            // Stay dead (and eligible for another try) until a reopen succeeds
+            self.state = ChannelState::Dead;
+        }
+        result
+    }
+
+    /// Move to a new shell on the reconnected session and restore state.
+    async fn reopen(&mut self) -> Result<()> {
+        let previous = self.current_privilege().map(str::to_string);
+
+        let session = self.session.reconnect().await?;
+        let mut pty = session.open_pty(&self.terminal).await?;
+        if self.pty.has_screen() {
+            pty.set_screen(Some((self.terminal.width, self.terminal.height)));
+        }
+
+        self.disconnect_rx = session.disconnect_rx().clone();
+        self.session = session;
+        self.pty = pty;
+        self.state = ChannelState::Ready;
+        self.stream_dirty = false;
+        self.closed = false;
+
+        self.initialize().await?;
+        if let Some(target) = previous
+            && self.current_privilege() != Some(target.as_str())
+        {
+            self.navigate_privilege(&target).await?;
+        }
+
+        debug!(
+            "channel reopened at privilege {:?}",
+            self.current_privilege()
+        );
+        self.session.emit(ReconnectEvent::ChannelReopened {
+            privilege: self.current_privilege().map(str::to_string),
+        });
+        Ok(())
+    }
+
+    /// Reconnect first if the connection was lost and a reconnect policy
+    /// is configured.
+    pub(crate) async fn ensure_connected(&mut self) -> Result<()> {
+        if self.needs_reconnect() {
+            self.reconnect().await?;
+        }
+        Ok(())
+    }
+
+    /// Whether the connection was lost (rather than closed by the caller)
+    /// and the session is configured to reconnect.
+    fn needs_reconnect(&self) -> bool {
+        if self.closed || self.session.reconnect_policy().is_none() {
+            return false;
+        }
+        match &*self.disconnect_rx.borrow() {
+            Some(DisconnectReason::Closed) => false,
+            Some(_) => true,
+            None => self.state == ChannelState::Dead || !self.session.is_alive(),
+        }
+    }
+
     /// Send a command and wait for the prompt.
     pub async fn send_command(&mut self, command: &str) -> Result<Response> {
+        self.ensure_connected().await?;
+        self.run_command(command).await
+    }
+
+    /// Send a command with its own timeout, patterns or output handling
+    /// (see [`CommandOptions`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use std::time::Duration;
+    /// # use ferrissh::CommandOptions;
+    /// # async fn example(channel: &mut ferrissh::Channel) -> Result<(), ferrissh::Error> {
+    /// let options = CommandOptions::new().timeout(Duration::from_secs(600));
+    /// let response = channel
+    ///     .send_command_with("request system software add /var/tmp/junos.tgz", &options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_command_with(
+        &mut self,
+        command: &str,
+        options: &CommandOptions,
+    ) -> Result<Response> {
+        self.ensure_connected().await?;
+        self.run_command_with(command, options).await
+    }
+
+    /// Send a command without reconnecting first.
+    async fn run_command(&mut self, command: &str) -> Result<Response> {
+        self.run_command_with(command, &CommandOptions::new()).await
+    }
+
+    /// Send a command with options, without reconnecting first.
+    async fn run_command_with(
+        &mut self,
+        command: &str,
+        options: &CommandOptions,
+    ) -> Result<Response> {
         self.check_ready()?;
+        self.relearn_stale_prompt().await?;
 
         debug!("send_command: {:?}", command);
 
@@ -154,10 +321,22 @@ impl Channel {
             return Err(e);
         }
 
-        // Wait for prompt
+        // Wait for prompt (or a pattern the options expect)
+        let mut matchers = options.patterns.clone();
+        let mut exact_len = matchers.len();
+        if !options.replace_patterns {
+            matchers.extend_from_slice(&self.prompt_matchers);
+            exact_len += self.exact_matchers;
+        }
+        let (exact, fallback) = matchers.split_at(exact_len);
         let read_result = self
             .pty
-            .read_until_any_pattern(&self.prompt_patterns, self.timeout)
+            .read_until_prompt(
+                exact,
+                fallback,
+                FALLBACK_PROMPT_QUIET,
+                options.timeout.unwrap_or(self.timeout),
+            )
             .await;
 
         let mut data = match read_result {
@@ -173,21 +352,28 @@ impl Channel {
         let elapsed = start.elapsed();
 
         // Extract prompt from the tail
-        let prompt = self.extract_prompt(&data);
-
-        // Update current privilege level
-        if let Ok(level) = self.privilege_manager.determine_from_prompt(&prompt) {
-            let level_name = level.name.clone();
-            let _ = self.privilege_manager.set_current(&level_name);
+        let tail = &data[data.len().saturating_sub(self.pty.search_depth())..];
+        let expected = options.patterns.iter().any(|m| m.is_match(tail));
+        let prompt = extract_prompt_with(&matchers, tail);
+
+        // Update current privilege level, unless the command stopped short
+        // of a prompt
+        if options.update_privilege && !expected {
+            self.update_privilege_from_prompt(&prompt);
+            self.relearn_stale_prompt().await?;
         }
 
         // Normalize output in place
-        if self.normalize {
+        if options.normalize.unwrap_or(self.normalize) {
             self.normalize_output_in_place(&mut data, command);
         }
 
         // Check for failure patterns
-        for pattern in &self.session.platform().failed_when_contains {
+        let failed_when_contains = options
+            .failed_when_contains
+            .as_ref()
+            .unwrap_or(&self.session.platform().failed_when_contains);
+        for pattern in failed_when_contains {
             if memchr::memmem::find(&data, pattern.as_bytes()).is_some() {
                 debug!("send_command: completed in {:?}, success=false", elapsed);
                 let payload = Payload::from_bytes_mut(data);
@@ -208,6 +394,30 @@ impl Channel {
         Ok(Response::new(command, payload, prompt, elapsed))
     }
 
+    /// Send a command with screen emulation for this command only (see
+    /// [`set_screen_emulation()`](Self::set_screen_emulation)).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn example(channel: &mut ferrissh::Channel) -> Result<(), ferrissh::Error> {
+    /// // `monitor` output redraws each counter in place.
+    /// let response = channel.send_command_rendered("monitor port 1/1/1 rate").await?;
+    /// println!("{}", response.result);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_command_rendered(&mut self, command: &str) -> Result<Response> {
+        self.ensure_connected().await?;
+        if self.screen_emulation() {
+            return self.run_command(command).await;
+        }
+        self.set_screen_emulation(true);
+        let result = self.run_command(command).await;
+        self.set_screen_emulation(false);
+        result
+    }
+
     /// Send multiple commands sequentially.
     pub async fn send_commands(&mut self, commands: &[&str]) -> Result<Vec<Response>> {
         let mut responses = Vec::with_capacity(commands.len());
@@ -235,7 +445,9 @@ impl Channel {
     /// detected leaves unread data on the channel and will cause subsequent
     /// commands to fail with [`DriverError::StreamNotDrained`].
     pub async fn send_command_stream(&mut self, command: &str) -> Result<CommandStream<'_>> {
+        self.ensure_connected().await?;
         self.check_ready()?;
+        self.relearn_stale_prompt().await?;
         debug!("send_command_stream: {:?}", command);
 
         let start = Instant::now();
@@ -249,8 +461,11 @@ impl Channel {
             return Err(e);
         }
 
+        let (matchers, fallback) = self.prompt_matchers.split_at(self.exact_matchers);
         let config = StreamConfig {
-            prompt_patterns: self.prompt_patterns.clone(),
+            prompt_matchers: matchers.to_vec(),
+            fallback_matchers: fallback.to_vec(),
+            fallback_quiet: FALLBACK_PROMPT_QUIET,
             search_depth: self.pty.search_depth(),
             timeout: self.timeout,
             normalize: self.normalize,
@@ -269,6 +484,12 @@ impl Channel {
 
     /// Acquire a specific privilege level.
     pub async fn acquire_privilege(&mut self, target: &str) -> Result<()> {
+        self.ensure_connected().await?;
+        self.navigate_privilege(target).await
+    }
+
+    /// Acquire a privilege level without reconnecting first.
+    async fn navigate_privilege(&mut self, target: &str) -> Result<()> {
         self.check_ready()?;
 
         let current = self
@@ -359,6 +580,7 @@ impl Channel {
                 Ok(level) => {
                     let level_name = level.name.clone();
                     self.privilege_manager.set_current(&level_name)?;
+                    self.prompt_stale = self.learned_prompt.is_some();
                     if level_name != *to {
                         return Err(
                             DriverError::PrivilegeAcquisitionFailed { target: to.clone() }.into(),
@@ -377,7 +599,7 @@ impl Channel {
             }
         }
 
-        Ok(())
+        self.relearn_stale_prompt().await
     }
 
     /// Send an interactive command sequence.
@@ -385,6 +607,7 @@ impl Channel {
         &mut self,
         events: &[InteractiveEvent],
     ) -> Result<InteractiveResult> {
+        self.ensure_connected().await?;
         self.check_ready()?;
 
         let total_start = Instant::now();
@@ -470,6 +693,7 @@ impl Channel {
 
     /// Send commands in configuration mode.
     pub async fn send_config(&mut self, commands: &[&str]) -> Result<Vec<Response>> {
+        self.ensure_connected().await?;
         debug!("send_config: {} commands", commands.len());
 
         // Save current privilege level
@@ -564,6 +788,40 @@ impl Channel {
         &self.prompt_patterns
     }
 
+    /// Every matcher prompts are detected with, in the order they are
+    /// tried.
+    pub fn prompt_matchers(&self) -> &[Arc<dyn PromptMatcher>] {
+        &self.prompt_matchers
+    }
+
+    /// Detect prompts with `matcher` too, trying it before the platform's
+    /// patterns (and any matcher added earlier).
+    ///
+    /// For prompts the platform's regexes match too loosely or not at
+    /// all. The matched text is still checked against the privilege
+    /// levels' patterns to track the current level.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use ferrissh::channel::LiteralMatcher;
+    ///
+    /// # fn example(channel: &mut ferrissh::Channel) {
+    /// channel.add_prompt_matcher(LiteralMatcher::new("core-rtr-01#"));
+    /// # }
+    /// ```
+    pub fn add_prompt_matcher(&mut self, matcher: impl PromptMatcher + 'static) {
+        self.custom_matchers.insert(0, Arc::new(matcher));
+        self.rebuild_prompt_matchers();
+    }
+
+    /// Remove the matchers added with
+    /// [`add_prompt_matcher()`](Self::add_prompt_matcher).
+    pub fn clear_prompt_matchers(&mut self) {
+        self.custom_matchers.clear();
+        self.rebuild_prompt_matchers();
+    }
+
     /// Set the default timeout.
     pub fn set_timeout(&mut self, timeout: Duration) {
         self.timeout = timeout;
@@ -574,11 +832,128 @@ impl Channel {
         self.normalize = normalize;
     }
 
+    /// Set whether output is rendered on a virtual screen (default: off).
+    ///
+    /// Escape sequences are normally stripped from the output, which
+    /// garbles anything that moves the cursor to redraw itself: progress
+    /// bars, `top`, `monitor` commands, CLIs that repaint the line. With
+    /// screen emulation, output is applied to a [`Screen`](crate::channel::Screen)
+    /// the size of the terminal, and commands return the text as it would
+    /// have been shown. Streams are not affected, since a chunk cannot be
+    /// redrawn once it has been yielded.
+    pub fn set_screen_emulation(&mut self, enabled: bool) {
+        let size = enabled.then_some((self.terminal.width, self.terminal.height));
+        self.pty.set_screen(size);
+    }
+
+    /// Whether output is rendered on a virtual screen.
+    pub fn screen_emulation(&self) -> bool {
+        self.pty.has_screen()
+    }
+
     /// When the last command completed successfully.
     pub fn last_command_at(&self) -> Option<Instant> {
         self.last_command_at
     }
 
+    /// The terminal settings this channel was opened with, at its current
+    /// size.
+    pub fn terminal(&self) -> &TerminalOptions {
+        &self.terminal
+    }
+
+    /// Resize the terminal (an SSH `window-change` request, or a Telnet
+    /// NAWS update).
+    ///
+    /// Lets commands that format to the terminal width — wide tables, long
+    /// interface descriptions — be re-run at a new size without
+    /// reconnecting. The new size is kept if the channel reconnects.
+    pub async fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        if self.state != ChannelState::Ready {
+            return Err(DriverError::NotConnected.into());
+        }
+        self.pty.resize(width, height).await?;
+        self.terminal.width = width;
+        self.terminal.height = height;
+        debug!("terminal resized to {}x{}", width, height);
+        Ok(())
+    }
+
+    /// Interrupt the running command with Ctrl-C and wait for the prompt.
+    ///
+    /// Recovers a channel whose command ran away — a `ping` or `monitor
+    /// traffic` that ended in a [`PatternTimeout`](ChannelError::PatternTimeout),
+    /// or a dropped [`CommandStream`]. Output up to the prompt is discarded
+    /// (see [`resync()`](Self::resync)).
+    pub async fn interrupt(&mut self) -> Result<()> {
+        self.write_control(INTERRUPT).await?;
+        self.resync().await
+    }
+
+    /// Wait for the prompt, then for output to settle, discarding
+    /// everything read.
+    ///
+    /// Waiting for output to stay quiet briefly catches a second prompt,
+    /// printed when a command finished just as it was interrupted.
+    ///
+    /// Use after [`signal()`](Self::signal) or
+    /// [`send_control()`](Self::send_control) to get back in step with the
+    /// shell. Leaves the channel ready for the next command, even if a
+    /// [`CommandStream`] was dropped undrained.
+    pub async fn resync(&mut self) -> Result<()> {
+        self.check_open()?;
+        let result = async {
+            let (_, prompt) = self.read_until_prompt().await?;
+            self.pty.drain(RESYNC_QUIET).await?;
+            Ok(prompt)
+        }
+        .await;
+        match result {
+            Ok(prompt) => {
+                debug!("resynchronised at prompt {:?}", prompt);
+                self.update_privilege_from_prompt(&prompt);
+                self.stream_dirty = false;
+                Ok(())
+            }
+            Err(e) => {
+                self.handle_error(&e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Send a control character: `'c'` for Ctrl-C, `'z'` for Ctrl-Z, `']'`
+    /// for a console server's escape, and so on.
+    ///
+    /// Letters are case-insensitive; `@ [ \ ] ^ _` and `?` (DEL) are also
+    /// accepted. Nothing is read back; follow with
+    /// [`resync()`](Self::resync) to wait for the prompt.
+    pub async fn send_control(&mut self, key: char) -> Result<()> {
+        let byte = control_byte(key).ok_or(ChannelError::InvalidControlKey(key))?;
+        self.write_control(byte).await
+    }
+
+    /// Send a break.
+    ///
+    /// Over Telnet this is `IAC BRK`, which console servers pass on as a
+    /// serial break (e.g. to reach ROMMON or a boot loader). SSH `break`
+    /// requests (RFC 4335) are not supported by russh, so over SSH this
+    /// fails with [`TransportError::Unsupported`].
+    pub async fn send_break(&mut self) -> Result<()> {
+        self.check_open()?;
+        self.pty.send_break().await
+    }
+
+    /// Deliver `signal` to the running command (an SSH `signal` request).
+    ///
+    /// Many servers ignore signal requests on PTY sessions; prefer
+    /// [`interrupt()`](Self::interrupt) to stop a command. Over Telnet only
+    /// `Sig::INT` is available, sent as `IAC IP`.
+    pub async fn signal(&mut self, signal: Sig) -> Result<()> {
+        self.check_open()?;
+        self.pty.signal(signal).await
+    }
+
     /// Rebuild prompt patterns from current privilege levels.
     ///
     /// Simply collects references to each level's already-compiled pattern.
@@ -590,12 +965,100 @@ impl Channel {
             .all_levels()
             .map(|level| level.pattern.clone())
             .collect();
+        self.rebuild_prompt_matchers();
+    }
+
+    /// Rebuild the matcher list from the custom matchers and the prompt
+    /// patterns.
+    fn rebuild_prompt_matchers(&mut self) {
+        self.prompt_matchers = self.custom_matchers.clone();
+        if let Some(learned) = &self.learned_prompt {
+            self.prompt_matchers.push(Arc::new(learned.clone()));
+            self.exact_matchers = self.prompt_matchers.len();
+        }
+        self.prompt_matchers
+            .extend(regex_matchers(&self.prompt_patterns));
+        if self.learned_prompt.is_none() {
+            self.exact_matchers = self.prompt_matchers.len();
+        }
+    }
+
+    /// Learn the device's exact prompt: send an empty line and record the
+    /// prompt it answers with.
+    ///
+    /// Prompts are then matched against the learned prompt first. The
+    /// privilege levels' patterns are a fallback: a line they match ends a
+    /// command only once no more output follows for a moment, so output
+    /// that merely ends with `$` or `>` no longer cuts the response short.
+    /// When the privilege level changes, the new level's prompt is learned
+    /// the same way before the next command. Other changes to the prompt,
+    /// such as the working directory, are matched by the patterns until
+    /// this is called again.
+    ///
+    /// Called when the channel opens if
+    /// [`SessionBuilder::learn_prompt()`](crate::SessionBuilder::learn_prompt)
+    /// is set.
+    pub async fn learn_prompt(&mut self) -> Result<()> {
+        self.check_ready()?;
+        self.learned_prompt = None;
+        self.prompt_stale = false;
+        self.rebuild_prompt_matchers();
+
+        if let Err(e) = self.pty.send("").await {
+            if Self::is_connection_error(&e) {
+                self.handle_disconnect(DisconnectReason::TransportError(e.to_string()));
+            }
+            return Err(e);
+        }
+        let data = match self.read_until_prompt().await {
+            Ok((data, _)) => data,
+            Err(e) => {
+                if Self::is_connection_error(&e) {
+                    self.handle_disconnect(DisconnectReason::TransportError(e.to_string()));
+                }
+                return Err(e);
+            }
+        };
+        self.set_learned_prompt(LearnedPrompt::from_output(&data));
+        Ok(())
+    }
+
+    /// The device's exact prompt, if learned (see
+    /// [`learn_prompt()`](Self::learn_prompt)).
+    pub fn learned_prompt(&self) -> Option<&LearnedPrompt> {
+        self.learned_prompt.as_ref()
+    }
+
+    /// Forget the learned prompt and match on the prompt patterns alone.
+    pub fn forget_prompt(&mut self) {
+        self.set_learned_prompt(None);
+    }
+
+    fn set_learned_prompt(&mut self, learned: Option<LearnedPrompt>) {
+        if let Some(learned) = &learned {
+            debug!(
+                "learned prompt: {:?}",
+                String::from_utf8_lossy(learned.prompt())
+            );
+        }
+        self.learned_prompt = learned;
+        self.rebuild_prompt_matchers();
+    }
+
+    /// Learn the prompt again if the privilege level changed since it was
+    /// learned.
+    async fn relearn_stale_prompt(&mut self) -> Result<()> {
+        if self.prompt_stale {
+            self.learn_prompt().await?;
+        }
+        Ok(())
     }
 
     /// Close this channel.
     ///
     /// Runs on_close commands and drops the PTY. Does NOT close the session.
     pub async fn close(&mut self) -> Result<()> {
+        self.closed = true;
         if self.state != ChannelState::Ready {
             return Ok(());
         }
@@ -613,7 +1076,7 @@ impl Channel {
             // Best-effort wait for prompt
             if let Err(e) = self
                 .pty
-                .read_until_any_pattern(&self.prompt_patterns, self.timeout)
+                .read_until_any_pattern(&self.prompt_matchers, self.timeout)
                 .await
             {
                 warn!("on_close command {:?} failed to complete: {}", cmd, e);
@@ -639,10 +1102,18 @@ impl Channel {
 
     /// Update the current privilege level from a prompt string.
     ///
-    /// Used by [`CommandStream`] when it detects the final prompt.
+    /// Used by [`send_command()`](Self::send_command), and by
+    /// [`CommandStream`] when it detects the final prompt.
+    ///
+    /// The prompt may be output that only looks like one, so it never
+    /// replaces the learned prompt; a change of level marks the prompt to be
+    /// learned again instead.
     pub(crate) fn update_privilege_from_prompt(&mut self, prompt: &str) {
         if let Ok(level) = self.privilege_manager.determine_from_prompt(prompt) {
             let level_name = level.name.clone();
+            if self.current_privilege() != Some(level_name.as_str()) {
+                self.prompt_stale = self.learned_prompt.is_some();
+            }
             let _ = self.privilege_manager.set_current(&level_name);
         }
     }
@@ -666,6 +1137,26 @@ impl Channel {
         }
     }
 
+    /// Write a control byte, outside of any command.
+    async fn write_control(&mut self, byte: u8) -> Result<()> {
+        self.check_open()?;
+        trace!("sending control byte {:#04x}", byte);
+        let result = self.pty.write(&[byte]).await;
+        if let Err(e) = &result {
+            self.handle_error(e);
+        }
+        result
+    }
+
+    /// Check that the channel is open, whether or not a command (or an
+    /// undrained stream) is still running on it.
+    fn check_open(&self) -> Result<()> {
+        if self.state != ChannelState::Ready || self.session.disconnect_rx().borrow().is_some() {
+            return Err(DriverError::NotConnected.into());
+        }
+        Ok(())
+    }
+
     /// Check that the channel is in `Ready` state.
     fn check_ready(&mut self) -> Result<()> {
         if self.state != ChannelState::Ready {
@@ -700,25 +1191,14 @@ impl Channel {
     fn extract_prompt(&self, data: &[u8]) -> String {
         let search_depth = self.pty.search_depth();
         let tail_start = data.len().saturating_sub(search_depth);
-        let tail = &data[tail_start..];
-        for pattern in &self.prompt_patterns {
-            if let Some(m) = pattern.find(tail) {
-                let matched = String::from_utf8_lossy(&tail[m.start()..])
-                    .trim()
-                    .to_string();
-                trace!("prompt matched: {:?}", matched);
-                return matched;
-            }
-        }
-        trace!("no prompt match in {} bytes of output", data.len());
-        String::new()
+        extract_prompt_with(&self.prompt_matchers, &data[tail_start..])
     }
 
     /// Read until the prompt is matched, then determine current privilege.
     async fn read_until_prompt(&mut self) -> Result<(BytesMut, String)> {
         let data = self
             .pty
-            .read_until_any_pattern(&self.prompt_patterns, self.timeout)
+            .read_until_any_pattern(&self.prompt_matchers, self.timeout)
             .await?;
 
         let prompt = self.extract_prompt(&data);
@@ -875,6 +1355,30 @@ pub(crate) fn strip_echo_in_place(buf: &mut BytesMut, command: &str) {
     }
 }
 
+/// The byte Ctrl-`key` sends, as a terminal would.
+fn control_byte(key: char) -> Option<u8> {
+    match key.to_ascii_uppercase() {
+        '?' => Some(0x7f),
+        key @ '@'..='_' => Some(key as u8 - b'@'),
+        _ => None,
+    }
+}
+
+/// The text from the first of `matchers`' matches in `tail` to the end.
+fn extract_prompt_with(matchers: &[Arc<dyn PromptMatcher>], tail: &[u8]) -> String {
+    for matcher in matchers {
+        if let Some(range) = matcher.find_prompt(tail) {
+            let matched = String::from_utf8_lossy(&tail[range.start..])
+                .trim()
+                .to_string();
+            trace!("prompt matched: {:?}", matched);
+            return matched;
+        }
+    }
+    trace!("no prompt match in {} bytes of output", tail.len());
+    String::new()
+}
+
 /// Strip the trailing prompt (last line) from the buffer.
 pub(crate) fn strip_trailing_prompt_in_place(buf: &mut BytesMut) {
     if let Some(pos) = memchr::memrchr(b'\n', buf) {
@@ -886,6 +1390,18 @@ pub(crate) fn strip_trailing_prompt_in_place(buf: &mut BytesMut) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_control_byte() {
+        assert_eq!(control_byte('c'), Some(0x03));
+        assert_eq!(control_byte('C'), Some(0x03));
+        assert_eq!(control_byte('z'), Some(0x1a));
+        assert_eq!(control_byte(']'), Some(0x1d));
+        assert_eq!(control_byte('@'), Some(0x00));
+        assert_eq!(control_byte('?'), Some(0x7f));
+        assert_eq!(control_byte('1'), None);
+        assert_eq!(control_byte('é'), None);
+    }
+
     // =========================================================================
     // normalize_linefeeds_in_place — unit tests
     // =========================================================================
@@ -0,0 +1,290 @@
+//! Non-PTY command execution over `exec` channels.
+//!
+//! [`Session::exec()`](crate::Session::exec) runs a single command on its
+//! own SSH session channel, without a PTY or shell. There is no prompt to
+//! detect and no echo to strip: stdout and stderr arrive separately and the
+//! server reports the command's exit status (or the signal that killed it).
+//! This suits Linux hosts and any device whose SSH server supports `exec`.
+//!
+//! # Usage
+//!
+//! ```rust,no_run
+//! # async fn example(session: &ferrissh::Session) -> Result<(), ferrissh::Error> {
+//! let result = session.exec("systemctl is-active sshd").await?;
+//! if !result.is_success() {
+//!     eprintln!("exit {:?}: {}", result.exit_status, result.stderr);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use futures_core::Stream;
+use log::debug;
+use russh::client::Msg;
+use russh::{ChannelMsg, Sig};
+
+use super::payload::Payload;
+use crate::error::{ChannelError, Result, TransportError};
+
+/// How an `exec` command ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// The command exited with this status code.
+    Code(u32),
+    /// The command was killed by a signal.
+    Signal {
+        /// Signal name without the `SIG` prefix, e.g. `TERM`.
+        signal: String,
+        /// Whether the process dumped core.
+        core_dumped: bool,
+        /// Error message reported by the server (often empty).
+        message: String,
+    },
+    /// The channel closed without the server reporting a status.
+    Unknown,
+}
+
+impl ExitStatus {
+    /// Whether the command exited with status 0.
+    pub fn success(&self) -> bool {
+        matches!(self, Self::Code(0))
+    }
+
+    /// The exit code, if the command exited normally.
+    pub fn code(&self) -> Option<u32> {
+        match self {
+            Self::Code(code) => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+/// Result of [`Session::exec()`](crate::Session::exec).
+#[derive(Debug, Clone)]
+pub struct ExecResult {
+    /// The command that was executed.
+    pub command: String,
+    /// Everything the command wrote to stdout.
+    pub stdout: Payload,
+    /// Everything the command wrote to stderr.
+    pub stderr: Payload,
+    /// Exit status or signal reported by the server.
+    pub exit_status: ExitStatus,
+    /// Time from opening the channel to the channel closing.
+    pub elapsed: Duration,
+}
+
+impl ExecResult {
+    /// Whether the command exited with status 0.
+    pub fn is_success(&self) -> bool {
+        self.exit_status.success()
+    }
+}
+
+/// A chunk of output from an [`ExecStream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecChunk {
+    /// Data the command wrote to stdout.
+    Stdout(Bytes),
+    /// Data the command wrote to stderr.
+    Stderr(Bytes),
+}
+
+/// Metadata available after an [`ExecStream`] finishes (channel closed).
+#[derive(Debug, Clone)]
+pub struct ExecCompletion {
+    /// Exit status or signal reported by the server.
+    pub exit_status: ExitStatus,
+    /// Time from opening the channel to the channel closing.
+    pub elapsed: Duration,
+}
+
+/// Incremental stream of stdout and stderr chunks from an `exec` command.
+///
+/// Created by [`Session::exec_stream()`](crate::Session::exec_stream).
+/// Mirrors the [`CommandStream`](super::CommandStream) API, but owns its
+/// SSH channel instead of borrowing a PTY channel, so any number can run
+/// alongside each other and alongside PTY channels on the same session.
+pub struct ExecStream {
+    /// The exec channel.
+    channel: russh::Channel<Msg>,
+    /// Maximum time to wait for the next message from the server.
+    timeout: Duration,
+    /// When the channel was opened.
+    start: Instant,
+    /// Exit status or signal, once reported.
+    exit_status: Option<ExitStatus>,
+    /// Whether the stream has finished (channel closed or error).
+    done: bool,
+    /// Completion metadata (populated when the channel closes).
+    completion: Option<ExecCompletion>,
+}
+
+impl ExecStream {
+    /// Create a new `ExecStream` over an exec channel.
+    pub(crate) fn new(channel: russh::Channel<Msg>, timeout: Duration, start: Instant) -> Self {
+        Self {
+            channel,
+            timeout,
+            start,
+            exit_status: None,
+            done: false,
+            completion: None,
+        }
+    }
+
+    /// Get the next chunk of stdout or stderr.
+    ///
+    /// Returns `Ok(Some(chunk))` for each chunk of output, `Ok(None)` once
+    /// the server closes the channel, or `Err` if nothing arrives within the
+    /// timeout, the server rejects the command, or the connection drops.
+    ///
+    /// After `Ok(None)` is returned, call [`completion()`](Self::completion)
+    /// to get the exit status and elapsed time.
+    pub async fn next_chunk(&mut self) -> Result<Option<ExecChunk>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        loop {
+            let msg = match tokio::time::timeout(self.timeout, self.channel.wait()).await {
+                Ok(msg) => msg,
+                Err(_) => {
+                    self.done = true;
+                    return Err(TransportError::Timeout(self.timeout).into());
+                }
+            };
+
+            match msg {
+                Some(ChannelMsg::Data { data }) if !data.is_empty() => {
+                    return Ok(Some(ExecChunk::Stdout(Bytes::copy_from_slice(&data))));
+                }
+                Some(ChannelMsg::ExtendedData { data, ext: 1 }) if !data.is_empty() => {
+                    return Ok(Some(ExecChunk::Stderr(Bytes::copy_from_slice(&data))));
+                }
+                Some(ChannelMsg::ExitStatus { exit_status }) => {
+                    self.exit_status = Some(ExitStatus::Code(exit_status));
+                }
+                Some(ChannelMsg::ExitSignal {
+                    signal_name,
+                    core_dumped,
+                    error_message,
+                    ..
+                }) => {
+                    self.exit_status = Some(ExitStatus::Signal {
+                        signal: signal_name_of(&signal_name),
+                        core_dumped,
+                        message: error_message,
+                    });
+                }
+                Some(ChannelMsg::Failure) => {
+                    self.done = true;
+                    return Err(ChannelError::ExecRequestFailed.into());
+                }
+                Some(ChannelMsg::Close) | None => {
+                    let exit_status = self.exit_status.take().unwrap_or(ExitStatus::Unknown);
+                    debug!("exec: channel closed, {:?}", exit_status);
+                    self.completion = Some(ExecCompletion {
+                        exit_status,
+                        elapsed: self.start.elapsed(),
+                    });
+                    self.done = true;
+                    return Ok(None);
+                }
+                // Success, Eof, WindowAdjusted, other extended data types
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Convert this stream into a [`Stream`].
+    ///
+    /// Note: this consumes `self`, so [`completion()`](Self::completion)
+    /// is not accessible afterward. Use the [`next_chunk()`](Self::next_chunk)
+    /// loop directly if you need the exit status.
+    pub fn into_stream(self) -> impl Stream<Item = Result<ExecChunk>> {
+        futures_util::stream::unfold(self, |mut stream| async move {
+            match stream.next_chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk), stream)),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), stream)),
+            }
+        })
+    }
+
+    /// Get the completion metadata after the stream finishes.
+    ///
+    /// Returns `None` if the stream hasn't finished yet.
+    pub fn completion(&self) -> Option<&ExecCompletion> {
+        self.completion.as_ref()
+    }
+
+    /// Check whether the stream has finished.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Stop reading and close the channel, discarding remaining output.
+    ///
+    /// Unlike [`CommandStream::cancel()`](super::CommandStream::cancel),
+    /// which interrupts the command and waits for the shell's prompt,
+    /// closing the channel is enough here, since nothing else shares it.
+    pub async fn cancel(&mut self) -> Result<()> {
+        if self.done {
+            return Ok(());
+        }
+        let _ = self.channel.close().await;
+        while self.next_chunk().await?.is_some() {}
+        Ok(())
+    }
+}
+
+/// Name of a russh signal without the `SIG` prefix.
+fn signal_name_of(signal: &Sig) -> String {
+    match signal {
+        Sig::ABRT => "ABRT",
+        Sig::ALRM => "ALRM",
+        Sig::FPE => "FPE",
+        Sig::HUP => "HUP",
+        Sig::ILL => "ILL",
+        Sig::INT => "INT",
+        Sig::KILL => "KILL",
+        Sig::PIPE => "PIPE",
+        Sig::QUIT => "QUIT",
+        Sig::SEGV => "SEGV",
+        Sig::TERM => "TERM",
+        Sig::USR1 => "USR1",
+        Sig::Custom(name) => name,
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_status_success_and_code() {
+        assert!(ExitStatus::Code(0).success());
+        assert!(!ExitStatus::Code(2).success());
+        assert_eq!(ExitStatus::Code(2).code(), Some(2));
+
+        let killed = ExitStatus::Signal {
+            signal: "KILL".into(),
+            core_dumped: false,
+            message: String::new(),
+        };
+        assert!(!killed.success());
+        assert_eq!(killed.code(), None);
+        assert!(!ExitStatus::Unknown.success());
+    }
+
+    #[test]
+    fn test_signal_name_of() {
+        assert_eq!(signal_name_of(&Sig::TERM), "TERM");
+        assert_eq!(signal_name_of(&Sig::Custom("USR2".into())), "USR2");
+    }
+}
@@ -9,20 +9,24 @@ use std::future::Future;
 use std::time::{Duration, Instant};
 
 use log::{debug, warn};
+use tokio::sync::broadcast;
 
 use super::Driver;
 use super::SessionState;
 use super::channel::Channel;
 use super::config_session::GenericConfigSession;
 use super::interactive::{InteractiveEvent, InteractiveResult};
+use super::options::CommandOptions;
 use super::privilege::PrivilegeManager;
 use super::response::Response;
 use super::stream::CommandStream;
 use crate::error::{DisconnectReason, DriverError, Result};
 use crate::platform::PlatformDefinition;
-use crate::session::Session;
-use crate::transport::SshTransport;
+use crate::reconnect::ReconnectEvent;
+use crate::session::{RECONNECT_EVENT_CAPACITY, Session};
+use crate::transport;
 use crate::transport::config::SshConfig;
+use crate::transport::{ConnectionInfo, TerminalOptions};
 
 /// Generic driver that works with any platform definition.
 ///
@@ -49,6 +53,9 @@ pub struct GenericDriver {
 
     /// The primary PTY channel (None when disconnected).
     channel: Option<Channel>,
+
+    /// Sender for reconnect progress (outlives individual sessions).
+    reconnect_events: broadcast::Sender<ReconnectEvent>,
 }
 
 impl GenericDriver {
@@ -60,6 +67,7 @@ impl GenericDriver {
             normalize,
             session: None,
             channel: None,
+            reconnect_events: broadcast::channel(RECONNECT_EVENT_CAPACITY).0,
         }
     }
 
@@ -109,7 +117,7 @@ impl GenericDriver {
     /// For vendor-specific features (named sessions, diff), use the vendor's
     /// own session type (e.g., `AristaConfigSession::new(&mut driver, "name")`).
     pub async fn config_session(&mut self) -> Result<GenericConfigSession<'_>> {
-        let channel = self.channel.as_mut().ok_or(DriverError::NotConnected)?;
+        let channel = self.ready_channel().await?;
         GenericConfigSession::new(channel).await
     }
 
@@ -123,6 +131,12 @@ impl GenericDriver {
         self.session.as_ref().map(|s| s.connected_since())
     }
 
+    /// What was learned about the server while connecting (see
+    /// [`Session::connection_info()`]).
+    pub fn connection_info(&self) -> Option<&ConnectionInfo> {
+        self.session.as_ref().map(|s| s.connection_info())
+    }
+
     /// When the last command completed successfully.
     pub fn last_command_at(&self) -> Option<Instant> {
         self.channel.as_ref().and_then(|c| c.last_command_at())
@@ -146,6 +160,38 @@ impl GenericDriver {
         }
     }
 
+    /// Subscribe to reconnect progress.
+    ///
+    /// Can be called before [`open()`](Driver::open); the subscription
+    /// carries across reconnects. Only produces events when a
+    /// [`ReconnectPolicy`](crate::ReconnectPolicy) is configured or
+    /// [`reconnect()`](Self::reconnect) is called.
+    pub fn reconnect_events(&self) -> broadcast::Receiver<ReconnectEvent> {
+        self.reconnect_events.subscribe()
+    }
+
+    /// Reconnect now, reopening the channel at its previous privilege level.
+    ///
+    /// With a reconnect policy configured this happens automatically on the
+    /// next command after a disconnect; this method is for reconnecting on
+    /// demand, or without a policy (using the default
+    /// [`ReconnectPolicy`](crate::ReconnectPolicy) schedule).
+    pub async fn reconnect(&mut self) -> Result<()> {
+        let channel = self.channel.as_mut().ok_or(DriverError::NotConnected)?;
+        let result = channel.reconnect().await;
+        self.session = Some(channel.session().clone());
+        result
+    }
+
+    /// The primary channel, reconnected first if the connection was lost
+    /// and a reconnect policy is configured.
+    async fn ready_channel(&mut self) -> Result<&mut Channel> {
+        let channel = self.channel.as_mut().ok_or(DriverError::NotConnected)?;
+        let result = channel.ensure_connected().await;
+        self.session = Some(channel.session().clone());
+        result.map(|()| channel)
+    }
+
     /// Get a reference to the underlying session.
     ///
     /// Returns `None` if the driver is not connected.
@@ -191,6 +237,14 @@ impl GenericDriver {
         session.open_channel().await
     }
 
+    /// Open an additional PTY channel with its own terminal settings.
+    ///
+    /// See [`Session::open_channel_with()`].
+    pub async fn open_channel_with(&self, terminal: TerminalOptions) -> Result<Channel> {
+        let session = self.session.as_ref().ok_or(DriverError::NotConnected)?;
+        session.open_channel_with(terminal).await
+    }
+
     /// Decompose the driver into its session and channel components.
     ///
     /// Returns `None` if the driver is not connected.
@@ -221,8 +275,13 @@ impl Driver for GenericDriver {
         );
 
         // Connect
-        let transport = SshTransport::connect(self.ssh_config.clone()).await?;
-        let session = Session::new(transport, self.platform.clone(), self.ssh_config.clone());
+        let transport = transport::connect(&self.ssh_config, &self.platform).await?;
+        let session = Session::new(
+            transport,
+            self.platform.clone(),
+            self.ssh_config.clone(),
+            self.reconnect_events.clone(),
+        );
 
         // Open a channel (waits for prompt, runs on_open, determines privilege)
         let mut channel = match session.open_channel().await {
@@ -266,28 +325,37 @@ impl Driver for GenericDriver {
     }
 
     async fn send_command(&mut self, command: &str) -> Result<Response> {
-        let channel = self.channel.as_mut().ok_or(DriverError::NotConnected)?;
-        channel.send_command(command).await
+        self.ready_channel().await?.send_command(command).await
+    }
+
+    async fn send_command_with(
+        &mut self,
+        command: &str,
+        options: &CommandOptions,
+    ) -> Result<Response> {
+        self.ready_channel()
+            .await?
+            .send_command_with(command, options)
+            .await
     }
 
     async fn send_command_stream<'a>(&'a mut self, command: &str) -> Result<CommandStream<'a>> {
-        let channel = self.channel.as_mut().ok_or(DriverError::NotConnected)?;
-        channel.send_command_stream(command).await
+        self.ready_channel()
+            .await?
+            .send_command_stream(command)
+            .await
     }
 
     async fn acquire_privilege(&mut self, target: &str) -> Result<()> {
-        let channel = self.channel.as_mut().ok_or(DriverError::NotConnected)?;
-        channel.acquire_privilege(target).await
+        self.ready_channel().await?.acquire_privilege(target).await
     }
 
     async fn send_interactive(&mut self, events: &[InteractiveEvent]) -> Result<InteractiveResult> {
-        let channel = self.channel.as_mut().ok_or(DriverError::NotConnected)?;
-        channel.send_interactive(events).await
+        self.ready_channel().await?.send_interactive(events).await
     }
 
     async fn send_config(&mut self, commands: &[&str]) -> Result<Vec<Response>> {
-        let channel = self.channel.as_mut().ok_or(DriverError::NotConnected)?;
-        channel.send_config(commands).await
+        self.ready_channel().await?.send_config(commands).await
     }
 
     fn is_open(&self) -> bool {
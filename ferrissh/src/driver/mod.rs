@@ -6,8 +6,10 @@
 mod builder;
 pub mod channel;
 pub mod config_session;
+pub mod exec;
 mod generic;
 mod interactive;
+mod options;
 pub mod payload;
 mod privilege;
 pub(crate) mod response;
@@ -19,9 +21,11 @@ pub use config_session::{
     ConfigSession, ConfirmableCommit, Diffable, GenericConfigSession, NamedSession, Validatable,
     ValidationResult,
 };
+pub use exec::{ExecChunk, ExecCompletion, ExecResult, ExecStream, ExitStatus};
 pub use generic::GenericDriver;
 // SessionState is defined in this module and re-exported here
 pub use interactive::{InteractiveBuilder, InteractiveEvent, InteractiveResult, InteractiveStep};
+pub use options::CommandOptions;
 pub use payload::Payload;
 pub use privilege::{PrivilegeLevelsBase, PrivilegeManager};
 pub use response::Response;
@@ -29,7 +33,7 @@ pub use stream::{CommandStream, StreamCompletion};
 
 use std::future::Future;
 
-use crate::error::Result;
+use crate::error::{DriverError, Result};
 
 /// The state of a driver's SSH session.
 ///
@@ -62,6 +66,25 @@ pub trait Driver: Send + Sync {
     /// Send a command and wait for the prompt.
     fn send_command(&mut self, command: &str) -> impl Future<Output = Result<Response>> + Send;
 
+    /// Send a command with its own timeout, patterns or output handling
+    /// (see [`CommandOptions`]).
+    ///
+    /// The default fails with [`DriverError::InvalidConfig`] rather than
+    /// run the command without its options; drivers that support
+    /// per-command options override it.
+    fn send_command_with(
+        &mut self,
+        _command: &str,
+        _options: &CommandOptions,
+    ) -> impl Future<Output = Result<Response>> + Send {
+        async {
+            Err(DriverError::InvalidConfig {
+                message: "this driver does not support per-command options".to_string(),
+            }
+            .into())
+        }
+    }
+
     /// Send a command and return a streaming iterator over output chunks.
     ///
     /// Unlike [`send_command()`](Self::send_command), this returns a
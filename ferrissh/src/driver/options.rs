@@ -0,0 +1,131 @@
+//! Per-command options for [`Channel::send_command_with()`](super::Channel::send_command_with).
+//!
+//! Most commands are fine with the channel's settings. Some are not:
+//! a software install needs a ten-minute timeout, and `reload` stops at a
+//! confirmation rather than a prompt. [`CommandOptions`] overrides the
+//! channel's settings for one command.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::channel::PromptMatcher;
+
+/// Settings for a single command, overriding the channel's.
+///
+/// Every option defaults to the channel's behavior.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use ferrissh::CommandOptions;
+/// use regex::bytes::Regex;
+///
+/// // A software install: a long timeout.
+/// let install = CommandOptions::new().timeout(Duration::from_secs(600));
+///
+/// // `reload` ends at its confirmation, not at a prompt.
+/// let reload = CommandOptions::new().expect(Regex::new(r"\[confirm\]\s*$").unwrap());
+/// ```
+#[derive(Clone)]
+pub struct CommandOptions {
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) patterns: Vec<Arc<dyn PromptMatcher>>,
+    pub(crate) replace_patterns: bool,
+    pub(crate) failed_when_contains: Option<Vec<String>>,
+    pub(crate) normalize: Option<bool>,
+    pub(crate) update_privilege: bool,
+}
+
+impl CommandOptions {
+    /// Options that change nothing.
+    pub fn new() -> Self {
+        Self {
+            timeout: None,
+            patterns: Vec::new(),
+            replace_patterns: false,
+            failed_when_contains: None,
+            normalize: None,
+            update_privilege: true,
+        }
+    }
+
+    /// Wait up to `timeout` for the command to finish, instead of the
+    /// channel's timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Also end the command when `pattern` matches, such as a
+    /// confirmation the command stops at.
+    ///
+    /// Tried before the channel's prompt patterns. Output matched by it
+    /// is not a prompt, so the privilege level is left alone.
+    pub fn expect(mut self, pattern: impl PromptMatcher + 'static) -> Self {
+        self.patterns.push(Arc::new(pattern));
+        self
+    }
+
+    /// End the command only when `pattern` matches, ignoring the channel's
+    /// prompt patterns.
+    ///
+    /// Call again (or use [`expect()`](Self::expect)) to add more.
+    pub fn expect_only(mut self, pattern: impl PromptMatcher + 'static) -> Self {
+        self.replace_patterns = true;
+        self.expect(pattern)
+    }
+
+    /// Mark the response failed if its output contains any of `patterns`,
+    /// instead of the platform's `failed_when_contains`.
+    ///
+    /// An empty list never fails the response.
+    pub fn failed_when_contains<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.failed_when_contains = Some(patterns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Normalize the output (strip the echo and prompt, fix line endings
+    /// and apply the platform's post-processing), or return it raw.
+    ///
+    /// Default: the channel's setting.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = Some(normalize);
+        self
+    }
+
+    /// Update the channel's privilege level from the prompt the command
+    /// ends at (default: `true`).
+    ///
+    /// Turn off for a command whose output may end like another level's
+    /// prompt.
+    pub fn update_privilege(mut self, update: bool) -> Self {
+        self.update_privilege = update;
+        self
+    }
+}
+
+impl Default for CommandOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for CommandOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CommandOptions")
+            .field("timeout", &self.timeout)
+            .field("patterns", &self.patterns.len())
+            .field("replace_patterns", &self.replace_patterns)
+            .field("failed_when_contains", &self.failed_when_contains)
+            .field("normalize", &self.normalize)
+            .field("update_privilege", &self.update_privilege)
+            .finish()
+    }
+}
@@ -21,14 +21,15 @@
 //! # }
 //! ```
 
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use bytes::{Bytes, BytesMut};
 use futures_core::Stream;
-use regex::bytes::Regex;
 
 use super::channel::Channel;
-use crate::error::Result;
+use crate::channel::PromptMatcher;
+use crate::error::{ChannelError, Error, Result};
 use crate::platform::StreamProcessor;
 
 /// Metadata available after a [`CommandStream`] finishes (prompt detected).
@@ -44,7 +45,9 @@ pub struct StreamCompletion {
 
 /// Configuration snapshot used to construct a [`CommandStream`].
 pub(crate) struct StreamConfig {
-    pub prompt_patterns: Vec<Regex>,
+    pub prompt_matchers: Vec<Arc<dyn PromptMatcher>>,
+    pub fallback_matchers: Vec<Arc<dyn PromptMatcher>>,
+    pub fallback_quiet: Duration,
     pub search_depth: usize,
     pub timeout: Duration,
     pub normalize: bool,
@@ -76,8 +79,14 @@ pub struct CommandStream<'a> {
     failed_when_contains: Vec<String>,
     /// How many bytes from the end to hold back for prompt detection.
     search_depth: usize,
-    /// Individual prompt patterns (avoids combined-NFA memory overhead).
-    prompt_patterns: Vec<Regex>,
+    /// Prompt matchers, tried in order.
+    prompt_matchers: Vec<Arc<dyn PromptMatcher>>,
+    /// Matchers trusted only once output stops for `fallback_quiet`.
+    fallback_matchers: Vec<Arc<dyn PromptMatcher>>,
+    /// How long output must stop for to accept a fallback match.
+    fallback_quiet: Duration,
+    /// Whether the held-back tail ends at a fallback match.
+    settling: bool,
     /// Timeout for each read operation.
     timeout: Duration,
     /// When the command was sent.
@@ -115,7 +124,10 @@ impl<'a> CommandStream<'a> {
             processor: config.processor,
             failed_when_contains: config.failed_when_contains,
             search_depth: config.search_depth,
-            prompt_patterns: config.prompt_patterns,
+            prompt_matchers: config.prompt_matchers,
+            fallback_matchers: config.fallback_matchers,
+            fallback_quiet: config.fallback_quiet,
+            settling: false,
             timeout: config.timeout,
             start,
             done: false,
@@ -139,9 +151,28 @@ impl<'a> CommandStream<'a> {
         }
 
         loop {
-            // 1. Read ANSI-stripped data from PTY
-            let raw = match self.channel.pty().read_chunk(self.timeout).await {
+            // 1. Read ANSI-stripped data from PTY. After a fallback match,
+            //    the output going quiet confirms it as the prompt.
+            let timeout = if self.settling {
+                self.fallback_quiet.min(self.timeout)
+            } else {
+                self.timeout
+            };
+            let raw = match self.channel.pty().read_chunk(timeout).await {
                 Ok(chunk) => chunk,
+                Err(Error::Channel(ChannelError::PatternTimeout(_))) if self.settling => {
+                    let tail_start = self.holdback.len().saturating_sub(self.search_depth);
+                    let tail = &self.holdback[tail_start..];
+                    if let Some(m) = self
+                        .fallback_matchers
+                        .iter()
+                        .find_map(|m| m.find_prompt(tail))
+                    {
+                        return Ok(self.finish(tail_start + m.start));
+                    }
+                    self.settling = false;
+                    continue;
+                }
                 Err(err) => {
                     self.done = true;
                     self.channel.handle_error(&err);
@@ -167,60 +198,30 @@ impl<'a> CommandStream<'a> {
             // 3. Check tail for prompt pattern (on un-normalized data)
             let tail_start = self.holdback.len().saturating_sub(self.search_depth);
             let tail = &self.holdback[tail_start..];
-            let prompt_match = self.prompt_patterns.iter().find_map(|p| p.find(tail));
+            let prompt_match = self
+                .prompt_matchers
+                .iter()
+                .find_map(|m| m.find_prompt(tail));
             if let Some(m) = prompt_match {
-                // PROMPT FOUND — finalize
-                //
-                // The regex may match only part of the prompt line (e.g., `$ ` at the end
-                // of `[user@host ~]$ `). We need to find the start of the LINE containing
-                // the match so the entire prompt decoration is excluded from output.
-                let match_abs_pos = tail_start + m.start();
-                let prompt_line_start =
-                    match memchr::memrchr(b'\n', &self.holdback[..match_abs_pos]) {
-                        Some(nl_pos) => nl_pos + 1,
-                        None => 0, // prompt is on the first (or only) line
-                    };
-
-                let prompt = String::from_utf8_lossy(&self.holdback[prompt_line_start..])
-                    .trim()
-                    .to_string();
-                let mut output = self.holdback.split_to(prompt_line_start);
-
-                // Strip trailing \n that separated output from the prompt line
-                super::channel::strip_trailing_prompt_in_place(&mut output);
-
-                // Normalize and vendor-process
-                if self.normalize {
-                    super::channel::normalize_linefeeds_in_place(&mut output);
-                }
-                if let Some(ref mut proc) = self.processor {
-                    proc.process_lines(&mut output);
-                }
+                return Ok(self.finish(tail_start + m.start));
+            }
 
-                // Check failure patterns (with overlap)
-                if self.found_failure.is_none() {
-                    self.found_failure = self.check_failure_patterns(&output);
+            // 4. PAGER PROMPT — answer it and leave it out of the output
+            if let Some(start) = self.channel.pty().find_pager_prompt(tail) {
+                self.holdback.truncate(tail_start + start);
+                if let Err(err) = self.channel.pty().answer_pager().await {
+                    self.done = true;
+                    self.channel.handle_error(&err);
+                    self.channel.mark_command_complete();
+                    return Err(err);
                 }
-
-                // Update channel privilege state
-                self.channel.update_privilege_from_prompt(&prompt);
-
-                self.completion = Some(StreamCompletion {
-                    prompt,
-                    elapsed: self.start.elapsed(),
-                    failure_pattern: self.found_failure.clone(),
-                });
-                self.done = true;
-                self.channel.mark_command_complete();
-
-                return if output.is_empty() {
-                    Ok(None)
-                } else {
-                    Ok(Some(output.freeze()))
-                };
+                continue;
             }
 
-            // 4. NO PROMPT — emit safe prefix (only complete lines)
+            // 5. FALLBACK PROMPT — wait for the output to stop before trusting it
+            self.settling = self.fallback_matchers.iter().any(|m| m.is_match(tail));
+
+            // 6. NO PROMPT — emit safe prefix (only complete lines)
             if self.holdback.len() <= self.search_depth {
                 continue; // not enough data to safely emit
             }
@@ -254,6 +255,54 @@ impl<'a> CommandStream<'a> {
         }
     }
 
+    /// Finish the stream at the prompt matched at `match_abs_pos`,
+    /// returning the output before it.
+    fn finish(&mut self, match_abs_pos: usize) -> Option<Bytes> {
+        // PROMPT FOUND — finalize
+        //
+        // The regex may match only part of the prompt line (e.g., `$ ` at the end
+        // of `[user@host ~]$ `). We need to find the start of the LINE containing
+        // the match so the entire prompt decoration is excluded from output.
+        let prompt_line_start = match memchr::memrchr(b'\n', &self.holdback[..match_abs_pos]) {
+            Some(nl_pos) => nl_pos + 1,
+            None => 0, // prompt is on the first (or only) line
+        };
+
+        let prompt = String::from_utf8_lossy(&self.holdback[prompt_line_start..])
+            .trim()
+            .to_string();
+        let mut output = self.holdback.split_to(prompt_line_start);
+
+        // Strip trailing \n that separated output from the prompt line
+        super::channel::strip_trailing_prompt_in_place(&mut output);
+
+        // Normalize and vendor-process
+        if self.normalize {
+            super::channel::normalize_linefeeds_in_place(&mut output);
+        }
+        if let Some(ref mut proc) = self.processor {
+            proc.process_lines(&mut output);
+        }
+
+        // Check failure patterns (with overlap)
+        if self.found_failure.is_none() {
+            self.found_failure = self.check_failure_patterns(&output);
+        }
+
+        // Update channel privilege state
+        self.channel.update_privilege_from_prompt(&prompt);
+
+        self.completion = Some(StreamCompletion {
+            prompt,
+            elapsed: self.start.elapsed(),
+            failure_pattern: self.found_failure.clone(),
+        });
+        self.done = true;
+        self.channel.mark_command_complete();
+
+        (!output.is_empty()).then(|| output.freeze())
+    }
+
     /// Convert this stream into a [`Stream`].
     ///
     /// Note: this consumes `self`, so [`completion()`](Self::completion)
@@ -281,15 +330,43 @@ impl<'a> CommandStream<'a> {
         self.done
     }
 
-    /// Drain the stream to completion, discarding remaining output.
+    /// Stop the command and resynchronise on the prompt.
     ///
-    /// Use this to abandon processing while keeping the channel usable
-    /// for subsequent commands. Without calling this (or draining via
+    /// Interrupts the remote command with Ctrl-C (see
+    /// [`Channel::interrupt()`]), discards its remaining output and waits
+    /// for the prompt, leaving the channel ready for the next command.
+    /// Without calling this (or draining via
     /// [`next_chunk()`](Self::next_chunk)), the channel will reject
     /// further commands with [`DriverError::StreamNotDrained`](crate::error::DriverError::StreamNotDrained).
+    ///
+    /// Does nothing if the stream has already finished. A cancelled stream
+    /// has no [`completion()`](Self::completion).
     pub async fn cancel(&mut self) -> Result<()> {
-        while self.next_chunk().await?.is_some() {}
-        Ok(())
+        if self.done {
+            return Ok(());
+        }
+        self.done = true;
+
+        // The command may have finished already, with its prompt still
+        // held back: interrupting now would make the shell print another.
+        // A fallback match may be output that merely looks like a prompt,
+        // so only the trusted matchers count here.
+        let tail_start = self.holdback.len().saturating_sub(self.search_depth);
+        let tail = &self.holdback[tail_start..];
+        if self.prompt_matchers.iter().any(|m| m.is_match(tail)) {
+            let prompt = String::from_utf8_lossy(tail)
+                .lines()
+                .last()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            self.channel.update_privilege_from_prompt(&prompt);
+            self.channel.mark_command_complete();
+            return Ok(());
+        }
+
+        self.holdback.clear();
+        self.channel.interrupt().await
     }
 
     /// Check failure patterns against a chunk, prepending the overlap tail
@@ -33,20 +33,37 @@ pub enum TransportError {
         port: u16,
         #[source]
         source: io::Error,
+        /// Each address tried, with the error connecting to it. Empty if
+        /// the failure came before any address was tried (e.g. name
+        /// resolution) or on a tunnelled hop.
+        attempts: Vec<(std::net::SocketAddr, io::Error)>,
     },
 
     /// SSH handshake or protocol error
     #[error("SSH error: {0}")]
     Ssh(#[from] russh::Error),
 
-    /// Authentication failed
-    #[error("Authentication failed for user '{user}'")]
-    AuthenticationFailed { user: String },
+    /// Authentication failed after every configured method was tried
+    #[error("Authentication failed for user '{user}' (tried: {})", .methods.join(", "))]
+    AuthenticationFailed {
+        user: String,
+        /// Methods attempted, in order (e.g. `["publickey", "password"]`).
+        /// Methods the server did not offer are not included.
+        methods: Vec<String>,
+    },
 
     /// SSH key error
     #[error("SSH key error: {0}")]
     Key(String),
 
+    /// Invalid algorithm preferences (unknown name or empty list)
+    #[error("Algorithm preferences error: {0}")]
+    Algorithms(String),
+
+    /// SSH agent error (socket unreachable, protocol failure, signing refused)
+    #[error("SSH agent error: {0}")]
+    Agent(String),
+
     /// Connection was closed unexpectedly
     #[error("Connection disconnected")]
     Disconnected,
@@ -69,10 +86,66 @@ pub enum TransportError {
         line: usize,
     },
 
+    /// Host key rejected by a pinned fingerprint list or a custom verifier
+    #[error("Host key for {host}:{port} ({fingerprint}) rejected: {reason}")]
+    HostKeyRejected {
+        host: String,
+        port: u16,
+        fingerprint: String,
+        reason: String,
+    },
+
     /// Error accessing or parsing known_hosts file
     #[error("Known hosts error: {0}")]
     KnownHosts(String),
 
+    /// Failed to connect to the proxy itself
+    #[error("Connection failed to proxy {host}:{port}: {source}")]
+    ProxyConnectionFailed {
+        host: String,
+        port: u16,
+        #[source]
+        source: io::Error,
+    },
+
+    /// Proxy rejected the supplied credentials, or requires credentials
+    /// that were not supplied
+    #[error("Proxy authentication failed at {host}:{port}")]
+    ProxyAuthenticationFailed { host: String, port: u16 },
+
+    /// Proxy refused the tunnel or sent a malformed response
+    #[error("Proxy {host}:{port} error: {message}")]
+    Proxy {
+        host: String,
+        port: u16,
+        message: String,
+    },
+
+    /// Connecting or authenticating to a jump host failed
+    #[error("Jump host {host}:{port}: {source}")]
+    JumpHost {
+        host: String,
+        port: u16,
+        #[source]
+        source: Box<TransportError>,
+    },
+
+    /// Could not listen on the local side of a port forward
+    #[error("Failed to listen on {address} for port forwarding: {source}")]
+    ForwardBind {
+        address: String,
+        #[source]
+        source: io::Error,
+    },
+
+    /// Server refused a port forwarding request
+    #[error("Port forwarding to {target} refused: {reason}")]
+    ForwardRejected { target: String, reason: String },
+
+    /// The connection's protocol cannot do this (e.g. SFTP over Telnet)
+    #[error("{feature} is not supported over {protocol}")]
+    Unsupported { feature: String, protocol: String },
+
     /// I/O error
     #[error("I/O error: {0}")]
     Io(#[from] io::Error),
@@ -89,6 +162,10 @@ pub enum ChannelError {
     #[error("Failed to request shell")]
     ShellRequestFailed,
 
+    /// Server refused to run an `exec` command
+    #[error("Exec request rejected by server")]
+    ExecRequestFailed,
+
     /// Pattern matching timed out
     #[error("Pattern not found within {0:?}")]
     PatternTimeout(std::time::Duration),
@@ -108,6 +185,55 @@ pub enum ChannelError {
     /// Invalid regex pattern
     #[error("Invalid regex pattern: {0}")]
     InvalidPattern(#[from] regex::Error),
+
+    /// `send_control()` was given a key with no control character
+    #[error("No control character for {0:?}")]
+    InvalidControlKey(char),
+
+    /// A subsystem (e.g. `sftp`) was rejected or failed to start
+    #[error("Subsystem '{name}' failed: {message}")]
+    SubsystemFailed { name: String, message: String },
+
+    /// The server rejected a file transfer operation
+    #[error("File transfer error on '{path}': {message}")]
+    Transfer { path: String, message: String },
+
+    /// Reading or writing a local file during a transfer failed
+    #[error("Local file error on '{}': {source}", .path.display())]
+    LocalIo {
+        path: std::path::PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// The checksum of a transferred file differs between the two sides
+    #[error("Checksum mismatch for '{path}': local {local}, remote {remote}")]
+    ChecksumMismatch {
+        path: String,
+        local: String,
+        remote: String,
+    },
+
+    /// The remote SCP process reported an error
+    #[error("SCP error from remote: {message}")]
+    ScpRemote { message: String },
+
+    /// The remote SCP process sent something the protocol does not allow
+    #[error("SCP protocol error: {message}")]
+    ScpProtocol { message: String },
+
+    /// A NETCONF operation failed with one or more `<rpc-error>`s
+    #[error(
+        "NETCONF rpc-error: {}",
+        .errors.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+    )]
+    NetconfRpc {
+        errors: Vec<crate::netconf::RpcError>,
+    },
+
+    /// The NETCONF server sent something the protocol does not allow
+    #[error("NETCONF protocol error: {message}")]
+    NetconfProtocol { message: String },
 }
 
 /// Driver layer errors (command execution, privilege escalation).
@@ -146,6 +272,10 @@ pub enum DriverError {
         "Channel has unread stream data — previous CommandStream was dropped before the prompt was detected"
     )]
     StreamNotDrained,
+
+    /// Automatic reconnect gave up
+    #[error("Reconnect failed after {attempts} attempt(s): {message}")]
+    ReconnectFailed { attempts: u32, message: String },
 }
 
 /// Platform/vendor definition errors.
@@ -168,6 +298,12 @@ pub enum DisconnectReason {
     ServerDisconnect { message: String },
     /// Connection lost due to transport error (keepalive timeout, I/O error, etc.).
     TransportError(String),
+    /// The connection to a jump host was lost, taking the tunnel down with it.
+    JumpHost {
+        host: String,
+        port: u16,
+        reason: Box<DisconnectReason>,
+    },
 }
 
 /// Result type alias using ferrissh's Error.
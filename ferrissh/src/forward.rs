@@ -0,0 +1,412 @@
+//! TCP port forwarding over an established session.
+//!
+//! [`Session::forward_local()`](crate::Session::forward_local) listens on a
+//! local address and tunnels each accepted connection to a host and port as
+//! seen from the device, over a `direct-tcpip` channel — NETCONF on 830, a
+//! web UI, or a gNMI port behind the management interface.
+//! [`Session::forward_remote()`](crate::Session::forward_remote) does the
+//! reverse: the device listens (`tcpip-forward`) and each connection it
+//! reports is relayed to a local target.
+//!
+//! Both return a handle; dropping it stops forwarding and closes the
+//! connections it carries. Forwards belong to one SSH connection: they also
+//! stop when the session disconnects, and are not re-established by a
+//! reconnect.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # async fn example(session: &ferrissh::Session) -> Result<(), ferrissh::Error> {
+//! // NETCONF on the device, reachable locally on an ephemeral port.
+//! let netconf = session.forward_local("127.0.0.1:0", "127.0.0.1", 830).await?;
+//! println!("NETCONF at {}", netconf.local_addr());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::{debug, warn};
+use russh::Channel;
+use russh::client::Msg;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::{JoinHandle, JoinSet};
+
+use crate::error::{DisconnectReason, Result, TransportError};
+use crate::session::Session;
+
+/// Pause after a failed `accept()`, doubled on each failure in a row, so a
+/// lasting error (such as running out of file descriptors) doesn't spin.
+const ACCEPT_BACKOFF_MIN: Duration = Duration::from_millis(10);
+
+/// Longest pause between failed `accept()`s.
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(1);
+
+/// A local port forward (`direct-tcpip`), active until dropped.
+///
+/// Created by [`Session::forward_local()`](crate::Session::forward_local).
+#[derive(Debug)]
+pub struct LocalForward {
+    /// Address the local listener is bound to.
+    local_addr: SocketAddr,
+    /// Host connections are forwarded to, as seen from the device.
+    remote_host: String,
+    /// Port connections are forwarded to.
+    remote_port: u16,
+    /// Accept loop; owns the listener and every open connection.
+    task: JoinHandle<()>,
+}
+
+impl LocalForward {
+    /// Bind `bind_addr` and start forwarding to `remote_host:remote_port`.
+    pub(crate) async fn start(
+        session: &Session,
+        bind_addr: &str,
+        remote_host: &str,
+        remote_port: u16,
+    ) -> Result<Self> {
+        let bind_error = |source| TransportError::ForwardBind {
+            address: bind_addr.to_string(),
+            source,
+        };
+        let listener = TcpListener::bind(bind_addr).await.map_err(bind_error)?;
+        let local_addr = listener.local_addr().map_err(bind_error)?;
+        debug!(
+            "forwarding {} to {}:{}",
+            local_addr, remote_host, remote_port
+        );
+
+        let task = tokio::spawn(accept_local(
+            listener,
+            session.clone(),
+            remote_host.to_string(),
+            remote_port,
+        ));
+
+        Ok(Self {
+            local_addr,
+            remote_host: remote_host.to_string(),
+            remote_port,
+            task,
+        })
+    }
+
+    /// The local address accepting connections (useful when binding port 0).
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// The host connections are forwarded to, as seen from the device.
+    pub fn remote_host(&self) -> &str {
+        &self.remote_host
+    }
+
+    /// The port connections are forwarded to.
+    pub fn remote_port(&self) -> u16 {
+        self.remote_port
+    }
+
+    /// Whether the forward is still accepting connections.
+    ///
+    /// Becomes `false` once the session disconnects.
+    pub fn is_active(&self) -> bool {
+        !self.task.is_finished()
+    }
+}
+
+impl Drop for LocalForward {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Accept local connections until the session disconnects.
+async fn accept_local(listener: TcpListener, session: Session, host: String, port: u16) {
+    let mut disconnect = session.disconnect_tx().subscribe();
+    let mut connections = JoinSet::new();
+    let mut backoff = ACCEPT_BACKOFF_MIN;
+
+    loop {
+        let failed = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    backoff = ACCEPT_BACKOFF_MIN;
+                    connections.spawn(tunnel_local(session.clone(), stream, peer, host.clone(), port));
+                    false
+                }
+                Err(e) => {
+                    warn!("forward: accept failed, retrying in {:?}: {}", backoff, e);
+                    true
+                }
+            },
+            Some(_) = connections.join_next(), if !connections.is_empty() => false,
+            _ = disconnect.wait_for(Option::is_some) => break,
+        };
+        if failed {
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(ACCEPT_BACKOFF_MAX);
+        }
+    }
+    debug!(
+        "forward: session disconnected, stopping forward to {}:{}",
+        host, port
+    );
+}
+
+/// Carry one local connection over a `direct-tcpip` channel.
+async fn tunnel_local(
+    session: Session,
+    mut stream: TcpStream,
+    peer: SocketAddr,
+    host: String,
+    port: u16,
+) {
+    let Ok(transport) = session.ssh_transport("port forwarding") else {
+        return;
+    };
+    let channel = match transport.open_direct_tcpip(&host, port, peer).await {
+        Ok(channel) => channel,
+        Err(e) => {
+            warn!("forward: {}:{} refused for {}: {}", host, port, peer, e);
+            return;
+        }
+    };
+    let mut remote = channel.into_stream();
+    if let Err(e) = tokio::io::copy_bidirectional(&mut stream, &mut remote).await {
+        debug!("forward: connection from {} ended: {}", peer, e);
+    }
+}
+
+/// A remote port forward (`tcpip-forward`), active until dropped.
+///
+/// Created by [`Session::forward_remote()`](crate::Session::forward_remote).
+/// Dropping it cancels the forward on the server in the background; call
+/// [`cancel()`](Self::cancel) to wait for the server to confirm.
+#[derive(Debug)]
+pub struct RemoteForward {
+    /// The session the forward was requested on.
+    session: Session,
+    /// Address the server listens on.
+    bind_addr: String,
+    /// Port the server listens on (allocated by the server if 0 was asked).
+    bind_port: u16,
+    /// Tells open connections to close.
+    stop: watch::Sender<bool>,
+    /// Whether the forward has already been cancelled.
+    cancelled: bool,
+}
+
+impl RemoteForward {
+    /// Ask the server to listen and register where its connections go.
+    pub(crate) async fn start(
+        session: &Session,
+        bind_addr: &str,
+        bind_port: u16,
+        local_host: &str,
+        local_port: u16,
+    ) -> Result<Self> {
+        let transport = session.ssh_transport("port forwarding")?;
+        let bound = transport
+            .request_remote_forward(bind_addr, bind_port.into())
+            .await?;
+        let bind_port = u16::try_from(bound).unwrap_or(bind_port);
+        debug!(
+            "server forwarding {}:{} to {}:{}",
+            bind_addr, bind_port, local_host, local_port
+        );
+
+        let (stop, stopped) = watch::channel(false);
+        transport.remote_forwards().insert(
+            bind_addr,
+            bind_port,
+            RemoteTarget {
+                host: local_host.to_string(),
+                port: local_port,
+                stopped,
+            },
+        );
+
+        Ok(Self {
+            session: session.clone(),
+            bind_addr: bind_addr.to_string(),
+            bind_port,
+            stop,
+            cancelled: false,
+        })
+    }
+
+    /// The address the server listens on.
+    pub fn bind_addr(&self) -> &str {
+        &self.bind_addr
+    }
+
+    /// The port the server listens on, as allocated if 0 was requested.
+    pub fn bind_port(&self) -> u16 {
+        self.bind_port
+    }
+
+    /// Whether the forward is still in place.
+    pub fn is_active(&self) -> bool {
+        !self.cancelled && self.session.is_alive()
+    }
+
+    /// Cancel the forward and wait for the server to stop listening.
+    pub async fn cancel(mut self) -> Result<()> {
+        self.unregister();
+        self.session
+            .ssh_transport("port forwarding")?
+            .cancel_remote_forward(&self.bind_addr, self.bind_port.into())
+            .await
+    }
+
+    /// Stop routing new connections and close the open ones.
+    fn unregister(&mut self) {
+        self.cancelled = true;
+        if let Ok(transport) = self.session.ssh_transport("port forwarding") {
+            transport
+                .remote_forwards()
+                .remove(&self.bind_addr, self.bind_port);
+        }
+        let _ = self.stop.send(true);
+    }
+}
+
+impl Drop for RemoteForward {
+    fn drop(&mut self) {
+        if self.cancelled {
+            return;
+        }
+        self.unregister();
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let session = self.session.clone();
+            let (addr, port) = (self.bind_addr.clone(), self.bind_port);
+            runtime.spawn(async move {
+                let Ok(transport) = session.ssh_transport("port forwarding") else {
+                    return;
+                };
+                if let Err(e) = transport.cancel_remote_forward(&addr, port.into()).await {
+                    debug!("forward: cancelling {}:{} failed: {}", addr, port, e);
+                }
+            });
+        }
+    }
+}
+
+/// Where connections from one remote forward are relayed to.
+#[derive(Clone)]
+pub(crate) struct RemoteTarget {
+    host: String,
+    port: u16,
+    /// Flips to `true` when the forward is cancelled.
+    stopped: watch::Receiver<bool>,
+}
+
+/// Active remote forwards of one connection, keyed by the address and
+/// port the server listens on.
+#[derive(Default)]
+pub(crate) struct RemoteForwards {
+    targets: Mutex<HashMap<(String, u16), RemoteTarget>>,
+}
+
+impl RemoteForwards {
+    fn insert(&self, addr: &str, port: u16, target: RemoteTarget) {
+        self.targets
+            .lock()
+            .unwrap()
+            .insert((addr.to_string(), port), target);
+    }
+
+    fn remove(&self, addr: &str, port: u16) {
+        self.targets
+            .lock()
+            .unwrap()
+            .remove(&(addr.to_string(), port));
+    }
+
+    /// Find the forward a `forwarded-tcpip` channel belongs to.
+    ///
+    /// Servers normally echo the requested address, but some report the
+    /// address they actually bound, so fall back to matching the port.
+    fn lookup(&self, addr: &str, port: u32) -> Option<RemoteTarget> {
+        let port = u16::try_from(port).ok()?;
+        let targets = self.targets.lock().unwrap();
+        targets
+            .get(&(addr.to_string(), port))
+            .or_else(|| {
+                targets
+                    .iter()
+                    .find(|((_, p), _)| *p == port)
+                    .map(|(_, target)| target)
+            })
+            .cloned()
+    }
+
+    /// Relay a `forwarded-tcpip` channel to its forward's local target.
+    pub(crate) fn dispatch(
+        &self,
+        channel: Channel<Msg>,
+        addr: &str,
+        port: u32,
+        disconnect: watch::Receiver<Option<DisconnectReason>>,
+    ) {
+        let target = self.lookup(addr, port);
+        tokio::spawn(async move {
+            let Some(mut target) = target else {
+                debug!("forward: no forward registered for {}", port);
+                let _ = channel.close().await;
+                return;
+            };
+            let mut local = match TcpStream::connect((target.host.as_str(), target.port)).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!(
+                        "forward: connecting to {}:{} failed: {}",
+                        target.host, target.port, e
+                    );
+                    let _ = channel.close().await;
+                    return;
+                }
+            };
+
+            let mut disconnect = disconnect;
+            let mut remote = channel.into_stream();
+            tokio::select! {
+                _ = tokio::io::copy_bidirectional(&mut local, &mut remote) => {}
+                _ = target.stopped.wait_for(|stopped| *stopped) => {}
+                _ = disconnect.wait_for(Option::is_some) => {}
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(port: u16) -> RemoteTarget {
+        RemoteTarget {
+            host: "127.0.0.1".into(),
+            port,
+            stopped: watch::channel(false).1,
+        }
+    }
+
+    #[test]
+    fn test_lookup_prefers_exact_address_then_port() {
+        let forwards = RemoteForwards::default();
+        forwards.insert("127.0.0.1", 8080, target(1));
+        forwards.insert("0.0.0.0", 9090, target(2));
+
+        assert_eq!(forwards.lookup("127.0.0.1", 8080).unwrap().port, 1);
+        assert_eq!(forwards.lookup("10.0.0.1", 9090).unwrap().port, 2);
+        assert!(forwards.lookup("127.0.0.1", 7070).is_none());
+        assert!(forwards.lookup("127.0.0.1", 70_000).is_none());
+
+        forwards.remove("127.0.0.1", 8080);
+        assert!(forwards.lookup("127.0.0.1", 8080).is_none());
+    }
+}
@@ -42,21 +42,32 @@
 pub mod channel;
 pub mod driver;
 pub mod error;
+pub mod forward;
+pub mod netconf;
 pub mod platform;
+pub mod reconnect;
 pub mod session;
+pub mod transfer;
 pub mod transport;
 
 // Re-export main types for convenience
 pub use driver::{
-    Channel, ChannelState, CommandStream, ConfigSession, ConfirmableCommit, Diffable, Driver,
-    DriverBuilder, GenericConfigSession, GenericDriver, InteractiveBuilder, InteractiveEvent,
-    InteractiveResult, NamedSession, Payload, Response, SessionState, StreamCompletion,
-    Validatable, ValidationResult,
+    Channel, ChannelState, CommandOptions, CommandStream, ConfigSession, ConfirmableCommit,
+    Diffable, Driver, DriverBuilder, ExecChunk, ExecCompletion, ExecResult, ExecStream, ExitStatus,
+    GenericConfigSession, GenericDriver, InteractiveBuilder, InteractiveEvent, InteractiveResult,
+    NamedSession, Payload, Response, SessionState, StreamCompletion, Validatable, ValidationResult,
 };
 pub use error::{DisconnectReason, Error};
+pub use forward::{LocalForward, RemoteForward};
+pub use netconf::{Datastore, NetconfClient};
 pub use platform::{
     ConfDConfigSession, ConfDJStyleConfigSession, Platform, PlatformDefinition, PrivilegeLevel,
     StreamProcessor,
 };
+pub use reconnect::{ReconnectEvent, ReconnectPolicy};
 pub use session::{Session, SessionBuilder};
-pub use transport::HostKeyVerification;
+pub use transfer::{
+    FileKind, FileStat, ScpClient, SftpClient, SftpEntry, TransferOptions, TransferProgress,
+    TransferReport,
+};
+pub use transport::{ConnectionInfo, HostKeyVerification, JumpHost, Protocol, TerminalOptions};
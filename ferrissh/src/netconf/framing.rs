@@ -0,0 +1,182 @@
+//! NETCONF message framing over SSH (RFC 6242).
+//!
+//! Until both sides have advertised `base:1.1` in their hellos, messages end
+//! with the `]]>]]>` delimiter. Afterwards each message is sent as one or
+//! more `\n#<len>\n` chunks followed by `\n##\n`.
+
+use bytes::{Buf, BytesMut};
+use memchr::memmem;
+
+/// The NETCONF 1.0 end-of-message delimiter.
+const END_OF_MESSAGE: &[u8] = b"]]>]]>";
+
+/// Largest chunk size RFC 6242 allows.
+const MAX_CHUNK_SIZE: u64 = u32::MAX as u64;
+
+/// How messages are delimited on the channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// NETCONF 1.0: messages end with `]]>]]>`.
+    EndOfMessage,
+    /// NETCONF 1.1: messages are sent as length-prefixed chunks.
+    Chunked,
+}
+
+/// Frame `message` for sending.
+pub(crate) fn encode(message: &str, framing: Framing) -> Vec<u8> {
+    match framing {
+        Framing::EndOfMessage => {
+            let mut framed = Vec::with_capacity(message.len() + END_OF_MESSAGE.len());
+            framed.extend_from_slice(message.as_bytes());
+            framed.extend_from_slice(END_OF_MESSAGE);
+            framed
+        }
+        Framing::Chunked => format!("\n#{}\n{}\n##\n", message.len(), message).into_bytes(),
+    }
+}
+
+/// Splits received bytes into messages.
+#[derive(Debug, Default)]
+pub(crate) struct Decoder {
+    buf: BytesMut,
+    /// Chunks of the message being decoded (chunked framing only).
+    message: Vec<u8>,
+}
+
+impl Decoder {
+    /// Append bytes read from the channel.
+    pub fn extend(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Take the next complete message, if one has arrived.
+    ///
+    /// Errors if the bytes do not follow `framing`.
+    pub fn next_message(&mut self, framing: Framing) -> Result<Option<String>, String> {
+        let message = match framing {
+            Framing::EndOfMessage => self.next_end_of_message(),
+            Framing::Chunked => self.next_chunked()?,
+        };
+        message
+            .map(|bytes| String::from_utf8(bytes).map_err(|_| "message is not UTF-8".to_string()))
+            .transpose()
+    }
+
+    fn next_end_of_message(&mut self) -> Option<Vec<u8>> {
+        let end = memmem::find(&self.buf, END_OF_MESSAGE)?;
+        let message = self.buf.split_to(end).to_vec();
+        self.buf.advance(END_OF_MESSAGE.len());
+        Some(message)
+    }
+
+    fn next_chunked(&mut self) -> Result<Option<Vec<u8>>, String> {
+        loop {
+            // Every chunk header is `\n#` followed by a size or `#`, then `\n`.
+            let Some(newline) = self.buf.iter().skip(1).position(|&b| b == b'\n') else {
+                if self.buf.len() > 12 || !b"\n#".starts_with(&self.buf[..self.buf.len().min(2)]) {
+                    return Err(self.header_error());
+                }
+                return Ok(None);
+            };
+            let header_len = newline + 2;
+            let header = &self.buf[..header_len];
+            if !header.starts_with(b"\n#") {
+                return Err(self.header_error());
+            }
+
+            let size = &header[2..header_len - 1];
+            if size == b"#" {
+                self.buf.advance(header_len);
+                return Ok(Some(std::mem::take(&mut self.message)));
+            }
+            let size = std::str::from_utf8(size)
+                .ok()
+                .filter(|s| !s.starts_with('0') && s.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|s| s.parse::<u64>().ok())
+                .filter(|&n| n <= MAX_CHUNK_SIZE)
+                .ok_or_else(|| self.header_error())? as usize;
+
+            if self.buf.len() < header_len + size {
+                return Ok(None);
+            }
+            self.buf.advance(header_len);
+            self.message.extend_from_slice(&self.buf.split_to(size));
+        }
+    }
+
+    fn header_error(&self) -> String {
+        let shown = &self.buf[..self.buf.len().min(16)];
+        format!("invalid chunk header {:?}", String::from_utf8_lossy(shown))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_end_of_message_round_trip() {
+        let mut decoder = Decoder::default();
+        let framed = encode("<hello/>", Framing::EndOfMessage);
+        assert_eq!(framed, b"<hello/>]]>]]>");
+
+        decoder.extend(&framed[..10]);
+        assert_eq!(decoder.next_message(Framing::EndOfMessage), Ok(None));
+        decoder.extend(&framed[10..]);
+        decoder.extend(b"\n<rpc/>]]>]]>");
+        assert_eq!(
+            decoder.next_message(Framing::EndOfMessage),
+            Ok(Some("<hello/>".to_string()))
+        );
+        assert_eq!(
+            decoder.next_message(Framing::EndOfMessage),
+            Ok(Some("\n<rpc/>".to_string()))
+        );
+        assert_eq!(decoder.next_message(Framing::EndOfMessage), Ok(None));
+    }
+
+    #[test]
+    fn test_chunked_round_trip() {
+        let framed = encode("<rpc/>", Framing::Chunked);
+        assert_eq!(framed, b"\n#6\n<rpc/>\n##\n");
+
+        // Fed one byte at a time.
+        let mut decoder = Decoder::default();
+        let mut decoded = None;
+        for b in &framed {
+            assert_eq!(decoded, None);
+            decoder.extend(&[*b]);
+            decoded = decoder.next_message(Framing::Chunked).unwrap();
+        }
+        assert_eq!(decoded.as_deref(), Some("<rpc/>"));
+    }
+
+    #[test]
+    fn test_chunked_multiple_chunks() {
+        let mut decoder = Decoder::default();
+        decoder.extend(b"\n#4\n<rpc\n#17\n message-id=\"1\"/>\n##\n");
+        assert_eq!(
+            decoder.next_message(Framing::Chunked),
+            Ok(Some("<rpc message-id=\"1\"/>".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_chunked_rejects_bad_headers() {
+        for bad in [
+            &b"<rpc/>"[..],
+            b"\n#0\n",
+            b"\n#01\nx",
+            b"\n#x\n",
+            b"\n#12345678901",
+        ] {
+            let mut decoder = Decoder::default();
+            decoder.extend(bad);
+            assert!(
+                decoder.next_message(Framing::Chunked).is_err(),
+                "{:?}",
+                String::from_utf8_lossy(bad)
+            );
+        }
+    }
+}
@@ -0,0 +1,517 @@
+//! NETCONF client over the `netconf` SSH subsystem (RFC 6241, RFC 6242).
+//!
+//! Junos, SR OS, EOS and ConfD-based systems such as ArcOS serve NETCONF on
+//! the same SSH credentials as their CLI. [`Session::netconf()`] opens the
+//! subsystem on a new channel of an existing connection and exchanges
+//! hellos; chunked framing is used when both sides support `base:1.1`,
+//! end-of-message framing otherwise.
+//!
+//! Payloads are passed and returned as XML strings. `<rpc-error>`s with
+//! severity `error` fail the call with [`ChannelError::NetconfRpc`];
+//! warnings are logged.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use ferrissh::netconf::Datastore;
+//!
+//! # async fn example(session: &ferrissh::Session) -> Result<(), ferrissh::Error> {
+//! let mut netconf = session.netconf().await?;
+//!
+//! netconf.lock(Datastore::Candidate).await?;
+//! netconf
+//!     .edit_config(
+//!         Datastore::Candidate,
+//!         "<system><host-name>edge1</host-name></system>",
+//!     )
+//!     .await?;
+//! netconf.validate(Datastore::Candidate).await?;
+//! netconf.commit().await?;
+//! netconf.unlock(Datastore::Candidate).await?;
+//!
+//! let config = netconf
+//!     .get_config(Datastore::Running, Some("<system/>"))
+//!     .await?;
+//! println!("{config}");
+//!
+//! netconf.close().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Session::netconf()`]: crate::Session::netconf
+
+mod framing;
+mod rpc_error;
+mod xml;
+
+pub use framing::Framing;
+pub use rpc_error::{ErrorSeverity, ErrorTag, ErrorType, RpcError};
+
+use std::time::Duration;
+
+use log::{debug, warn};
+use russh::ChannelMsg;
+use russh::client::Msg;
+
+use crate::error::{ChannelError, Error, Result, TransportError};
+use framing::Decoder;
+
+/// NETCONF 1.0 base protocol.
+pub const BASE_1_0: &str = "urn:ietf:params:netconf:base:1.0";
+/// NETCONF 1.1 base protocol (enables chunked framing).
+pub const BASE_1_1: &str = "urn:ietf:params:netconf:base:1.1";
+/// The `candidate` datastore, `commit` and `discard-changes`.
+pub const CANDIDATE: &str = "urn:ietf:params:netconf:capability:candidate:1.0";
+/// `edit-config` directly on `running`.
+pub const WRITABLE_RUNNING: &str = "urn:ietf:params:netconf:capability:writable-running:1.0";
+/// The `startup` datastore.
+pub const STARTUP: &str = "urn:ietf:params:netconf:capability:startup:1.0";
+/// The `validate` operation (NETCONF 1.1 semantics).
+pub const VALIDATE: &str = "urn:ietf:params:netconf:capability:validate:1.1";
+/// `commit` with `<confirmed/>`.
+pub const CONFIRMED_COMMIT: &str = "urn:ietf:params:netconf:capability:confirmed-commit:1.1";
+
+/// Namespace of the base protocol's elements.
+const NAMESPACE: &str = "urn:ietf:params:xml:ns:netconf:base:1.0";
+
+/// A configuration datastore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Datastore {
+    /// The configuration the device is running.
+    Running,
+    /// The scratch configuration committed with [`NetconfClient::commit()`].
+    Candidate,
+    /// The configuration loaded at boot.
+    Startup,
+}
+
+impl Datastore {
+    /// The element naming this datastore in a `<source>` or `<target>`.
+    fn element(self) -> &'static str {
+        match self {
+            Self::Running => "<running/>",
+            Self::Candidate => "<candidate/>",
+            Self::Startup => "<startup/>",
+        }
+    }
+}
+
+/// How `edit-config` combines the new configuration with the existing
+/// one where the payload has no `operation` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultOperation {
+    /// Merge into the existing configuration (the server default).
+    Merge,
+    /// Replace the existing configuration.
+    Replace,
+    /// Change only elements with an explicit `operation` attribute.
+    None,
+}
+
+/// Whether `edit-config` validates before applying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestOption {
+    /// Validate, and apply only if valid.
+    TestThenSet,
+    /// Apply without validating.
+    Set,
+    /// Validate without applying.
+    TestOnly,
+}
+
+/// What `edit-config` does after an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorOption {
+    /// Stop at the first error (the server default).
+    StopOnError,
+    /// Carry on, reporting every error.
+    ContinueOnError,
+    /// Stop and undo the changes already made.
+    RollbackOnError,
+}
+
+/// Options for [`NetconfClient::edit_config_with()`].
+///
+/// Anything left unset is omitted from the request, so the server's
+/// default applies.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditConfigOptions {
+    default_operation: Option<DefaultOperation>,
+    test_option: Option<TestOption>,
+    error_option: Option<ErrorOption>,
+}
+
+impl EditConfigOptions {
+    /// Options that leave every choice to the server.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `<default-operation>`.
+    pub fn default_operation(mut self, operation: DefaultOperation) -> Self {
+        self.default_operation = Some(operation);
+        self
+    }
+
+    /// Set `<test-option>` (needs the `validate` capability).
+    pub fn test_option(mut self, option: TestOption) -> Self {
+        self.test_option = Some(option);
+        self
+    }
+
+    /// Set `<error-option>`.
+    pub fn error_option(mut self, option: ErrorOption) -> Self {
+        self.error_option = Some(option);
+        self
+    }
+
+    /// The option elements, in the order the schema requires.
+    fn to_xml(&self) -> String {
+        let mut xml = String::new();
+        if let Some(operation) = self.default_operation {
+            let value = match operation {
+                DefaultOperation::Merge => "merge",
+                DefaultOperation::Replace => "replace",
+                DefaultOperation::None => "none",
+            };
+            xml.push_str(&format!("<default-operation>{value}</default-operation>"));
+        }
+        if let Some(option) = self.test_option {
+            let value = match option {
+                TestOption::TestThenSet => "test-then-set",
+                TestOption::Set => "set",
+                TestOption::TestOnly => "test-only",
+            };
+            xml.push_str(&format!("<test-option>{value}</test-option>"));
+        }
+        if let Some(option) = self.error_option {
+            let value = match option {
+                ErrorOption::StopOnError => "stop-on-error",
+                ErrorOption::ContinueOnError => "continue-on-error",
+                ErrorOption::RollbackOnError => "rollback-on-error",
+            };
+            xml.push_str(&format!("<error-option>{value}</error-option>"));
+        }
+        xml
+    }
+}
+
+/// A NETCONF session on an SSH channel.
+///
+/// Created by [`Session::netconf()`](crate::Session::netconf). Requests
+/// are sent one at a time, each bounded by the session timeout.
+pub struct NetconfClient {
+    /// The `netconf` subsystem channel.
+    channel: russh::Channel<Msg>,
+    /// Bytes received but not yet returned as messages.
+    decoder: Decoder,
+    /// Framing negotiated in the hello exchange.
+    framing: Framing,
+    /// Session id the server assigned.
+    session_id: Option<u32>,
+    /// Capabilities the server advertised.
+    capabilities: Vec<String>,
+    /// `message-id` of the next request.
+    next_message_id: u64,
+    /// Time allowed for each reply.
+    timeout: Duration,
+}
+
+impl NetconfClient {
+    /// Exchange hellos on a freshly opened `netconf` subsystem channel.
+    pub(crate) async fn new(channel: russh::Channel<Msg>, timeout: Duration) -> Result<Self> {
+        let mut client = Self {
+            channel,
+            decoder: Decoder::default(),
+            framing: Framing::EndOfMessage,
+            session_id: None,
+            capabilities: Vec::new(),
+            next_message_id: 1,
+            timeout,
+        };
+
+        let hello = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <hello xmlns=\"{NAMESPACE}\"><capabilities>\
+             <capability>{BASE_1_0}</capability>\
+             <capability>{BASE_1_1}</capability>\
+             </capabilities></hello>"
+        );
+        client.send(&hello).await?;
+
+        let reply = client.receive().await?;
+        let Some(hello) = xml::find(&reply, "hello") else {
+            return Err(protocol("expected <hello> from server"));
+        };
+        client.capabilities = hello
+            .find_all("capability")
+            .iter()
+            .map(|capability| capability.text())
+            .collect();
+        client.session_id = hello
+            .find("session-id")
+            .and_then(|id| id.text().parse().ok());
+
+        if client.supports(BASE_1_1) {
+            client.framing = Framing::Chunked;
+        } else if !client.supports(BASE_1_0) {
+            return Err(protocol("server supports neither base:1.0 nor base:1.1"));
+        }
+        debug!(
+            "NETCONF session {:?} established with {:?} framing",
+            client.session_id, client.framing
+        );
+        Ok(client)
+    }
+
+    /// The session id the server assigned in its hello.
+    pub fn session_id(&self) -> Option<u32> {
+        self.session_id
+    }
+
+    /// The capability URIs the server advertised, as sent.
+    pub fn capabilities(&self) -> &[String] {
+        &self.capabilities
+    }
+
+    /// Whether the server advertised `capability` (e.g. [`CANDIDATE`]).
+    ///
+    /// Any `?` query parameters the server appended are ignored.
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities
+            .iter()
+            .any(|advertised| advertised.split('?').next() == Some(capability))
+    }
+
+    /// The framing in use.
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Send an arbitrary RPC and return the contents of its `<rpc-reply>`.
+    ///
+    /// `operation` is the XML inside `<rpc>`, e.g.
+    /// `<get-software-information/>` on Junos.
+    pub async fn rpc(&mut self, operation: &str) -> Result<String> {
+        let message_id = self.next_message_id;
+        self.next_message_id += 1;
+
+        let request = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <rpc message-id=\"{message_id}\" xmlns=\"{NAMESPACE}\">{operation}</rpc>"
+        );
+        self.send(&request).await?;
+
+        loop {
+            let message = self.receive().await?;
+            let Some(reply) = xml::find(&message, "rpc-reply") else {
+                debug!("ignoring NETCONF message that is not an rpc-reply");
+                continue;
+            };
+            let id = reply.attr("message-id");
+            if id.as_deref() != Some(message_id.to_string().as_str()) {
+                return Err(protocol(format!(
+                    "reply has message-id {id:?}, expected {message_id}"
+                )));
+            }
+
+            let (errors, warnings): (Vec<RpcError>, Vec<RpcError>) = RpcError::all(reply.content)
+                .into_iter()
+                .partition(|error| error.severity == ErrorSeverity::Error);
+            for warning in &warnings {
+                warn!("NETCONF warning: {}", warning);
+            }
+            if !errors.is_empty() {
+                return Err(ChannelError::NetconfRpc { errors }.into());
+            }
+            return Ok(reply.content.to_string());
+        }
+    }
+
+    /// Retrieve running configuration and state data.
+    ///
+    /// `filter` is a subtree filter selecting what to return; `None`
+    /// returns everything. Returns the contents of `<data>`.
+    pub async fn get(&mut self, filter: Option<&str>) -> Result<String> {
+        let reply = self.rpc(&format!("<get>{}</get>", subtree(filter))).await?;
+        data(&reply)
+    }
+
+    /// Retrieve configuration from `source`.
+    ///
+    /// `filter` is a subtree filter selecting what to return; `None`
+    /// returns everything. Returns the contents of `<data>`.
+    pub async fn get_config(&mut self, source: Datastore, filter: Option<&str>) -> Result<String> {
+        let reply = self
+            .rpc(&format!(
+                "<get-config><source>{}</source>{}</get-config>",
+                source.element(),
+                subtree(filter)
+            ))
+            .await?;
+        data(&reply)
+    }
+
+    /// Load `config` (the XML inside `<config>`) into `target`, using the
+    /// server's default options.
+    pub async fn edit_config(&mut self, target: Datastore, config: &str) -> Result<()> {
+        self.edit_config_with(target, config, &EditConfigOptions::new())
+            .await
+    }
+
+    /// Load `config` (the XML inside `<config>`) into `target`.
+    pub async fn edit_config_with(
+        &mut self,
+        target: Datastore,
+        config: &str,
+        options: &EditConfigOptions,
+    ) -> Result<()> {
+        self.rpc_ok(&format!(
+            "<edit-config><target>{}</target>{}<config>{config}</config></edit-config>",
+            target.element(),
+            options.to_xml()
+        ))
+        .await
+    }
+
+    /// Lock `target` against changes from other sessions.
+    ///
+    /// Fails with [`ErrorTag::LockDenied`] if another session holds the
+    /// lock.
+    pub async fn lock(&mut self, target: Datastore) -> Result<()> {
+        self.rpc_ok(&format!(
+            "<lock><target>{}</target></lock>",
+            target.element()
+        ))
+        .await
+    }
+
+    /// Release a lock taken with [`lock()`](Self::lock).
+    pub async fn unlock(&mut self, target: Datastore) -> Result<()> {
+        self.rpc_ok(&format!(
+            "<unlock><target>{}</target></unlock>",
+            target.element()
+        ))
+        .await
+    }
+
+    /// Make the candidate configuration the running one.
+    pub async fn commit(&mut self) -> Result<()> {
+        self.rpc_ok("<commit/>").await
+    }
+
+    /// Reset the candidate configuration to the running one.
+    pub async fn discard_changes(&mut self) -> Result<()> {
+        self.rpc_ok("<discard-changes/>").await
+    }
+
+    /// Check the configuration in `source` for errors without applying it.
+    pub async fn validate(&mut self, source: Datastore) -> Result<()> {
+        self.rpc_ok(&format!(
+            "<validate><source>{}</source></validate>",
+            source.element()
+        ))
+        .await
+    }
+
+    /// End the NETCONF session gracefully and close the channel.
+    ///
+    /// Locks held by the session are released by the server.
+    pub async fn close(mut self) -> Result<()> {
+        self.rpc_ok("<close-session/>").await?;
+        let _ = self.channel.eof().await;
+        let _ = self.channel.close().await;
+        Ok(())
+    }
+
+    /// Send an RPC whose only successful reply is `<ok/>`.
+    async fn rpc_ok(&mut self, operation: &str) -> Result<()> {
+        let reply = self.rpc(operation).await?;
+        match xml::find(&reply, "ok") {
+            Some(_) => Ok(()),
+            None => Err(protocol("expected <ok/> in reply")),
+        }
+    }
+
+    /// Frame and send one message.
+    async fn send(&mut self, message: &str) -> Result<()> {
+        let framed = framing::encode(message, self.framing);
+        self.channel
+            .data(&framed[..])
+            .await
+            .map_err(TransportError::Ssh)?;
+        Ok(())
+    }
+
+    /// Wait for the next complete message.
+    async fn receive(&mut self) -> Result<String> {
+        loop {
+            if let Some(message) = self.decoder.next_message(self.framing).map_err(protocol)? {
+                return Ok(message);
+            }
+            let msg = tokio::time::timeout(self.timeout, self.channel.wait())
+                .await
+                .map_err(|_| TransportError::Timeout(self.timeout))?;
+            match msg {
+                Some(ChannelMsg::Data { data }) => self.decoder.extend(&data),
+                Some(ChannelMsg::Eof) | Some(ChannelMsg::Close) | None => {
+                    return Err(protocol("channel closed unexpectedly"));
+                }
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+/// A subtree `<filter>`, or nothing.
+fn subtree(filter: Option<&str>) -> String {
+    filter
+        .map(|filter| format!("<filter type=\"subtree\">{filter}</filter>"))
+        .unwrap_or_default()
+}
+
+/// The contents of the `<data>` element in a reply.
+fn data(reply: &str) -> Result<String> {
+    xml::find(reply, "data")
+        .map(|data| data.content.trim().to_string())
+        .ok_or_else(|| protocol("expected <data> in reply"))
+}
+
+fn protocol(message: impl Into<String>) -> Error {
+    ChannelError::NetconfProtocol {
+        message: message.into(),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_config_options_xml() {
+        assert_eq!(EditConfigOptions::new().to_xml(), "");
+        let options = EditConfigOptions::new()
+            .error_option(ErrorOption::RollbackOnError)
+            .default_operation(DefaultOperation::Replace)
+            .test_option(TestOption::TestOnly);
+        assert_eq!(
+            options.to_xml(),
+            "<default-operation>replace</default-operation>\
+             <test-option>test-only</test-option>\
+             <error-option>rollback-on-error</error-option>"
+        );
+    }
+
+    #[test]
+    fn test_data_extraction() {
+        let reply = "<data>\n  <system><host-name>r1</host-name></system>\n</data>";
+        assert_eq!(
+            data(reply).unwrap(),
+            "<system><host-name>r1</host-name></system>"
+        );
+        assert_eq!(data("<data/>").unwrap(), "");
+        assert!(data("<ok/>").is_err());
+    }
+}
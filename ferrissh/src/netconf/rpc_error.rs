@@ -0,0 +1,267 @@
+//! Typed `<rpc-error>` elements (RFC 6241 section 4.3 and appendix A).
+
+use std::fmt;
+
+use super::xml::{self, Element};
+
+/// The layer an error occurred in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorType {
+    /// Secure transport layer.
+    Transport,
+    /// Messages layer.
+    Rpc,
+    /// Operations layer.
+    Protocol,
+    /// Content layer.
+    Application,
+    /// A value the RFC does not define.
+    Other(String),
+}
+
+impl ErrorType {
+    fn parse(s: &str) -> Self {
+        match s {
+            "transport" => Self::Transport,
+            "rpc" => Self::Rpc,
+            "protocol" => Self::Protocol,
+            "application" => Self::Application,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// The value as it appears in `<error-type>`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Transport => "transport",
+            Self::Rpc => "rpc",
+            Self::Protocol => "protocol",
+            Self::Application => "application",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+/// What went wrong, from `<error-tag>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorTag {
+    /// A resource the request needs is already in use.
+    InUse,
+    /// A parameter value is out of range or not allowed.
+    InvalidValue,
+    /// The request or response is too large to handle.
+    TooBig,
+    /// An expected attribute is missing.
+    MissingAttribute,
+    /// An attribute value is not correct.
+    BadAttribute,
+    /// An unexpected attribute is present.
+    UnknownAttribute,
+    /// An expected element is missing.
+    MissingElement,
+    /// An element value is not correct.
+    BadElement,
+    /// An unexpected element is present.
+    UnknownElement,
+    /// An unexpected namespace is present.
+    UnknownNamespace,
+    /// Access to the requested data is denied.
+    AccessDenied,
+    /// The lock is held by another session.
+    LockDenied,
+    /// Resources are exhausted.
+    ResourceDenied,
+    /// Rolling back a failed request did not fully succeed.
+    RollbackFailed,
+    /// The data to create already exists.
+    DataExists,
+    /// The data to delete or replace does not exist.
+    DataMissing,
+    /// The operation is not supported by this implementation.
+    OperationNotSupported,
+    /// The operation failed for a reason no other tag covers.
+    OperationFailed,
+    /// Obsolete; servers should not send it.
+    PartialOperation,
+    /// A message could not be parsed.
+    MalformedMessage,
+    /// A value the RFC does not define.
+    Other(String),
+}
+
+impl ErrorTag {
+    fn parse(s: &str) -> Self {
+        match s {
+            "in-use" => Self::InUse,
+            "invalid-value" => Self::InvalidValue,
+            "too-big" => Self::TooBig,
+            "missing-attribute" => Self::MissingAttribute,
+            "bad-attribute" => Self::BadAttribute,
+            "unknown-attribute" => Self::UnknownAttribute,
+            "missing-element" => Self::MissingElement,
+            "bad-element" => Self::BadElement,
+            "unknown-element" => Self::UnknownElement,
+            "unknown-namespace" => Self::UnknownNamespace,
+            "access-denied" => Self::AccessDenied,
+            "lock-denied" => Self::LockDenied,
+            "resource-denied" => Self::ResourceDenied,
+            "rollback-failed" => Self::RollbackFailed,
+            "data-exists" => Self::DataExists,
+            "data-missing" => Self::DataMissing,
+            "operation-not-supported" => Self::OperationNotSupported,
+            "operation-failed" => Self::OperationFailed,
+            "partial-operation" => Self::PartialOperation,
+            "malformed-message" => Self::MalformedMessage,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// The value as it appears in `<error-tag>`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::InUse => "in-use",
+            Self::InvalidValue => "invalid-value",
+            Self::TooBig => "too-big",
+            Self::MissingAttribute => "missing-attribute",
+            Self::BadAttribute => "bad-attribute",
+            Self::UnknownAttribute => "unknown-attribute",
+            Self::MissingElement => "missing-element",
+            Self::BadElement => "bad-element",
+            Self::UnknownElement => "unknown-element",
+            Self::UnknownNamespace => "unknown-namespace",
+            Self::AccessDenied => "access-denied",
+            Self::LockDenied => "lock-denied",
+            Self::ResourceDenied => "resource-denied",
+            Self::RollbackFailed => "rollback-failed",
+            Self::DataExists => "data-exists",
+            Self::DataMissing => "data-missing",
+            Self::OperationNotSupported => "operation-not-supported",
+            Self::OperationFailed => "operation-failed",
+            Self::PartialOperation => "partial-operation",
+            Self::MalformedMessage => "malformed-message",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+/// How serious an error is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorSeverity {
+    /// The operation failed.
+    Error,
+    /// The operation succeeded, with a warning.
+    Warning,
+}
+
+/// One `<rpc-error>` from an `<rpc-reply>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcError {
+    /// The layer the error occurred in.
+    pub error_type: ErrorType,
+    /// What went wrong.
+    pub tag: ErrorTag,
+    /// Whether the operation failed.
+    pub severity: ErrorSeverity,
+    /// Implementation-specific error identifier.
+    pub app_tag: Option<String>,
+    /// Path to the element the error is about.
+    pub path: Option<String>,
+    /// Human-readable description.
+    pub message: Option<String>,
+    /// The raw XML of `<error-info>`, if any.
+    pub info: Option<String>,
+}
+
+impl RpcError {
+    /// Parse an `<rpc-error>` element.
+    pub(crate) fn parse(element: &Element<'_>) -> Self {
+        let text = |name| element.find(name).map(|e| e.text());
+        Self {
+            error_type: ErrorType::parse(&text("error-type").unwrap_or_default()),
+            tag: ErrorTag::parse(&text("error-tag").unwrap_or_default()),
+            severity: match text("error-severity").as_deref() {
+                Some("warning") => ErrorSeverity::Warning,
+                _ => ErrorSeverity::Error,
+            },
+            app_tag: text("error-app-tag"),
+            path: text("error-path"),
+            message: text("error-message"),
+            info: element
+                .find("error-info")
+                .map(|e| e.content.trim().to_string()),
+        }
+    }
+
+    /// Every `<rpc-error>` in `reply`.
+    pub(crate) fn all(reply: &str) -> Vec<Self> {
+        xml::find_all(reply, "rpc-error")
+            .iter()
+            .map(Self::parse)
+            .collect()
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} error {}",
+            self.error_type.as_str(),
+            self.tag.as_str()
+        )?;
+        if let Some(message) = &self.message {
+            write!(f, ": {message}")?;
+        }
+        if let Some(path) = &self.path {
+            write!(f, " (at {path})")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rpc_errors() {
+        let reply = r#"<rpc-reply message-id="3">
+              <rpc-error>
+                <error-type>protocol</error-type>
+                <error-tag>lock-denied</error-tag>
+                <error-severity>error</error-severity>
+                <error-info><session-id>4</session-id></error-info>
+                <error-message xml:lang="en">Lock held by &lt;admin&gt;</error-message>
+              </rpc-error>
+              <nc:rpc-error>
+                <nc:error-type>application</nc:error-type>
+                <nc:error-tag>x-vendor</nc:error-tag>
+                <nc:error-severity>warning</nc:error-severity>
+                <nc:error-path>/interfaces/interface[name='ge-0/0/0']</nc:error-path>
+              </nc:rpc-error>
+            </rpc-reply>"#;
+
+        let errors = RpcError::all(reply);
+        assert_eq!(errors.len(), 2);
+
+        assert_eq!(errors[0].error_type, ErrorType::Protocol);
+        assert_eq!(errors[0].tag, ErrorTag::LockDenied);
+        assert_eq!(errors[0].severity, ErrorSeverity::Error);
+        assert_eq!(errors[0].message.as_deref(), Some("Lock held by <admin>"));
+        assert_eq!(
+            errors[0].info.as_deref(),
+            Some("<session-id>4</session-id>")
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "protocol error lock-denied: Lock held by <admin>"
+        );
+
+        assert_eq!(errors[1].tag, ErrorTag::Other("x-vendor".into()));
+        assert_eq!(errors[1].severity, ErrorSeverity::Warning);
+        assert_eq!(
+            errors[1].path.as_deref(),
+            Some("/interfaces/interface[name='ge-0/0/0']")
+        );
+    }
+}
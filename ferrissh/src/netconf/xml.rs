@@ -0,0 +1,323 @@
+//! Just enough XML for NETCONF replies: finding elements by local name and
+//! unescaping their text.
+//!
+//! Replies are not validated. Namespace prefixes are ignored when matching
+//! names, so `<nc:rpc-error>` and `<rpc-error>` are found alike.
+
+use std::borrow::Cow;
+
+/// Replace entity and character references with the characters they
+/// stand for, and unwrap CDATA sections. Unknown references are kept as
+/// written.
+pub(crate) fn unescape(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<']) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find(['&', '<']) {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            out.push_str(&cdata[..end]);
+            rest = cdata.get(end + 3..).unwrap_or_default();
+            continue;
+        }
+        let reference = rest
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| Some((decode_reference(&rest[1..end])?, end)));
+        match reference {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// The character an entity or character reference (without `&` and `;`)
+/// stands for.
+fn decode_reference(name: &str) -> Option<char> {
+    match name {
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "amp" => Some('&'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        _ => {
+            let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => name.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+/// An element found in a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Element<'a> {
+    /// The attributes, as written between the name and the closing `>`.
+    attrs: &'a str,
+    /// Everything between the start and end tags (empty for `<x/>`).
+    pub content: &'a str,
+}
+
+impl<'a> Element<'a> {
+    /// The element's text, unescaped and trimmed.
+    pub fn text(&self) -> String {
+        unescape(self.content).trim().to_string()
+    }
+
+    /// The value of the attribute with local name `name`.
+    pub fn attr(&self, name: &str) -> Option<String> {
+        let mut rest = self.attrs;
+        while let Some(eq) = rest.find('=') {
+            let key = rest[..eq].trim();
+            let value = rest[eq + 1..].trim_start();
+            let quote = value.chars().next()?;
+            if quote != '"' && quote != '\'' {
+                return None;
+            }
+            let end = value[1..].find(quote)? + 1;
+            if local_name(key) == name {
+                return Some(unescape(&value[1..end]).into_owned());
+            }
+            rest = &value[end + 1..];
+        }
+        None
+    }
+
+    /// The first descendant named `name`.
+    pub fn find(&self, name: &str) -> Option<Element<'a>> {
+        find(self.content, name)
+    }
+
+    /// Descendants named `name` (see [`find_all()`]).
+    pub fn find_all(&self, name: &str) -> Vec<Element<'a>> {
+        find_all(self.content, name)
+    }
+}
+
+/// The first element named `name` in `xml`.
+pub(crate) fn find<'a>(xml: &'a str, name: &str) -> Option<Element<'a>> {
+    Elements::new(xml, name).next()
+}
+
+/// Every element named `name` in `xml`, in document order. Matches nested
+/// inside another match are part of its content, not returned separately.
+pub(crate) fn find_all<'a>(xml: &'a str, name: &str) -> Vec<Element<'a>> {
+    Elements::new(xml, name).collect()
+}
+
+/// The part of a qualified name after its prefix.
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TagKind {
+    Start,
+    End,
+    Empty,
+}
+
+/// A start, end or empty-element tag.
+#[derive(Debug, Clone, Copy)]
+struct Tag<'a> {
+    kind: TagKind,
+    name: &'a str,
+    attrs: &'a str,
+    /// Offset of the `<`.
+    start: usize,
+    /// Offset just past the `>`.
+    end: usize,
+}
+
+/// The next tag at or after `from`, skipping comments, CDATA sections,
+/// processing instructions and declarations.
+fn next_tag(xml: &str, mut from: usize) -> Option<Tag<'_>> {
+    loop {
+        let start = from + xml.get(from..)?.find('<')?;
+        let rest = &xml[start..];
+        let skip_to = |terminator: &str| {
+            rest.find(terminator)
+                .map(|end| start + end + terminator.len())
+        };
+        if rest.starts_with("<!--") {
+            from = skip_to("-->")?;
+        } else if rest.starts_with("<![CDATA[") {
+            from = skip_to("]]>")?;
+        } else if rest.starts_with("<?") {
+            from = skip_to("?>")?;
+        } else if rest.starts_with("<!") {
+            from = skip_to(">")?;
+        } else {
+            return parse_tag(xml, start);
+        }
+    }
+}
+
+/// Parse the tag whose `<` is at `start`.
+fn parse_tag(xml: &str, start: usize) -> Option<Tag<'_>> {
+    let body_start = start + 1;
+    let closing = xml[body_start..].starts_with('/');
+    let name_start = body_start + usize::from(closing);
+
+    // Find the closing `>`, skipping over quoted attribute values.
+    let mut quote = None;
+    let mut close = None;
+    for (i, c) in xml[name_start..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => {
+                close = Some(name_start + i);
+                break;
+            }
+            (None, _) => {}
+        }
+    }
+    let close = close?;
+
+    let mut body = &xml[name_start..close];
+    let empty = !closing && body.ends_with('/');
+    if empty {
+        body = &body[..body.len() - 1];
+    }
+    let name_len = body
+        .find(|c: char| c.is_ascii_whitespace())
+        .unwrap_or(body.len());
+    Some(Tag {
+        kind: match (closing, empty) {
+            (true, _) => TagKind::End,
+            (false, true) => TagKind::Empty,
+            (false, false) => TagKind::Start,
+        },
+        name: &body[..name_len],
+        attrs: &body[name_len..],
+        start,
+        end: close + 1,
+    })
+}
+
+/// Iterator over the elements named `name`.
+struct Elements<'a, 'n> {
+    xml: &'a str,
+    name: &'n str,
+    pos: usize,
+}
+
+impl<'a, 'n> Elements<'a, 'n> {
+    fn new(xml: &'a str, name: &'n str) -> Self {
+        Self { xml, name, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for Elements<'a, '_> {
+    type Item = Element<'a>;
+
+    fn next(&mut self) -> Option<Element<'a>> {
+        loop {
+            let tag = next_tag(self.xml, self.pos)?;
+            self.pos = tag.end;
+            if local_name(tag.name) != self.name {
+                continue;
+            }
+            match tag.kind {
+                TagKind::End => continue,
+                TagKind::Empty => {
+                    return Some(Element {
+                        attrs: tag.attrs,
+                        content: "",
+                    });
+                }
+                TagKind::Start => {}
+            }
+
+            // Find the matching end tag, counting nested same-name elements.
+            let mut depth = 1;
+            while let Some(inner) = next_tag(self.xml, self.pos) {
+                self.pos = inner.end;
+                if inner.name != tag.name {
+                    continue;
+                }
+                match inner.kind {
+                    TagKind::Start => depth += 1,
+                    TagKind::End => depth -= 1,
+                    TagKind::Empty => {}
+                }
+                if depth == 0 {
+                    return Some(Element {
+                        attrs: tag.attrs,
+                        content: &self.xml[tag.end..inner.start],
+                    });
+                }
+            }
+            // Unterminated: everything to the end of the document.
+            return Some(Element {
+                attrs: tag.attrs,
+                content: &self.xml[tag.end..],
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unescape_references_and_cdata() {
+        assert_eq!(unescape("&#65;&#x42;&unknown; &"), "AB&unknown; &");
+        assert_eq!(unescape("x<![CDATA[<raw> & ]]>y"), "x<raw> & y");
+        assert_eq!(
+            unescape("&lt;a&gt; &amp; &quot;b&quot; &apos;c&apos;"),
+            r#"<a> & "b" 'c'"#
+        );
+        assert!(matches!(unescape("plain"), Cow::Borrowed("plain")));
+    }
+
+    #[test]
+    fn test_find_ignores_prefixes_and_skips_markup() {
+        let xml = r#"<?xml version="1.0"?>
+            <!-- <rpc-error>not this</rpc-error> -->
+            <nc:rpc-reply xmlns:nc="urn:x" message-id='7'>
+              <nc:rpc-error><nc:error-tag>in-use</nc:error-tag></nc:rpc-error>
+              <rpc-error><error-tag attr="a>b">lock-denied</error-tag></rpc-error>
+              <nc:ok/>
+            </nc:rpc-reply>"#;
+
+        let reply = find(xml, "rpc-reply").unwrap();
+        assert_eq!(reply.attr("message-id").as_deref(), Some("7"));
+        assert_eq!(reply.attr("nc").as_deref(), Some("urn:x"));
+
+        let errors = reply.find_all("rpc-error");
+        let tags: Vec<String> = errors
+            .iter()
+            .map(|e| e.find("error-tag").unwrap().text())
+            .collect();
+        assert_eq!(tags, ["in-use", "lock-denied"]);
+
+        assert_eq!(reply.find("ok").unwrap().content, "");
+        assert!(reply.find("data").is_none());
+    }
+
+    #[test]
+    fn test_find_nested_same_name() {
+        let xml = "<data><a><a>inner</a></a><a>second</a></data>";
+        let found = find_all(xml, "a");
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].content, "<a>inner</a>");
+        assert_eq!(found[1].text(), "second");
+    }
+}
@@ -4,6 +4,7 @@ use std::fmt;
 use std::sync::Arc;
 
 use indexmap::IndexMap;
+use regex::bytes::Regex;
 
 use super::VendorBehavior;
 use super::privilege_level::PrivilegeLevel;
@@ -32,6 +33,13 @@ pub struct PlatformDefinition {
     /// Commands to run before connection is closed.
     pub on_close_commands: Vec<String>,
 
+    /// Patterns matching pager prompts (e.g. `--More--`) at the end of the
+    /// output, answered automatically for when paging cannot be disabled.
+    pub pager_patterns: Vec<Regex>,
+
+    /// What to send to answer a pager prompt (default: a space).
+    pub pager_response: String,
+
     /// Terminal width for PTY.
     pub terminal_width: u32,
 
@@ -52,6 +60,8 @@ impl PlatformDefinition {
             failed_when_contains: vec![],
             on_open_commands: vec![],
             on_close_commands: vec![],
+            pager_patterns: vec![],
+            pager_response: " ".to_string(),
             terminal_width: 511,
             terminal_height: 24,
             behavior: None,
@@ -88,6 +98,21 @@ impl PlatformDefinition {
         self
     }
 
+    /// Add a pager prompt pattern.
+    ///
+    /// The pattern should be anchored to the end of the output (e.g.
+    /// `--More--\s*$`).
+    pub fn with_pager_pattern(mut self, pattern: Regex) -> Self {
+        self.pager_patterns.push(pattern);
+        self
+    }
+
+    /// Set what is sent to answer a pager prompt.
+    pub fn with_pager_response(mut self, response: impl Into<String>) -> Self {
+        self.pager_response = response.into();
+        self
+    }
+
     /// Set terminal dimensions.
     pub fn with_terminal_size(mut self, width: u32, height: u32) -> Self {
         self.terminal_width = width;
@@ -116,6 +141,8 @@ impl fmt::Debug for PlatformDefinition {
             .field("failed_when_contains", &self.failed_when_contains)
             .field("on_open_commands", &self.on_open_commands)
             .field("on_close_commands", &self.on_close_commands)
+            .field("pager_patterns", &self.pager_patterns)
+            .field("pager_response", &self.pager_response)
             .field("terminal_width", &self.terminal_width)
             .field("terminal_height", &self.terminal_height)
             .field(
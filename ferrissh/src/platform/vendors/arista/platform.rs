@@ -40,6 +40,8 @@ static PRIV_EXEC_AUTH: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?-u)^password:\s?$").unwrap());
 static CONFIG_PATTERN: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?m)(?-u)^[\w.\-@()/: ]+\(config[\w.\-@/:+]*\)#\s?$").unwrap());
+static PAGER_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?-u)[ \t]*--More--\s*$").unwrap());
 
 /// Platform name for Arista EOS.
 pub const PLATFORM_NAME: &str = "arista_eos";
@@ -83,6 +85,7 @@ pub fn platform() -> PlatformDefinition {
         .with_failure_pattern("% Duplicate sequence number")
         .with_on_open_command("terminal length 0")
         .with_on_open_command("terminal width 32767")
+        .with_pager_pattern(PAGER_PATTERN.clone())
         .with_terminal_size(32767, 24)
 }
 
@@ -21,6 +21,9 @@ static EXEC_PATTERN: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?m)(?-u)^[\w\-.@()/:]+#\s?$").unwrap());
 static CONFIG_PATTERN: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?m)(?-u)^[\w\-.@()/:]+\(config[\w.\-@/:]*\)#\s?$").unwrap());
+/// ConfD's pager prompts (J-style and C-style).
+static PAGER_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?-u)(?:---\(more(?: \d+%)?\)---|[ \t]*--More--)\s*$").unwrap());
 
 pub const PLATFORM_NAME: &str = "arrcus_arcos";
 
@@ -48,6 +51,7 @@ pub fn platform() -> PlatformDefinition {
         .with_on_open_command("set cli screen-width 511")
         .with_on_open_command("set cli screen-length 0")
         .with_on_open_command("set cli complete-on-space off")
+        .with_pager_pattern(PAGER_PATTERN.clone())
 }
 
 #[cfg(test)]
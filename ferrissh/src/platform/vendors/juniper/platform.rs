@@ -60,6 +60,8 @@ static ROOT_SHELL_PATTERN: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?m)(?-u)^[^\n]*root@\S*[%#]\s?$").unwrap());
 static ROOT_SHELL_AUTH: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?-u)^password:\s?$").unwrap());
+static PAGER_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?-u)---\(more(?: \d+%)?\)---\s*$").unwrap());
 
 /// Platform name for Juniper JUNOS.
 pub const PLATFORM_NAME: &str = "juniper_junos";
@@ -109,6 +111,7 @@ pub fn platform() -> PlatformDefinition {
         .with_failure_pattern("invalid numeric value")
         .with_on_open_command("set cli screen-length 0")
         .with_on_open_command("set cli screen-width 511")
+        .with_pager_pattern(PAGER_PATTERN.clone())
         .with_terminal_size(511, 24)
         .with_behavior(Arc::new(JuniperBehavior))
 }
@@ -452,4 +455,13 @@ mod tests {
                 .contains(&"No valid completions".to_string())
         );
     }
+
+    #[test]
+    fn test_pager_pattern_match() {
+        let platform = platform();
+        let pager = |tail: &[u8]| platform.pager_patterns.iter().any(|p| p.is_match(tail));
+        assert!(pager(b"  ge-0/0/0 up\n---(more)---"));
+        assert!(pager(b"  ge-0/0/0 up\n---(more 45%)---"));
+        assert!(!pager(b"---(more)---\nuser@router> "));
+    }
 }
@@ -12,6 +12,9 @@ use crate::platform::{PlatformDefinition, PrivilegeLevel};
 static USER_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[$]\s*$").unwrap());
 static ROOT_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[#]\s*$").unwrap());
 static ROOT_AUTH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[Pp]assword[:\s]*$").unwrap());
+/// `more`'s prompt, with the percentage it shows when reading a file.
+static PAGER_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?-u)--More--(?:\(\d+%\))?\s*$").unwrap());
 
 /// Create the Linux platform definition.
 pub fn platform() -> PlatformDefinition {
@@ -31,6 +34,7 @@ pub fn platform() -> PlatformDefinition {
         .with_failure_pattern("No such file or directory")
         .with_failure_pattern("Permission denied")
         .with_failure_pattern("Operation not permitted")
+        .with_pager_pattern(PAGER_PATTERN.clone())
         .with_terminal_size(511, 24)
 }
 
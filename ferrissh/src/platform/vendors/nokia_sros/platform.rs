@@ -81,6 +81,11 @@ static CLASSIC_EXEC_PATTERN: LazyLock<Regex> =
 static CLASSIC_CONFIG_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"(?m)(?-u)^\*?[a-dA-D]:[\w \t_.-]+>config[\w>./-]*(?:#|\$)\s?$").unwrap()
 });
+static PAGER_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?-u)Press Q to quit, Enter to print next line or any other key to print next page\.?\s*$").unwrap()
+});
+static CLASSIC_PAGER_PATTERN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?-u)Press any key to continue \(Q to quit\)\s*$").unwrap());
 
 /// Platform name for Nokia SR OS.
 pub const PLATFORM_NAME: &str = "nokia_sros";
@@ -158,6 +163,9 @@ pub fn platform() -> PlatformDefinition {
         // Classic CLI paging disable (// runs in Classic engine from MD-CLI):
         .with_on_open_command("//environment no more")
         .with_on_open_command("environment no more")
+        // Pagers of both engines, for output that ignores the above
+        .with_pager_pattern(PAGER_PATTERN.clone())
+        .with_pager_pattern(CLASSIC_PAGER_PATTERN.clone())
         // Failure patterns (superset for both engines)
         .with_failure_pattern("MINOR:")
         .with_failure_pattern("MAJOR:")
@@ -572,4 +580,15 @@ mod tests {
         assert_eq!(platform.terminal_width, 512);
         assert_eq!(platform.terminal_height, 24);
     }
+
+    #[test]
+    fn test_pager_pattern_match() {
+        let platform = platform();
+        let pager = |tail: &[u8]| platform.pager_patterns.iter().any(|p| p.is_match(tail));
+        assert!(pager(b"1/1/1 Up\nPress any key to continue (Q to quit)"));
+        assert!(pager(
+            b"1/1/1 Up\nPress Q to quit, Enter to print next line or any other key to print next page."
+        ));
+        assert!(!pager(b"Press any key to continue (Q to quit)\nA:router# "));
+    }
 }
@@ -0,0 +1,255 @@
+//! Automatic reconnection after connection loss.
+//!
+//! A [`ReconnectPolicy`] is opt-in via
+//! [`DriverBuilder::reconnect()`](crate::DriverBuilder::reconnect) or
+//! [`SessionBuilder::reconnect()`](crate::SessionBuilder::reconnect). When a
+//! channel finds its connection gone (device reload, WAN flap, keepalive
+//! timeout), the next command reconnects the session with exponential
+//! backoff, reopens the channel, re-runs `on_open_commands` and restores the
+//! privilege level the channel was at. The command that was in flight when
+//! the connection dropped is not retried — it returns its error as before.
+//!
+//! Progress is reported as [`ReconnectEvent`]s on a broadcast channel; see
+//! [`GenericDriver::reconnect_events()`](crate::GenericDriver::reconnect_events)
+//! and [`Session::reconnect_events()`](crate::Session::reconnect_events).
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+use crate::error::DisconnectReason;
+
+/// Backoff schedule for reconnect attempts.
+///
+/// The delay before attempt `n` (1-based) is
+/// `initial_backoff * multiplier^(n - 1)`, capped at `max_backoff`, then
+/// scaled by a random factor in `[1 - jitter, 1 + jitter]` so that many
+/// collectors losing the same device don't reconnect in lockstep.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use ferrissh::ReconnectPolicy;
+///
+/// // Ride out a device reload: up to 10 attempts, 5s doubling to 2 minutes.
+/// let policy = ReconnectPolicy::new()
+///     .max_attempts(10)
+///     .initial_backoff(Duration::from_secs(5))
+///     .max_backoff(Duration::from_secs(120));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Maximum number of connection attempts per reconnect (default: 5).
+    pub max_attempts: u32,
+    /// Delay before the first attempt (default: 1 second).
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between attempts (default: 60 seconds).
+    pub max_backoff: Duration,
+    /// Factor the delay grows by after each failed attempt (default: 2.0).
+    pub multiplier: f64,
+    /// Random spread applied to each delay, as a fraction (default: 0.2).
+    pub jitter: f64,
+}
+
+impl ReconnectPolicy {
+    /// Create a policy with the default schedule.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of connection attempts.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts;
+        self
+    }
+
+    /// Set the delay before the first attempt.
+    pub fn initial_backoff(mut self, delay: Duration) -> Self {
+        self.initial_backoff = delay;
+        self
+    }
+
+    /// Set the upper bound on the delay between attempts.
+    pub fn max_backoff(mut self, delay: Duration) -> Self {
+        self.max_backoff = delay;
+        self
+    }
+
+    /// Set the factor the delay grows by after each failed attempt.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Set the random spread applied to each delay (0.0 disables jitter).
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The delay before attempt `attempt` (1-based), including jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let base = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let capped = base.min(self.max_backoff.as_secs_f64());
+
+        let spread = if self.jitter > 0.0 {
+            // A fresh RandomState is randomly keyed, which is all the
+            // randomness jitter needs.
+            let unit = RandomState::new().hash_one(attempt) as f64 / u64::MAX as f64;
+            1.0 - self.jitter + 2.0 * self.jitter * unit
+        } else {
+            1.0
+        };
+
+        // A huge max_backoff can be past what a Duration holds once in
+        // seconds as f64 (and with jitter on top)
+        Duration::try_from_secs_f64((capped * spread).max(0.0)).unwrap_or(self.max_backoff)
+    }
+
+    /// Check the policy for values that would never reconnect or never
+    /// back off.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 {
+            return Err("reconnect max_attempts must be at least 1".to_string());
+        }
+        if self.multiplier.is_nan() || self.multiplier < 1.0 {
+            return Err(format!(
+                "reconnect multiplier must be at least 1.0, got {}",
+                self.multiplier
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err(format!(
+                "reconnect jitter must be between 0.0 and 1.0, got {}",
+                self.jitter
+            ));
+        }
+        if self.max_backoff < self.initial_backoff {
+            return Err("reconnect max_backoff must not be less than initial_backoff".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+/// Progress of a reconnect, broadcast to subscribers.
+///
+/// A successful reconnect produces `Disconnected`, one `Attempt` per try
+/// (each failed try followed by `AttemptFailed`), `Reconnected`, and then
+/// one `ChannelReopened` per channel as each is next used.
+#[derive(Debug, Clone)]
+pub enum ReconnectEvent {
+    /// A reconnect started because the session was lost.
+    Disconnected {
+        /// Why the previous connection ended.
+        reason: DisconnectReason,
+    },
+    /// A connection attempt is about to be made after `delay`.
+    Attempt {
+        /// Attempt number, starting at 1.
+        attempt: u32,
+        /// Backoff delay before this attempt.
+        delay: Duration,
+    },
+    /// A connection attempt failed.
+    AttemptFailed {
+        /// Attempt number, starting at 1.
+        attempt: u32,
+        /// The connection error.
+        error: String,
+    },
+    /// The SSH session was re-established.
+    Reconnected {
+        /// Number of attempts it took.
+        attempts: u32,
+    },
+    /// A channel was reopened on the new session, `on_open_commands` were
+    /// re-run and its privilege level restored.
+    ChannelReopened {
+        /// The channel's privilege level after reopening.
+        privilege: Option<String>,
+    },
+    /// Every attempt failed (or the error was not retryable, such as an
+    /// authentication failure); the channel stays dead.
+    GaveUp {
+        /// Number of attempts made.
+        attempts: u32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_exponentially_and_caps() {
+        let policy = ReconnectPolicy::new()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(500))
+            .jitter(0.0);
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+        assert_eq!(policy.delay(4), Duration::from_millis(500));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_delay_with_huge_max_backoff() {
+        let policy = ReconnectPolicy::new()
+            .initial_backoff(Duration::MAX)
+            .max_backoff(Duration::MAX)
+            .jitter(0.5);
+        for attempt in 1..50 {
+            assert!(policy.delay(attempt) >= Duration::from_secs(u64::MAX / 4));
+        }
+        assert_eq!(policy.jitter(0.0).delay(1), Duration::MAX);
+    }
+
+    #[test]
+    fn test_delay_jitter_stays_in_range() {
+        let policy = ReconnectPolicy::new()
+            .initial_backoff(Duration::from_secs(10))
+            .max_backoff(Duration::from_secs(10))
+            .jitter(0.5);
+        for attempt in 1..50 {
+            let delay = policy.delay(attempt);
+            assert!(delay >= Duration::from_secs(5), "{delay:?}");
+            assert!(delay <= Duration::from_secs(15), "{delay:?}");
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_values() {
+        assert!(ReconnectPolicy::default().validate().is_ok());
+        assert!(ReconnectPolicy::new().max_attempts(0).validate().is_err());
+        assert!(ReconnectPolicy::new().multiplier(0.5).validate().is_err());
+        assert!(
+            ReconnectPolicy::new()
+                .multiplier(f64::NAN)
+                .validate()
+                .is_err()
+        );
+        assert!(ReconnectPolicy::new().jitter(1.5).validate().is_err());
+        let err = ReconnectPolicy::new()
+            .initial_backoff(Duration::from_secs(10))
+            .max_backoff(Duration::from_secs(1))
+            .validate()
+            .unwrap_err();
+        assert!(err.contains("max_backoff"), "got: {err}");
+    }
+}
@@ -33,28 +33,44 @@
 //! # }
 //! ```
 
-use std::path::PathBuf;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use log::debug;
+use bytes::BytesMut;
+use log::{debug, warn};
 use regex::bytes::Regex;
-use tokio::sync::watch;
+use tokio::sync::{Mutex, broadcast, watch};
 
-use crate::channel::{PtyChannel, PtyConfig};
+use crate::channel::{Pager, PtyChannel, PtyConfig};
 use crate::driver::PrivilegeLevelsBase;
 use crate::driver::channel::Channel;
-use crate::error::{DisconnectReason, DriverError, PlatformError, Result};
+use crate::driver::{ExecChunk, ExecResult, ExecStream, Payload};
+use crate::error::{DisconnectReason, DriverError, Error, PlatformError, Result, TransportError};
+use crate::forward::{LocalForward, RemoteForward};
+use crate::netconf::NetconfClient;
 use crate::platform::{Platform, PlatformDefinition};
+use crate::reconnect::{ReconnectEvent, ReconnectPolicy};
+use crate::transfer::{ScpClient, SftpClient};
 use secrecy::SecretString;
 
-use crate::transport::SshTransport;
-use crate::transport::config::{AuthMethod, HostKeyVerification, SshConfig};
+use crate::transport::config::{
+    AuthMethod, CertificateSource, HostKeyVerification, JumpHost, Protocol, ProxyConfig, SshConfig,
+};
+use crate::transport::{self, SshTransport, Transport};
+use crate::transport::{
+    AlgorithmPreferences, ConnectionInfo, HostConfig, KeyboardInteractiveResponder,
+    NegotiatedAlgorithms, OpenSshConfig, Pty, TerminalOptions,
+};
+
+/// Buffered reconnect events per subscriber before the oldest are dropped.
+pub(crate) const RECONNECT_EVENT_CAPACITY: usize = 32;
 
 /// Inner state of an SSH session, shared via `Arc`.
 struct SessionInner {
-    /// The SSH transport.
-    transport: SshTransport,
+    /// The transport (SSH or Telnet).
+    transport: Box<dyn Transport>,
 
     /// Platform definition.
     platform: Arc<PlatformDefinition>,
@@ -76,6 +92,12 @@ struct SessionInner {
 
     /// When the session was established.
     connected_since: Instant,
+
+    /// Sender for reconnect progress, shared with every successor session.
+    reconnect_events: broadcast::Sender<ReconnectEvent>,
+
+    /// The session that replaced this one after a reconnect.
+    successor: Mutex<Option<Session>>,
 }
 
 /// An authenticated SSH connection to a device.
@@ -94,9 +116,10 @@ pub struct Session {
 impl Session {
     /// Create a new session from its components.
     pub(crate) fn new(
-        transport: SshTransport,
+        transport: Box<dyn Transport>,
         platform: PlatformDefinition,
         ssh_config: SshConfig,
+        reconnect_events: broadcast::Sender<ReconnectEvent>,
     ) -> Self {
         let disconnect_tx = transport.disconnect_tx().clone();
         let disconnect_rx = transport.disconnect_rx().clone();
@@ -119,6 +142,29 @@ impl Session {
                 disconnect_tx,
                 disconnect_rx,
                 connected_since: Instant::now(),
+                reconnect_events,
+                successor: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Create the session that replaces this one on a fresh transport.
+    fn successor_with(&self, transport: Box<dyn Transport>) -> Self {
+        let disconnect_tx = transport.disconnect_tx().clone();
+        let disconnect_rx = transport.disconnect_rx().clone();
+
+        Self {
+            inner: Arc::new(SessionInner {
+                transport,
+                platform: self.inner.platform.clone(),
+                ssh_config: self.inner.ssh_config.clone(),
+                prompt_patterns: self.inner.prompt_patterns.clone(),
+                privilege_base: self.inner.privilege_base.clone(),
+                disconnect_tx,
+                disconnect_rx,
+                connected_since: Instant::now(),
+                reconnect_events: self.inner.reconnect_events.clone(),
+                successor: Mutex::new(None),
             }),
         }
     }
@@ -130,13 +176,36 @@ impl Session {
     /// `on_open_commands`, and determines the initial privilege level before
     /// returning.
     pub async fn open_channel(&self) -> Result<Channel> {
-        let russh_channel = self.inner.transport.open_channel().await?;
-        let pty = PtyChannel::new(russh_channel, PtyConfig::default());
+        self.open_channel_with(self.terminal_options()).await
+    }
 
-        let auth_password = match &self.inner.ssh_config.auth {
-            AuthMethod::Password(pwd) => Some(pwd.clone()),
-            _ => None,
-        };
+    /// Open a new PTY channel with its own terminal settings.
+    ///
+    /// Like [`open_channel()`](Self::open_channel), but with the terminal
+    /// type, size, PTY modes and environment variables given here instead of
+    /// the builder's.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # async fn example(session: &ferrissh::Session) -> Result<(), ferrissh::Error> {
+    /// let terminal = session.terminal_options().term("vt100").env("LANG", "C");
+    /// let mut channel = session.open_channel_with(terminal).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn open_channel_with(&self, terminal: TerminalOptions) -> Result<Channel> {
+        let pty = self.open_pty(&terminal).await?;
+
+        let auth_password =
+            self.inner
+                .ssh_config
+                .auth_methods
+                .iter()
+                .find_map(|method| match method {
+                    AuthMethod::Password(pwd) => Some(pwd.clone()),
+                    _ => None,
+                });
 
         let mut channel = Channel::new(
             self.clone(),
@@ -144,8 +213,8 @@ impl Session {
             self.inner.ssh_config.timeout,
             self.inner.prompt_patterns.clone(),
             true, // normalize
-            self.inner.disconnect_rx.clone(),
             auth_password,
+            terminal,
         );
 
         // Wait for initial prompt, run on_open, determine privilege
@@ -159,6 +228,286 @@ impl Session {
         }
     }
 
+    /// Run `command` on its own channel without a PTY.
+    ///
+    /// Sends an SSH `exec` request, collects stdout and stderr separately,
+    /// and returns them with the exit status (or signal) the server reports.
+    /// No prompt matching is involved, so this works regardless of platform
+    /// prompts — but only on servers that support `exec`. Fails if no data
+    /// arrives within the session timeout.
+    pub async fn exec(&self, command: &str) -> Result<ExecResult> {
+        let mut stream = self.exec_stream(command).await?;
+        let mut stdout = BytesMut::new();
+        let mut stderr = BytesMut::new();
+        while let Some(chunk) = stream.next_chunk().await? {
+            match chunk {
+                ExecChunk::Stdout(data) => stdout.extend_from_slice(&data),
+                ExecChunk::Stderr(data) => stderr.extend_from_slice(&data),
+            }
+        }
+
+        let completion = stream
+            .completion()
+            .cloned()
+            .expect("exec stream finished without completion");
+        debug!(
+            "exec: {:?} completed in {:?} ({:?})",
+            command, completion.elapsed, completion.exit_status
+        );
+
+        Ok(ExecResult {
+            command: command.to_string(),
+            stdout: Payload::from_bytes_mut(stdout),
+            stderr: Payload::from_bytes_mut(stderr),
+            exit_status: completion.exit_status,
+            elapsed: completion.elapsed,
+        })
+    }
+
+    /// Run `command` without a PTY and stream its output as it arrives.
+    ///
+    /// The streaming counterpart of [`exec()`](Self::exec). Each chunk
+    /// is tagged as stdout or stderr; the exit status is available from
+    /// [`ExecStream::completion()`] once the stream is drained.
+    pub async fn exec_stream(&self, command: &str) -> Result<ExecStream> {
+        let start = Instant::now();
+        let channel = self
+            .ssh_transport("exec")?
+            .open_exec_channel(command)
+            .await?;
+        Ok(ExecStream::new(
+            channel,
+            self.inner.ssh_config.timeout,
+            start,
+        ))
+    }
+
+    /// Open an SFTP client on this session's connection.
+    ///
+    /// Runs the `sftp` subsystem on a new channel, so transfers share the
+    /// authenticated connection with any open PTY channels instead of
+    /// logging in again. Each request is bounded by the session timeout.
+    pub async fn sftp(&self) -> Result<SftpClient> {
+        let channel = self
+            .ssh_transport("SFTP")?
+            .open_subsystem_channel("sftp")
+            .await?;
+        SftpClient::new(channel, self.inner.ssh_config.timeout).await
+    }
+
+    /// Open a NETCONF session on this session's connection.
+    ///
+    /// Runs the `netconf` subsystem on a new channel and exchanges hellos.
+    /// Devices that serve NETCONF only on port 830 need a session
+    /// connected to that port. Each request is bounded by the session
+    /// timeout.
+    pub async fn netconf(&self) -> Result<NetconfClient> {
+        let channel = self
+            .ssh_transport("NETCONF")?
+            .open_subsystem_channel("netconf")
+            .await?;
+        NetconfClient::new(channel, self.inner.ssh_config.timeout).await
+    }
+
+    /// Create an SCP client on this session's connection.
+    ///
+    /// For devices that allow `scp` but not SFTP. Each transfer runs the
+    /// remote `scp` binary over its own `exec` channel.
+    pub fn scp(&self) -> ScpClient {
+        ScpClient::new(self.clone())
+    }
+
+    /// Forward a local TCP port to `remote_host:remote_port` via the device.
+    ///
+    /// Listens on `bind_addr` (e.g. `"127.0.0.1:0"` for an ephemeral port;
+    /// see [`LocalForward::local_addr()`]) and tunnels each connection over
+    /// a `direct-tcpip` channel. `remote_host` is resolved by the server.
+    ///
+    /// Forwarding stops when the returned handle is dropped or the session
+    /// disconnects. Forwards are not re-established by a reconnect.
+    pub async fn forward_local(
+        &self,
+        bind_addr: &str,
+        remote_host: &str,
+        remote_port: u16,
+    ) -> Result<LocalForward> {
+        LocalForward::start(self, bind_addr, remote_host, remote_port).await
+    }
+
+    /// Ask the device to listen on `bind_addr:bind_port` and relay each
+    /// connection to `local_host:local_port` on this side.
+    ///
+    /// A `bind_port` of 0 lets the server pick one; see
+    /// [`RemoteForward::bind_port()`]. Uses a `tcpip-forward` request, which
+    /// the server may refuse (`AllowTcpForwarding`, `GatewayPorts`).
+    ///
+    /// Forwarding stops when the returned handle is dropped or the session
+    /// disconnects. Forwards are not re-established by a reconnect.
+    pub async fn forward_remote(
+        &self,
+        bind_addr: &str,
+        bind_port: u16,
+        local_host: &str,
+        local_port: u16,
+    ) -> Result<RemoteForward> {
+        RemoteForward::start(self, bind_addr, bind_port, local_host, local_port).await
+    }
+
+    /// Open the transport shell backing a new PTY channel.
+    pub(crate) async fn open_pty(&self, terminal: &TerminalOptions) -> Result<PtyChannel> {
+        let shell = self.inner.transport.open_shell(terminal).await?;
+        let platform = self.platform();
+        let config = PtyConfig {
+            pager: Some(Pager::new(
+                platform.pager_patterns.clone(),
+                platform.pager_response.as_bytes(),
+            )),
+            ..PtyConfig::default()
+        };
+        Ok(PtyChannel::new(shell, config))
+    }
+
+    /// Re-establish the SSH connection this session was made with.
+    ///
+    /// Connects again with the session's [`ReconnectPolicy`] (or the default
+    /// policy when none was configured) and returns the new session; this
+    /// one is left as it is. Channels opened on this session move to the new
+    /// one on their own via [`Channel::reconnect()`], which they call
+    /// automatically when a policy is configured.
+    ///
+    /// Every clone of this session shares one successor: concurrent or
+    /// repeated calls return the same new session while it is alive rather
+    /// than opening a connection each.
+    pub async fn reconnect(&self) -> Result<Session> {
+        let mut current = self.clone();
+        loop {
+            let mut successor = current.inner.successor.lock().await;
+            if let Some(next) = successor.clone() {
+                drop(successor);
+                current = next;
+                continue;
+            }
+
+            if !Arc::ptr_eq(&current.inner, &self.inner) && current.is_alive() {
+                return Ok(current.clone());
+            }
+
+            let next = current.connect_with_backoff().await?;
+            *successor = Some(next.clone());
+            return Ok(next);
+        }
+    }
+
+    /// Connect a successor session, retrying according to the policy.
+    async fn connect_with_backoff(&self) -> Result<Session> {
+        let policy = self.inner.ssh_config.reconnect.clone().unwrap_or_default();
+
+        let reason = self
+            .inner
+            .disconnect_rx
+            .borrow()
+            .clone()
+            .unwrap_or_else(|| DisconnectReason::TransportError("connection lost".into()));
+        debug!(
+            "reconnecting to {}:{} after {:?}",
+            self.inner.ssh_config.host, self.inner.ssh_config.port, reason
+        );
+        self.emit(ReconnectEvent::Disconnected { reason });
+
+        let mut last_error = String::new();
+        let mut attempts = 0;
+        while attempts < policy.max_attempts {
+            attempts += 1;
+            let delay = policy.delay(attempts);
+            self.emit(ReconnectEvent::Attempt {
+                attempt: attempts,
+                delay,
+            });
+            tokio::time::sleep(delay).await;
+
+            match transport::connect(&self.inner.ssh_config, &self.inner.platform).await {
+                Ok(transport) => {
+                    debug!("reconnected after {} attempt(s)", attempts);
+                    self.emit(ReconnectEvent::Reconnected { attempts });
+                    return Ok(self.successor_with(transport));
+                }
+                Err(e) => {
+                    warn!("reconnect attempt {} failed: {}", attempts, e);
+                    last_error = e.to_string();
+                    self.emit(ReconnectEvent::AttemptFailed {
+                        attempt: attempts,
+                        error: last_error.clone(),
+                    });
+                    if !is_retryable(&e) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.emit(ReconnectEvent::GaveUp { attempts });
+        Err(DriverError::ReconnectFailed {
+            attempts,
+            message: last_error,
+        }
+        .into())
+    }
+
+    /// Subscribe to reconnect progress for this session and its successors.
+    pub fn reconnect_events(&self) -> broadcast::Receiver<ReconnectEvent> {
+        self.inner.reconnect_events.subscribe()
+    }
+
+    /// Broadcast a reconnect event (dropped when nobody is subscribed).
+    pub(crate) fn emit(&self, event: ReconnectEvent) {
+        let _ = self.inner.reconnect_events.send(event);
+    }
+
+    /// The automatic reconnect policy, if one was configured.
+    pub(crate) fn reconnect_policy(&self) -> Option<&ReconnectPolicy> {
+        self.inner.ssh_config.reconnect.as_ref()
+    }
+
+    /// Algorithms agreed with the server during key exchange.
+    ///
+    /// All empty for Telnet sessions, which negotiate none.
+    pub fn negotiated_algorithms(&self) -> &NegotiatedAlgorithms {
+        &self.connection_info().algorithms
+    }
+
+    /// What was learned about the server while connecting: its SSH
+    /// identification string, pre-login banner, negotiated algorithms and
+    /// the socket addresses.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # fn example(session: &ferrissh::Session) {
+    /// let info = session.connection_info();
+    /// println!(
+    ///     "{} at {:?}, banner: {:?}",
+    ///     info.server_id.as_deref().unwrap_or("telnet"),
+    ///     info.peer_addr,
+    ///     info.banner
+    /// );
+    /// # }
+    /// ```
+    pub fn connection_info(&self) -> &ConnectionInfo {
+        self.inner.transport.connection_info()
+    }
+
+    /// The terminal settings channels are opened with by default.
+    ///
+    /// A starting point for [`open_channel_with()`](Self::open_channel_with).
+    pub fn terminal_options(&self) -> TerminalOptions {
+        self.inner.ssh_config.terminal_options()
+    }
+
+    /// The protocol this session's connection uses.
+    pub fn protocol(&self) -> Protocol {
+        self.inner.transport.protocol()
+    }
+
     /// Check if the underlying SSH transport is still alive.
     pub fn is_alive(&self) -> bool {
         self.inner.transport.is_alive()
@@ -179,6 +528,30 @@ impl Session {
         &self.inner.prompt_patterns
     }
 
+    /// The SSH transport, for opening channels of other kinds.
+    ///
+    /// Fails with [`TransportError::Unsupported`] naming `feature` when the
+    /// session is not SSH.
+    pub(crate) fn ssh_transport(&self, feature: &str) -> Result<&SshTransport> {
+        self.inner.transport.as_ssh().ok_or_else(|| {
+            TransportError::Unsupported {
+                feature: feature.to_string(),
+                protocol: self.protocol().to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Whether channels learn their prompt when they open.
+    pub(crate) fn learns_prompt(&self) -> bool {
+        self.inner.ssh_config.learn_prompt
+    }
+
+    /// The session timeout.
+    pub(crate) fn timeout(&self) -> Duration {
+        self.inner.ssh_config.timeout
+    }
+
     /// Get the shared privilege levels base (Arc-shared across channels).
     pub(crate) fn privilege_base(&self) -> &Arc<PrivilegeLevelsBase> {
         &self.inner.privilege_base
@@ -206,6 +579,11 @@ impl Session {
         }
     }
 
+    /// Get a disconnect receiver for a new channel.
+    pub(crate) fn disconnect_rx(&self) -> &watch::Receiver<Option<DisconnectReason>> {
+        &self.inner.disconnect_rx
+    }
+
     /// Get the disconnect sender.
     pub(crate) fn disconnect_tx(&self) -> &Arc<watch::Sender<Option<DisconnectReason>>> {
         &self.inner.disconnect_tx
@@ -239,9 +617,33 @@ impl Session {
     }
 }
 
+/// Whether a failed connection attempt is worth repeating.
+///
+/// Authentication and host key failures won't go away by waiting, on the
+/// target or on a jump host.
+fn is_retryable(e: &Error) -> bool {
+    match e {
+        Error::Transport(e) => is_retryable_transport(e),
+        _ => true,
+    }
+}
+
+fn is_retryable_transport(e: &TransportError) -> bool {
+    match e {
+        TransportError::JumpHost { source, .. } => is_retryable_transport(source),
+        TransportError::AuthenticationFailed { .. }
+        | TransportError::HostKeyUnknown { .. }
+        | TransportError::HostKeyChanged { .. }
+        | TransportError::HostKeyRejected { .. }
+        | TransportError::Algorithms(_) => false,
+        _ => true,
+    }
+}
+
 impl std::fmt::Debug for Session {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Session")
+            .field("protocol", &self.protocol())
             .field("host", &self.inner.ssh_config.host)
             .field("port", &self.inner.ssh_config.port)
             .field("platform", &self.inner.platform.name)
@@ -276,14 +678,23 @@ impl std::fmt::Debug for Session {
 /// # }
 /// ```
 pub struct SessionBuilder {
+    protocol: Protocol,
     host: String,
-    port: u16,
+    port: Option<u16>,
     username: Option<String>,
-    auth: AuthMethod,
+    auth_methods: Vec<AuthMethod>,
+    config_auth_methods: Vec<AuthMethod>,
+    certificate: Option<CertificateSource>,
     platform: Option<Platform>,
     timeout: Duration,
+    connect_timeout: Option<Duration>,
+    source_address: Option<IpAddr>,
+    bind_interface: Option<String>,
     terminal_width: Option<u32>,
     terminal_height: Option<u32>,
+    terminal_type: String,
+    terminal_modes: Vec<(Pty, u32)>,
+    env: Vec<(String, String)>,
     host_key_verification: HostKeyVerification,
     known_hosts_path: Option<PathBuf>,
     keepalive_interval: Option<Option<Duration>>,
@@ -292,20 +703,34 @@ pub struct SessionBuilder {
     window_size: Option<u32>,
     maximum_packet_size: Option<u32>,
     channel_buffer_size: Option<usize>,
+    jump_hosts: Vec<JumpHost>,
+    proxy: Option<ProxyConfig>,
+    algorithms: AlgorithmPreferences,
+    reconnect: Option<ReconnectPolicy>,
+    learn_prompt: bool,
 }
 
 impl SessionBuilder {
     /// Create a new session builder for the specified host.
     pub fn new(host: impl Into<String>) -> Self {
         Self {
+            protocol: Protocol::Ssh,
             host: host.into(),
-            port: 22,
+            port: None,
             username: None,
-            auth: AuthMethod::None,
+            auth_methods: Vec::new(),
+            config_auth_methods: Vec::new(),
+            certificate: None,
             platform: None,
             timeout: Duration::from_secs(30),
+            connect_timeout: None,
+            source_address: None,
+            bind_interface: None,
             terminal_width: None,
             terminal_height: None,
+            terminal_type: "xterm".to_string(),
+            terminal_modes: Vec::new(),
+            env: Vec::new(),
             host_key_verification: HostKeyVerification::AcceptNew,
             known_hosts_path: None,
             keepalive_interval: None,
@@ -314,12 +739,70 @@ impl SessionBuilder {
             window_size: None,
             maximum_packet_size: None,
             channel_buffer_size: None,
+            jump_hosts: Vec::new(),
+            proxy: None,
+            algorithms: AlgorithmPreferences::default(),
+            reconnect: None,
+            learn_prompt: false,
+        }
+    }
+
+    /// Create a builder for `alias` from `~/.ssh/config`.
+    ///
+    /// Applies `HostName`, `Port`, `User`, `IdentityFile`, `CertificateFile`,
+    /// `UserKnownHostsFile`, `StrictHostKeyChecking`, `ServerAliveInterval`,
+    /// `ServerAliveCountMax`, `ConnectTimeout`, `BindAddress`,
+    /// `BindInterface` and `ProxyJump` from the matching `Host`
+    /// blocks. Every identity file is tried, each certificate with its key;
+    /// they are only used if no authentication method is set.
+    /// Builder methods called afterwards override the file's values.
+    /// A missing config file is treated as empty.
+    pub fn from_ssh_config(alias: &str) -> Result<Self> {
+        let host = OpenSshConfig::load_default()?.resolve(alias)?;
+        Ok(Self::from_host_config(alias, host))
+    }
+
+    /// Create a builder for `alias` from an OpenSSH config file at `path`.
+    ///
+    /// See [`from_ssh_config()`](Self::from_ssh_config).
+    pub fn from_ssh_config_file(alias: &str, path: impl AsRef<Path>) -> Result<Self> {
+        let host = OpenSshConfig::load(path)?.resolve(alias)?;
+        Ok(Self::from_host_config(alias, host))
+    }
+
+    /// Apply resolved OpenSSH config values to a fresh builder.
+    fn from_host_config(alias: &str, host: HostConfig) -> Self {
+        let settings = host.into_settings(alias);
+        let mut builder = Self::new(settings.host);
+        builder.port = settings.port;
+        builder.username = settings.username;
+        builder.config_auth_methods = settings.auth_methods;
+        builder.known_hosts_path = settings.known_hosts_path;
+        if let Some(mode) = settings.host_key_verification {
+            builder.host_key_verification = mode;
         }
+        builder.keepalive_interval = settings.keepalive_interval;
+        builder.keepalive_max = settings.keepalive_max;
+        builder.connect_timeout = settings.connect_timeout;
+        builder.source_address = settings.source_address;
+        builder.bind_interface = settings.bind_interface;
+        builder.jump_hosts = settings.jump_hosts;
+        builder
     }
 
-    /// Set the SSH port (default: 22).
+    /// Set the port (default: 22 for SSH, 23 for Telnet).
     pub fn port(mut self, port: u16) -> Self {
-        self.port = port;
+        self.port = Some(port);
+        self
+    }
+
+    /// Set the protocol (default: [`Protocol::Ssh`]).
+    ///
+    /// Telnet sessions log in with the username and password and carry a
+    /// single channel; exec, file transfer, port forwarding, jump hosts and
+    /// key-based authentication need SSH.
+    pub fn protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
         self
     }
 
@@ -329,31 +812,130 @@ impl SessionBuilder {
         self
     }
 
-    /// Set password authentication.
+    /// Add an authentication method. Methods are tried in the order added.
+    pub fn auth(mut self, method: AuthMethod) -> Self {
+        self.auth_methods.push(method);
+        self
+    }
+
+    /// Add password authentication.
     pub fn password(mut self, password: impl Into<String>) -> Self {
-        self.auth = AuthMethod::Password(SecretString::from(password.into()));
+        self.auth_methods
+            .push(AuthMethod::Password(SecretString::from(password.into())));
         self
     }
 
-    /// Set private key authentication.
+    /// Add private key authentication.
     pub fn private_key(mut self, key_path: impl Into<PathBuf>) -> Self {
-        self.auth = AuthMethod::PrivateKey {
+        self.auth_methods.push(AuthMethod::PrivateKey {
             path: key_path.into(),
             passphrase: None,
-        };
+        });
         self
     }
 
-    /// Set private key authentication with passphrase.
+    /// Add private key authentication with passphrase.
     pub fn private_key_with_passphrase(
         mut self,
         key_path: impl Into<PathBuf>,
         passphrase: impl Into<String>,
     ) -> Self {
-        self.auth = AuthMethod::PrivateKey {
+        self.auth_methods.push(AuthMethod::PrivateKey {
             path: key_path.into(),
             passphrase: Some(SecretString::from(passphrase.into())),
-        };
+        });
+        self
+    }
+
+    /// Add private key authentication from in-memory key material.
+    pub fn private_key_pem(mut self, pem: impl Into<String>) -> Self {
+        self.auth_methods.push(AuthMethod::PrivateKeyPem {
+            pem: SecretString::from(pem.into()),
+            passphrase: None,
+        });
+        self
+    }
+
+    /// Add private key authentication from in-memory key material with passphrase.
+    pub fn private_key_pem_with_passphrase(
+        mut self,
+        pem: impl Into<String>,
+        passphrase: impl Into<String>,
+    ) -> Self {
+        self.auth_methods.push(AuthMethod::PrivateKeyPem {
+            pem: SecretString::from(pem.into()),
+            passphrase: Some(SecretString::from(passphrase.into())),
+        });
+        self
+    }
+
+    /// Authenticate with an OpenSSH user certificate file (`*-cert.pub`).
+    pub fn certificate(mut self, cert_path: impl Into<PathBuf>) -> Self {
+        self.certificate = Some(CertificateSource::Path(cert_path.into()));
+        self
+    }
+
+    /// Authenticate with an in-memory OpenSSH user certificate.
+    pub fn certificate_openssh(mut self, cert: impl Into<String>) -> Self {
+        self.certificate = Some(CertificateSource::OpenSsh(cert.into()));
+        self
+    }
+
+    /// Add keyboard-interactive authentication with a custom responder.
+    pub fn keyboard_interactive(
+        mut self,
+        responder: Arc<dyn KeyboardInteractiveResponder>,
+    ) -> Self {
+        self.auth_methods
+            .push(AuthMethod::KeyboardInteractive(responder));
+        self
+    }
+
+    /// Add SSH agent authentication using the socket in `SSH_AUTH_SOCK`.
+    pub fn agent(mut self) -> Self {
+        self.auth_methods.push(AuthMethod::Agent { socket: None });
+        self
+    }
+
+    /// Add SSH agent authentication using an explicit agent socket path.
+    pub fn agent_socket(mut self, socket: impl Into<PathBuf>) -> Self {
+        self.auth_methods.push(AuthMethod::Agent {
+            socket: Some(socket.into()),
+        });
+        self
+    }
+
+    /// Tunnel the connection through a jump host (`ProxyJump`).
+    ///
+    /// Call repeatedly to chain several jump hosts, outermost first.
+    pub fn jump_host(mut self, jump: JumpHost) -> Self {
+        self.jump_hosts.push(jump);
+        self
+    }
+
+    /// Connect through a SOCKS5 or HTTP CONNECT proxy.
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Set key exchange, host key, cipher and MAC algorithm preferences.
+    pub fn algorithms(mut self, algorithms: AlgorithmPreferences) -> Self {
+        self.algorithms = algorithms;
+        self
+    }
+
+    /// Reconnect automatically after the connection is lost (default: off).
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    /// Learn each channel's exact prompt when it opens (default: off).
+    ///
+    /// See [`Channel::learn_prompt()`](crate::Channel::learn_prompt).
+    pub fn learn_prompt(mut self, learn: bool) -> Self {
+        self.learn_prompt = learn;
         self
     }
 
@@ -363,12 +945,40 @@ impl SessionBuilder {
         self
     }
 
-    /// Set the connection timeout.
+    /// Set the operation timeout: how long commands wait for a prompt
+    /// (default: 30 seconds). Also bounds connecting, unless
+    /// [`connect_timeout()`](Self::connect_timeout) is set.
     pub fn timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
 
+    /// Set how long the TCP connection and SSH handshake may take
+    /// (default: the [`timeout()`](Self::timeout)).
+    ///
+    /// Lets an unreachable device fail fast while slow commands keep a
+    /// generous timeout.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Connect from this local address (OpenSSH's `BindAddress`).
+    ///
+    /// On multi-homed hosts this picks the address device ACLs expect. Only
+    /// the host's addresses of the same family (IPv4 or IPv6) are tried.
+    pub fn source_address(mut self, address: IpAddr) -> Self {
+        self.source_address = Some(address);
+        self
+    }
+
+    /// Connect through this network interface (OpenSSH's
+    /// `BindInterface`). Linux only; elsewhere connecting fails.
+    pub fn bind_interface(mut self, interface: impl Into<String>) -> Self {
+        self.bind_interface = Some(interface.into());
+        self
+    }
+
     /// Set the host key verification mode (default: `AcceptNew`).
     pub fn host_key_verification(mut self, mode: HostKeyVerification) -> Self {
         self.host_key_verification = mode;
@@ -394,6 +1004,31 @@ impl SessionBuilder {
         self
     }
 
+    /// Set the terminal type (default: `"xterm"`).
+    ///
+    /// Some devices only render their CLI correctly for `vt100`.
+    pub fn terminal_type(mut self, term: impl Into<String>) -> Self {
+        self.terminal_type = term.into();
+        self
+    }
+
+    /// Add a PTY mode sent with every PTY request (e.g. `(Pty::ECHO, 0)`).
+    ///
+    /// Not used over Telnet.
+    pub fn terminal_mode(mut self, mode: Pty, value: u32) -> Self {
+        self.terminal_modes.push((mode, value));
+        self
+    }
+
+    /// Add an environment variable set on every shell (e.g. `LANG`).
+    ///
+    /// The server must allow the name (OpenSSH: `AcceptEnv`); others are
+    /// silently dropped. Not used over Telnet.
+    pub fn env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((name.into(), value.into()));
+        self
+    }
+
     /// Set the SSH keepalive interval.
     pub fn keepalive_interval(mut self, interval: Option<Duration>) -> Self {
         self.keepalive_interval = Some(interval);
@@ -455,7 +1090,8 @@ impl SessionBuilder {
             }
             .into());
         }
-        if self.port == 0 {
+        let port = self.port.unwrap_or(self.protocol.default_port());
+        if port == 0 {
             return Err(DriverError::InvalidConfig {
                 message: "Port cannot be 0".to_string(),
             }
@@ -466,10 +1102,73 @@ impl SessionBuilder {
             message: "Username is required".to_string(),
         })?;
 
-        if matches!(self.auth, AuthMethod::None) {
+        if let Some(proxy) = &self.proxy
+            && (proxy.host().is_empty() || proxy.port() == 0)
+        {
+            return Err(DriverError::InvalidConfig {
+                message: format!("Invalid proxy '{}:{}'", proxy.host(), proxy.port()),
+            }
+            .into());
+        }
+
+        for jump in &self.jump_hosts {
+            if jump.host.is_empty() || jump.port == 0 {
+                return Err(DriverError::InvalidConfig {
+                    message: format!("Invalid jump host '{}:{}'", jump.host, jump.port),
+                }
+                .into());
+            }
+        }
+
+        if self.protocol == Protocol::Telnet && !self.jump_hosts.is_empty() {
+            return Err(DriverError::InvalidConfig {
+                message: "Jump hosts are not supported over Telnet".to_string(),
+            }
+            .into());
+        }
+
+        self.algorithms
+            .to_preferred()
+            .map_err(|e| DriverError::InvalidConfig {
+                message: e.to_string(),
+            })?;
+
+        if let Some(policy) = &self.reconnect {
+            policy
+                .validate()
+                .map_err(|message| DriverError::InvalidConfig { message })?;
+        }
+
+        let mut auth_methods = self.auth_methods;
+        if auth_methods.is_empty() {
+            auth_methods = self.config_auth_methods;
+        }
+
+        if let Some(cert) = self.certificate
+            && !AuthMethod::attach_certificate(&mut auth_methods, cert)
+        {
             return Err(DriverError::InvalidConfig {
-                message: "Authentication method is required - call password() or private_key()"
-                    .to_string(),
+                message: "certificate() requires private_key() or private_key_pem()".to_string(),
+            }
+            .into());
+        }
+
+        if auth_methods.is_empty() {
+            return Err(DriverError::InvalidConfig {
+                message:
+                    "Authentication method is required - call password(), private_key() or agent()"
+                        .to_string(),
+            }
+            .into());
+        }
+
+        if self.protocol == Protocol::Telnet
+            && !auth_methods
+                .iter()
+                .all(|method| matches!(method, AuthMethod::Password(_)))
+        {
+            return Err(DriverError::InvalidConfig {
+                message: "Telnet only supports password authentication".to_string(),
             }
             .into());
         }
@@ -482,13 +1181,20 @@ impl SessionBuilder {
         let platform = PlatformDefinition::from(platform);
 
         let ssh_config = SshConfig {
+            protocol: self.protocol,
             host: self.host,
-            port: self.port,
+            port,
             username,
-            auth: self.auth,
+            auth_methods,
             timeout: self.timeout,
+            connect_timeout: self.connect_timeout.unwrap_or(self.timeout),
+            source_address: self.source_address,
+            bind_interface: self.bind_interface,
             terminal_width: self.terminal_width.unwrap_or(platform.terminal_width),
             terminal_height: self.terminal_height.unwrap_or(platform.terminal_height),
+            terminal_type: self.terminal_type,
+            terminal_modes: self.terminal_modes,
+            env: self.env,
             host_key_verification: self.host_key_verification,
             known_hosts_path: self.known_hosts_path,
             keepalive_interval: self
@@ -499,6 +1205,11 @@ impl SessionBuilder {
             window_size: self.window_size,
             maximum_packet_size: self.maximum_packet_size,
             channel_buffer_size: self.channel_buffer_size,
+            jump_hosts: self.jump_hosts,
+            proxy: self.proxy,
+            algorithms: self.algorithms,
+            reconnect: self.reconnect,
+            learn_prompt: self.learn_prompt,
         };
 
         debug!(
@@ -506,9 +1217,15 @@ impl SessionBuilder {
             ssh_config.host, ssh_config.port, platform.name
         );
 
-        let transport = SshTransport::connect(ssh_config.clone()).await?;
+        let transport = transport::connect(&ssh_config, &platform).await?;
 
-        Ok(Session::new(transport, platform, ssh_config))
+        let (reconnect_events, _) = broadcast::channel(RECONNECT_EVENT_CAPACITY);
+        Ok(Session::new(
+            transport,
+            platform,
+            ssh_config,
+            reconnect_events,
+        ))
     }
 }
 
@@ -542,4 +1259,21 @@ mod tests {
         let err = result.unwrap_err().to_string();
         assert!(err.contains("Port cannot be 0"), "got: {}", err);
     }
+
+    #[test]
+    fn test_jump_host_auth_failure_is_not_retried() {
+        let auth = TransportError::AuthenticationFailed {
+            user: "admin".to_string(),
+            methods: vec!["password".to_string()],
+        };
+        let jump = |source| {
+            Error::Transport(TransportError::JumpHost {
+                host: "bastion".to_string(),
+                port: 22,
+                source: Box::new(source),
+            })
+        };
+        assert!(!is_retryable(&jump(auth)));
+        assert!(is_retryable(&jump(TransportError::Disconnected)));
+    }
 }
@@ -0,0 +1,290 @@
+//! File transfer over an authenticated session.
+//!
+//! [`Session::sftp()`](crate::Session::sftp) opens an [`SftpClient`] on the
+//! session's existing SSH connection — no second login — for uploads,
+//! downloads and remote file management alongside any open PTY channels.
+//!
+//! For devices that allow `scp` but not SFTP,
+//! [`Session::scp()`](crate::Session::scp) returns an [`ScpClient`] that
+//! speaks the SCP protocol to the remote `scp` binary over an `exec`
+//! channel, including recursive copies with modes and timestamps.
+//!
+//! Transfers take [`TransferOptions`] to report progress and, over SFTP, to
+//! verify the copy afterwards: with verification on, the destination is
+//! read back and its SHA-256 compared against the data that was sent.
+//!
+//! # Usage
+//!
+//! ```rust,no_run
+//! use ferrissh::TransferOptions;
+//!
+//! # async fn example(session: &ferrissh::Session) -> Result<(), ferrissh::Error> {
+//! let sftp = session.sftp().await?;
+//!
+//! let options = TransferOptions::new()
+//!     .verify_checksum(true)
+//!     .on_progress(|p| println!("{}/{} bytes", p.transferred, p.total));
+//! let report = sftp.upload("junos-23.4R1.tgz", "/var/tmp/junos-23.4R1.tgz", &options).await?;
+//! println!("sent {} bytes, sha256 {}", report.bytes, report.sha256);
+//!
+//! sftp.close().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod scp;
+mod sftp;
+
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+pub use scp::ScpClient;
+pub use sftp::{FileKind, FileStat, SftpClient, SftpEntry};
+
+use crate::error::{ChannelError, Error, Result};
+
+/// Size of each read/write during a transfer.
+const CHUNK_SIZE: usize = 32 * 1024;
+
+/// Callback invoked with transfer progress.
+pub type ProgressCallback = Arc<dyn Fn(&TransferProgress) + Send + Sync>;
+
+/// Progress of a single file transfer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferProgress {
+    /// Path of the file being copied, on the source side.
+    pub path: String,
+    /// Bytes of this file copied so far.
+    pub transferred: u64,
+    /// Size of the file.
+    pub total: u64,
+}
+
+impl TransferProgress {
+    /// Fraction of the file transferred, between 0.0 and 1.0.
+    ///
+    /// An empty file counts as complete.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.transferred as f64 / self.total as f64).min(1.0)
+        }
+    }
+}
+
+/// Options for an upload or download.
+#[derive(Clone, Default)]
+pub struct TransferOptions {
+    /// Called after every chunk is written.
+    progress: Option<ProgressCallback>,
+    /// Read the destination back and compare checksums afterwards.
+    verify_checksum: bool,
+}
+
+impl TransferOptions {
+    /// Create options with no progress reporting and no verification.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `callback` with the running byte count after every chunk.
+    ///
+    /// Recursive transfers report each file separately, starting from 0.
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(&TransferProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Verify the transfer by comparing SHA-256 checksums (default: false).
+    ///
+    /// Doubles the I/O on the destination side, since the whole file is
+    /// read back after it has been written. Only SFTP can read files back;
+    /// SCP transfers ignore this and report `verified: false`.
+    pub fn verify_checksum(mut self, verify: bool) -> Self {
+        self.verify_checksum = verify;
+        self
+    }
+
+    fn report(&self, path: &str, transferred: u64, total: u64) {
+        if let Some(callback) = &self.progress {
+            callback(&TransferProgress {
+                path: path.to_string(),
+                transferred,
+                total,
+            });
+        }
+    }
+}
+
+impl fmt::Debug for TransferOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransferOptions")
+            .field("progress", &self.progress.is_some())
+            .field("verify_checksum", &self.verify_checksum)
+            .finish()
+    }
+}
+
+/// Outcome of a completed upload or download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferReport {
+    /// Number of bytes copied.
+    pub bytes: u64,
+    /// Number of files copied.
+    pub files: u64,
+    /// Lowercase hex SHA-256 of the data copied (of all files' contents in
+    /// transfer order, for a recursive copy).
+    pub sha256: String,
+    /// Whether the destination was read back and its checksum matched.
+    pub verified: bool,
+    /// Wall-clock time of the transfer, including verification.
+    pub elapsed: Duration,
+}
+
+/// Copy `reader` into `writer`, hashing and reporting progress as it goes.
+///
+/// Returns the byte count and hex SHA-256 of everything copied. The writer
+/// is shut down (flushed, or closed for remote files) before returning.
+async fn copy_hashed<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    path: &str,
+    total: u64,
+    options: &TransferOptions,
+    read_err: impl Fn(std::io::Error) -> Error,
+    write_err: impl Fn(std::io::Error) -> Error,
+) -> Result<(u64, String)>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut transferred = 0u64;
+
+    options.report(path, 0, total);
+    loop {
+        let n = reader.read(&mut buf).await.map_err(&read_err)?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buf[..n]).await.map_err(&write_err)?;
+        hasher.update(&buf[..n]);
+        transferred += n as u64;
+        options.report(path, transferred, total);
+    }
+    writer.shutdown().await.map_err(&write_err)?;
+
+    Ok((transferred, hex(&hasher.finalize())))
+}
+
+/// Hex SHA-256 of everything `reader` yields.
+async fn sha256_of<R>(reader: &mut R, read_err: impl Fn(std::io::Error) -> Error) -> Result<String>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        let n = reader.read(&mut buf).await.map_err(&read_err)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+/// Hex SHA-256 of a local file.
+async fn local_sha256(path: &Path) -> Result<String> {
+    let mut file = tokio::fs::File::open(path).await.map_err(local_io(path))?;
+    sha256_of(&mut file, local_io(path)).await
+}
+
+/// Map a local I/O error on `path` to [`ChannelError::LocalIo`].
+fn local_io(path: &Path) -> impl Fn(std::io::Error) -> Error + '_ {
+    move |source| {
+        ChannelError::LocalIo {
+            path: path.to_path_buf(),
+            source,
+        }
+        .into()
+    }
+}
+
+/// Lowercase hex encoding.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_copy_hashed_reports_progress_and_hash() {
+        let data = vec![7u8; CHUNK_SIZE * 2 + 10];
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let options = TransferOptions::new().on_progress(move |p| {
+            recorder.lock().unwrap().push(p.transferred);
+        });
+
+        let mut out = Vec::new();
+        let (bytes, sha256) = copy_hashed(
+            &mut data.as_slice(),
+            &mut out,
+            "mem",
+            data.len() as u64,
+            &options,
+            local_io(Path::new("in")),
+            local_io(Path::new("out")),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(out, data);
+        assert_eq!(bytes, data.len() as u64);
+        assert_eq!(sha256, hex(&Sha256::digest(&data)));
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.first(), Some(&0));
+        assert_eq!(seen.last(), Some(&bytes));
+        assert!(seen.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_hex_and_empty_sha256() {
+        assert_eq!(hex(&[0x00, 0x0f, 0xab]), "000fab");
+        assert_eq!(
+            hex(&Sha256::digest(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_progress_fraction() {
+        let half = TransferProgress {
+            path: "a".into(),
+            transferred: 50,
+            total: 100,
+        };
+        assert_eq!(half.fraction(), 0.5);
+        let empty = TransferProgress {
+            path: "b".into(),
+            transferred: 0,
+            total: 0,
+        };
+        assert_eq!(empty.fraction(), 1.0);
+    }
+}
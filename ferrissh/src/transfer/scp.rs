@@ -0,0 +1,757 @@
+//! SCP over an `exec` channel.
+//!
+//! Speaks the classic `rcp`/`scp` protocol to the remote `scp` binary: as
+//! the source for uploads (the remote runs `scp -t`) and as the sink for
+//! downloads (the remote runs `scp -f`). Every control record is one line,
+//! answered with a zero byte; a `1` or `2` byte followed by a message line
+//! reports an error instead.
+
+use std::fs::FileTimes;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use bytes::{Buf, BytesMut};
+use log::debug;
+use russh::ChannelMsg;
+use russh::client::Msg;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use super::{CHUNK_SIZE, TransferOptions, TransferReport, hex, local_io};
+use crate::error::{ChannelError, Error, Result, TransportError};
+use crate::session::Session;
+
+/// Longest control record accepted from the remote.
+const MAX_RECORD_LEN: usize = 64 * 1024;
+
+/// An SCP client on an existing SSH connection.
+///
+/// Created by [`Session::scp()`](crate::Session::scp). Each upload or
+/// download runs the remote `scp` binary on its own `exec` channel, so
+/// several can run at once alongside PTY channels.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use ferrissh::TransferOptions;
+///
+/// # async fn example(session: &ferrissh::Session) -> Result<(), ferrissh::Error> {
+/// let scp = session.scp().recursive(true).preserve(true);
+/// scp.download("/var/log", "backup/logs", &TransferOptions::new()).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ScpClient {
+    /// The session whose connection carries the transfers.
+    session: Session,
+    /// Copy directories recursively (`scp -r`).
+    recursive: bool,
+    /// Preserve modes and timestamps (`scp -p`).
+    preserve: bool,
+}
+
+impl ScpClient {
+    /// Create a client that copies single files without preserving times.
+    pub(crate) fn new(session: Session) -> Self {
+        Self {
+            session,
+            recursive: false,
+            preserve: false,
+        }
+    }
+
+    /// Copy directories and their contents (default: false).
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Preserve permission modes and modification/access times
+    /// (default: false).
+    ///
+    /// Without it, uploaded files get the remote's default mode and
+    /// downloaded files the local umask, both with the current time.
+    pub fn preserve(mut self, preserve: bool) -> Self {
+        self.preserve = preserve;
+        self
+    }
+
+    /// Upload the local file or directory `local` to `remote`.
+    ///
+    /// If `remote` is an existing directory the copy is placed inside it,
+    /// otherwise it is created (or replaced) under that name. Directories
+    /// need [`recursive(true)`](Self::recursive); entries that are neither
+    /// regular files nor directories are skipped.
+    pub async fn upload(
+        &self,
+        local: impl AsRef<Path>,
+        remote: &str,
+        options: &TransferOptions,
+    ) -> Result<TransferReport> {
+        let local = local.as_ref();
+        let start = Instant::now();
+
+        let metadata = tokio::fs::metadata(local).await.map_err(local_io(local))?;
+        if metadata.is_dir() && !self.recursive {
+            return Err(ChannelError::Transfer {
+                path: local.display().to_string(),
+                message: "is a directory (enable recursive to copy it)".to_string(),
+            }
+            .into());
+        }
+
+        let mut scp = ScpChannel::open(&self.session, &self.command("-t", remote)).await?;
+        scp.expect_ack().await?;
+
+        let mut tally = Tally::default();
+        let mut pending = vec![Upload::Visit(local.to_path_buf())];
+        while let Some(step) = pending.pop() {
+            let path = match step {
+                Upload::Visit(path) => path,
+                Upload::Leave => {
+                    scp.record("E").await?;
+                    continue;
+                }
+            };
+
+            let metadata = tokio::fs::metadata(&path).await.map_err(local_io(&path))?;
+            if !metadata.is_dir() && !metadata.is_file() {
+                debug!("scp: skipping {}: not a file or directory", path.display());
+                continue;
+            }
+            let name = record_name(&path)?;
+            if self.preserve {
+                scp.record(&times_record(&metadata)).await?;
+            }
+
+            let mode = local_mode(&metadata);
+            if metadata.is_dir() {
+                scp.record(&format!("D{mode:04o} 0 {name}")).await?;
+                pending.push(Upload::Leave);
+                let mut children = Vec::new();
+                let mut entries = tokio::fs::read_dir(&path).await.map_err(local_io(&path))?;
+                while let Some(entry) = entries.next_entry().await.map_err(local_io(&path))? {
+                    children.push(entry.path());
+                }
+                children.sort();
+                pending.extend(children.into_iter().rev().map(Upload::Visit));
+            } else {
+                scp.record(&format!("C{mode:04o} {} {name}", metadata.len()))
+                    .await?;
+                send_file(&mut scp, &path, metadata.len(), options, &mut tally).await?;
+            }
+        }
+
+        scp.finish().await?;
+        debug!(
+            "scp: uploaded {} files ({} bytes) to {}",
+            tally.files, tally.bytes, remote
+        );
+        Ok(tally.into_report(start))
+    }
+
+    /// Download the remote file or directory `remote` to `local`.
+    ///
+    /// If `local` is an existing directory the copy is placed inside it,
+    /// otherwise it is created (or replaced) under that name. Directories
+    /// need [`recursive(true)`](Self::recursive).
+    ///
+    /// The remote must send back the file or directory asked for: one named
+    /// like the last component of `remote`, and only one unless copying
+    /// recursively. Anything else fails the download, so a malicious server
+    /// cannot write other files into `local`.
+    pub async fn download(
+        &self,
+        remote: &str,
+        local: impl AsRef<Path>,
+        options: &TransferOptions,
+    ) -> Result<TransferReport> {
+        let local = local.as_ref();
+        let start = Instant::now();
+        let into_dir = tokio::fs::metadata(local)
+            .await
+            .is_ok_and(|metadata| metadata.is_dir());
+
+        let mut scp = ScpChannel::open(&self.session, &self.command("-f", remote)).await?;
+        scp.ack().await?;
+
+        let mut tally = Tally::default();
+        let mut times: Option<Times> = None;
+        let mut dirs: Vec<OpenDir> = Vec::new();
+        let mut top_level_entries = 0;
+        while let Some(kind) = scp.read_byte().await? {
+            match kind {
+                b'T' => {
+                    times = Some(parse_times(&scp.read_line().await?)?);
+                    scp.ack().await?;
+                }
+                b'C' | b'D' => {
+                    let entry = parse_entry(&scp.read_line().await?)?;
+                    if dirs.is_empty() {
+                        top_level_entries += 1;
+                        if top_level_entries > 1 && !self.recursive {
+                            return Err(protocol("more than one file sent for a single file"));
+                        }
+                        check_requested_name(remote, &entry.name)?;
+                    }
+                    let (target, source) = match dirs.last() {
+                        Some(dir) => (
+                            dir.local.join(&entry.name),
+                            format!("{}/{}", dir.source.trim_end_matches('/'), entry.name),
+                        ),
+                        None if into_dir => (local.join(&entry.name), remote.to_string()),
+                        None => (local.to_path_buf(), remote.to_string()),
+                    };
+
+                    if kind == b'D' {
+                        if !self.recursive {
+                            return Err(protocol("directory sent for a non-recursive copy"));
+                        }
+                        create_dir(&target).await?;
+                        dirs.push(OpenDir {
+                            local: target,
+                            source,
+                            mode: entry.mode,
+                            times: times.take(),
+                        });
+                        scp.ack().await?;
+                        continue;
+                    }
+
+                    scp.ack().await?;
+                    receive_file(&mut scp, &target, &source, entry.size, options, &mut tally)
+                        .await?;
+                    scp.expect_ack().await?;
+                    let times = times.take();
+                    if self.preserve {
+                        apply_metadata(&target, entry.mode, times)?;
+                    }
+                    scp.ack().await?;
+                }
+                b'E' => {
+                    scp.read_line().await?;
+                    let dir = dirs
+                        .pop()
+                        .ok_or_else(|| protocol("end of directory outside any directory"))?;
+                    if self.preserve {
+                        apply_metadata(&dir.local, dir.mode, dir.times)?;
+                    }
+                    scp.ack().await?;
+                }
+                1 | 2 => {
+                    let message = scp.read_line().await?;
+                    return Err(ChannelError::ScpRemote { message }.into());
+                }
+                other => {
+                    return Err(protocol(format!("unexpected record type {other:#04x}")));
+                }
+            }
+        }
+        if !dirs.is_empty() {
+            return Err(scp.ended_early());
+        }
+
+        scp.finish().await?;
+        debug!(
+            "scp: downloaded {} files ({} bytes) from {}",
+            tally.files, tally.bytes, remote
+        );
+        Ok(tally.into_report(start))
+    }
+
+    /// The remote `scp` command line for `mode` (`-t` or `-f`).
+    fn command(&self, mode: &str, path: &str) -> String {
+        let mut command = String::from("scp ");
+        if self.recursive {
+            command.push_str("-r ");
+        }
+        if self.preserve {
+            command.push_str("-p ");
+        }
+        command.push_str(mode);
+        command.push(' ');
+        command.push_str(&shell_quote(path));
+        command
+    }
+}
+
+/// A step in the depth-first walk of an upload.
+enum Upload {
+    /// Send this file or directory.
+    Visit(PathBuf),
+    /// Close the directory opened most recently.
+    Leave,
+}
+
+/// A directory being received, whose metadata is applied once it is closed.
+struct OpenDir {
+    local: PathBuf,
+    source: String,
+    mode: u32,
+    times: Option<Times>,
+}
+
+/// Modification and access times from a `T` record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Times {
+    modified: SystemTime,
+    accessed: SystemTime,
+}
+
+/// A parsed `C` (file) or `D` (directory) record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    mode: u32,
+    size: u64,
+    name: String,
+}
+
+/// Running totals across the files of one transfer.
+#[derive(Default)]
+struct Tally {
+    hasher: Sha256,
+    bytes: u64,
+    files: u64,
+}
+
+impl Tally {
+    fn into_report(self, start: Instant) -> TransferReport {
+        TransferReport {
+            bytes: self.bytes,
+            files: self.files,
+            sha256: hex(&self.hasher.finalize()),
+            verified: false,
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+/// The `exec` channel running the remote `scp`, with buffered reads.
+struct ScpChannel {
+    channel: russh::Channel<Msg>,
+    /// Output received from the remote but not consumed yet.
+    buf: BytesMut,
+    /// Everything the remote wrote to stderr.
+    stderr: Vec<u8>,
+    /// Exit status, once reported.
+    exit_status: Option<u32>,
+    /// Whether the channel has closed.
+    closed: bool,
+    /// Maximum time to wait for the next message.
+    timeout: Duration,
+}
+
+impl ScpChannel {
+    async fn open(session: &Session, command: &str) -> Result<Self> {
+        debug!("scp: running {:?}", command);
+        let channel = session
+            .ssh_transport("SCP")?
+            .open_exec_channel(command)
+            .await?;
+        Ok(Self {
+            channel,
+            buf: BytesMut::new(),
+            stderr: Vec::new(),
+            exit_status: None,
+            closed: false,
+            timeout: session.timeout(),
+        })
+    }
+
+    /// Wait for more output. Returns `false` once the channel has closed.
+    async fn fill(&mut self) -> Result<bool> {
+        while !self.closed {
+            let msg = tokio::time::timeout(self.timeout, self.channel.wait())
+                .await
+                .map_err(|_| TransportError::Timeout(self.timeout))?;
+            match msg {
+                Some(ChannelMsg::Data { data }) if !data.is_empty() => {
+                    self.buf.extend_from_slice(&data);
+                    return Ok(true);
+                }
+                Some(ChannelMsg::ExtendedData { data, ext: 1 }) => {
+                    self.stderr.extend_from_slice(&data);
+                }
+                Some(ChannelMsg::ExitStatus { exit_status }) => {
+                    self.exit_status = Some(exit_status);
+                }
+                Some(ChannelMsg::Failure) => return Err(ChannelError::ExecRequestFailed.into()),
+                Some(ChannelMsg::Close) | None => self.closed = true,
+                Some(_) => {}
+            }
+        }
+        Ok(false)
+    }
+
+    /// The error for the remote ending the conversation before it should.
+    fn ended_early(&self) -> Error {
+        let stderr = String::from_utf8_lossy(&self.stderr).trim().to_string();
+        if !stderr.is_empty() {
+            return ChannelError::ScpRemote { message: stderr }.into();
+        }
+        match self.exit_status {
+            Some(code) => protocol(format!("remote scp exited with status {code}")),
+            None => protocol("channel closed unexpectedly"),
+        }
+    }
+
+    /// Read one byte, or `None` if the channel has closed.
+    async fn read_byte(&mut self) -> Result<Option<u8>> {
+        if self.buf.is_empty() && !self.fill().await? {
+            return Ok(None);
+        }
+        Ok(Some(self.buf.get_u8()))
+    }
+
+    /// Read the rest of a record, without its trailing newline.
+    async fn read_line(&mut self) -> Result<String> {
+        loop {
+            if let Some(pos) = memchr::memchr(b'\n', &self.buf) {
+                let line = self.buf.split_to(pos + 1);
+                return Ok(String::from_utf8_lossy(&line[..pos]).into_owned());
+            }
+            if self.buf.len() > MAX_RECORD_LEN {
+                return Err(protocol("record too long"));
+            }
+            if !self.fill().await? {
+                return Err(self.ended_early());
+            }
+        }
+    }
+
+    /// Read up to `max` bytes of file data.
+    async fn read_chunk(&mut self, max: u64) -> Result<BytesMut> {
+        if self.buf.is_empty() && !self.fill().await? {
+            return Err(self.ended_early());
+        }
+        let n = usize::try_from(max).map_or(self.buf.len(), |max| max.min(self.buf.len()));
+        Ok(self.buf.split_to(n))
+    }
+
+    /// Wait for the remote to accept what was just sent.
+    async fn expect_ack(&mut self) -> Result<()> {
+        match self.read_byte().await? {
+            Some(0) => Ok(()),
+            Some(1 | 2) => {
+                let message = self.read_line().await?;
+                Err(ChannelError::ScpRemote { message }.into())
+            }
+            Some(other) => Err(protocol(format!(
+                "expected acknowledgement, got byte {other:#04x}"
+            ))),
+            None => Err(self.ended_early()),
+        }
+    }
+
+    async fn send(&self, data: &[u8]) -> Result<()> {
+        self.channel.data(data).await.map_err(TransportError::Ssh)?;
+        Ok(())
+    }
+
+    /// Accept what the remote just sent.
+    async fn ack(&self) -> Result<()> {
+        self.send(&[0]).await
+    }
+
+    /// Send a control record and wait for it to be accepted.
+    async fn record(&mut self, record: &str) -> Result<()> {
+        self.send(format!("{record}\n").as_bytes()).await?;
+        self.expect_ack().await
+    }
+
+    /// Signal the end of input and wait for the remote to exit.
+    async fn finish(mut self) -> Result<()> {
+        let _ = self.channel.eof().await;
+        while self.fill().await? {
+            self.buf.clear();
+        }
+        match self.exit_status {
+            Some(0) | None => Ok(()),
+            Some(_) => Err(self.ended_early()),
+        }
+    }
+}
+
+/// Send the contents of a file whose `C` record was just accepted.
+async fn send_file(
+    scp: &mut ScpChannel,
+    path: &Path,
+    size: u64,
+    options: &TransferOptions,
+    tally: &mut Tally,
+) -> Result<()> {
+    let label = path.display().to_string();
+    let mut file = tokio::fs::File::open(path).await.map_err(local_io(path))?;
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut sent = 0u64;
+
+    options.report(&label, 0, size);
+    while sent < size {
+        let want = (size - sent).min(CHUNK_SIZE as u64) as usize;
+        let n = file.read(&mut buf[..want]).await.map_err(local_io(path))?;
+        if n == 0 {
+            return Err(ChannelError::Transfer {
+                path: label,
+                message: "file shrank while being copied".to_string(),
+            }
+            .into());
+        }
+        scp.send(&buf[..n]).await?;
+        tally.hasher.update(&buf[..n]);
+        sent += n as u64;
+        options.report(&label, sent, size);
+    }
+
+    scp.ack().await?;
+    scp.expect_ack().await?;
+    tally.bytes += size;
+    tally.files += 1;
+    Ok(())
+}
+
+/// Receive `size` bytes of file data into `target`.
+async fn receive_file(
+    scp: &mut ScpChannel,
+    target: &Path,
+    source: &str,
+    size: u64,
+    options: &TransferOptions,
+    tally: &mut Tally,
+) -> Result<()> {
+    let mut file = tokio::fs::File::create(target)
+        .await
+        .map_err(local_io(target))?;
+    let mut received = 0u64;
+
+    options.report(source, 0, size);
+    while received < size {
+        let chunk = scp.read_chunk(size - received).await?;
+        file.write_all(&chunk).await.map_err(local_io(target))?;
+        tally.hasher.update(&chunk);
+        received += chunk.len() as u64;
+        options.report(source, received, size);
+    }
+    file.shutdown().await.map_err(local_io(target))?;
+
+    tally.bytes += size;
+    tally.files += 1;
+    Ok(())
+}
+
+/// Create a directory being received, reusing it if it already exists.
+async fn create_dir(path: &Path) -> Result<()> {
+    match tokio::fs::create_dir(path).await {
+        Err(e) if !(e.kind() == std::io::ErrorKind::AlreadyExists && path.is_dir()) => {
+            Err(local_io(path)(e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Set the mode and, if known, the timestamps of a received file or
+/// directory.
+fn apply_metadata(path: &Path, mode: u32, times: Option<Times>) -> Result<()> {
+    // Times first: a restrictive mode could stop us opening the file.
+    if let Some(times) = times {
+        let file = std::fs::File::open(path).map_err(local_io(path))?;
+        file.set_times(
+            FileTimes::new()
+                .set_modified(times.modified)
+                .set_accessed(times.accessed),
+        )
+        .map_err(local_io(path))?;
+    }
+    set_mode(path, mode)
+}
+
+/// The mode a local file or directory is sent with.
+#[cfg(unix)]
+fn local_mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o7777
+}
+
+/// The mode a local file or directory is sent with: without Unix modes,
+/// the usual defaults (read-only files lose write permission).
+#[cfg(not(unix))]
+fn local_mode(metadata: &std::fs::Metadata) -> u32 {
+    match (metadata.is_dir(), metadata.permissions().readonly()) {
+        (true, _) => 0o755,
+        (false, true) => 0o444,
+        (false, false) => 0o644,
+    }
+}
+
+/// Set the mode of a received file or directory.
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).map_err(local_io(path))
+}
+
+/// Modes are not applied without Unix permissions.
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+/// The name a local file is sent under.
+fn record_name(path: &Path) -> Result<String> {
+    let name = path.file_name().and_then(|name| name.to_str());
+    match name {
+        Some(name) if !name.contains('\n') => Ok(name.to_string()),
+        _ => Err(ChannelError::Transfer {
+            path: path.display().to_string(),
+            message: "cannot be sent as an SCP file name".to_string(),
+        }
+        .into()),
+    }
+}
+
+/// The `T` record carrying a local file's timestamps.
+fn times_record(metadata: &std::fs::Metadata) -> String {
+    let secs = |time: std::io::Result<SystemTime>| {
+        time.ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_secs())
+    };
+    format!(
+        "T{} 0 {} 0",
+        secs(metadata.modified()),
+        secs(metadata.accessed())
+    )
+}
+
+/// Parse the body of a `T` record: `mtime mtime_usec atime atime_usec`.
+fn parse_times(line: &str) -> Result<Times> {
+    let fields: Vec<u64> = line
+        .split(' ')
+        .map(str::parse)
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| protocol(format!("malformed time record {line:?}")))?;
+    let [mtime, _, atime, _] = fields[..] else {
+        return Err(protocol(format!("malformed time record {line:?}")));
+    };
+    Ok(Times {
+        modified: UNIX_EPOCH + Duration::from_secs(mtime),
+        accessed: UNIX_EPOCH + Duration::from_secs(atime),
+    })
+}
+
+/// Parse the body of a `C` or `D` record: `mode size name`.
+fn parse_entry(line: &str) -> Result<Entry> {
+    let malformed = || protocol(format!("malformed file record {line:?}"));
+    let mut fields = line.splitn(3, ' ');
+    let (Some(mode), Some(size), Some(name)) = (fields.next(), fields.next(), fields.next()) else {
+        return Err(malformed());
+    };
+    let mode = u32::from_str_radix(mode, 8).map_err(|_| malformed())?;
+    let size = size.parse().map_err(|_| malformed())?;
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+        return Err(protocol(format!("refusing unsafe file name {name:?}")));
+    }
+    Ok(Entry {
+        mode: mode & 0o7777,
+        size,
+        name: name.to_string(),
+    })
+}
+
+/// Check that a top-level entry is the one `remote` names.
+///
+/// A remote path without a usable last component (`.`, `/`) names
+/// whatever the remote sends.
+fn check_requested_name(remote: &str, name: &str) -> Result<()> {
+    let requested = remote
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or("");
+    if requested.is_empty() || requested == "." || requested == ".." || requested == name {
+        return Ok(());
+    }
+    Err(protocol(format!(
+        "sent {name:?} when {requested:?} was requested"
+    )))
+}
+
+/// Quote `path` for the remote shell.
+///
+/// Paths made of common characters are passed through untouched, since
+/// some network OSes run `scp` without a POSIX shell in front of it.
+fn shell_quote(path: &str) -> String {
+    let plain = !path.is_empty()
+        && path
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"_-./:@%+=,~".contains(&b));
+    if plain {
+        path.to_string()
+    } else {
+        format!("'{}'", path.replace('\'', r"'\''"))
+    }
+}
+
+fn protocol(message: impl Into<String>) -> Error {
+    ChannelError::ScpProtocol {
+        message: message.into(),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entry() {
+        assert_eq!(
+            parse_entry("0644 1234 my file.txt").unwrap(),
+            Entry {
+                mode: 0o644,
+                size: 1234,
+                name: "my file.txt".into(),
+            }
+        );
+        assert!(parse_entry("0644 12").is_err());
+        assert!(parse_entry("rw-r 12 x").is_err());
+        assert!(parse_entry("0755 0 ..").is_err());
+        assert!(parse_entry("0644 1 ../etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_check_requested_name() {
+        assert!(check_requested_name("/var/log/messages", "messages").is_ok());
+        assert!(check_requested_name("/var/log/", "log").is_ok());
+        assert!(check_requested_name("config.txt", "config.txt").is_ok());
+        assert!(check_requested_name(".", "home").is_ok());
+        assert!(check_requested_name("/var/log/messages", ".bashrc").is_err());
+    }
+
+    #[test]
+    fn test_parse_times() {
+        let times = parse_times("1700000000 0 1700000100 0").unwrap();
+        assert_eq!(
+            times.modified,
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+        );
+        assert_eq!(
+            times.accessed,
+            UNIX_EPOCH + Duration::from_secs(1_700_000_100)
+        );
+        assert!(parse_times("1700000000 0").is_err());
+        assert!(parse_times("soon 0 later 0").is_err());
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(
+            shell_quote("/var/tmp/junos-23.4.tgz"),
+            "/var/tmp/junos-23.4.tgz"
+        );
+        assert_eq!(shell_quote("flash:startup-config"), "flash:startup-config");
+        assert_eq!(shell_quote("my file"), "'my file'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+}
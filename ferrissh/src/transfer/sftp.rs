@@ -0,0 +1,293 @@
+//! SFTP client over the `sftp` subsystem.
+
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+use log::debug;
+use russh::client::Msg;
+use russh_sftp::client::SftpSession;
+use russh_sftp::client::fs::Metadata;
+use russh_sftp::protocol::FileType;
+use tokio::io::AsyncWriteExt;
+
+use super::{TransferOptions, TransferReport, copy_hashed, local_io, local_sha256, sha256_of};
+use crate::error::{ChannelError, Error, Result};
+
+/// Kind of a remote file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// A regular file.
+    File,
+    /// A directory.
+    Dir,
+    /// A symbolic link (only reported for directory entries).
+    Symlink,
+    /// Anything else: device, socket, FIFO, or unknown.
+    Other,
+}
+
+/// Attributes of a remote file, as reported by the server.
+///
+/// Servers may omit any attribute, hence the `Option`s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStat {
+    /// What kind of file this is.
+    pub kind: FileKind,
+    /// Size in bytes.
+    pub size: Option<u64>,
+    /// Permission bits (e.g. `0o644`), without the file type bits.
+    pub permissions: Option<u32>,
+    /// Owner user id.
+    pub uid: Option<u32>,
+    /// Owner group id.
+    pub gid: Option<u32>,
+    /// Last modification time.
+    pub modified: Option<SystemTime>,
+}
+
+impl FileStat {
+    /// Whether this is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.kind == FileKind::Dir
+    }
+
+    /// Whether this is a regular file.
+    pub fn is_file(&self) -> bool {
+        self.kind == FileKind::File
+    }
+}
+
+impl From<&Metadata> for FileStat {
+    fn from(metadata: &Metadata) -> Self {
+        let kind = match metadata.file_type() {
+            FileType::File => FileKind::File,
+            FileType::Dir => FileKind::Dir,
+            FileType::Symlink => FileKind::Symlink,
+            FileType::Other => FileKind::Other,
+        };
+        Self {
+            kind,
+            size: metadata.size,
+            permissions: metadata.permissions.map(|mode| mode & 0o7777),
+            uid: metadata.uid,
+            gid: metadata.gid,
+            modified: metadata
+                .mtime
+                .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs.into())),
+        }
+    }
+}
+
+/// An entry in a remote directory listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SftpEntry {
+    /// File name, without the directory.
+    pub name: String,
+    /// The entry's attributes.
+    pub stat: FileStat,
+}
+
+/// An SFTP session on an existing SSH connection.
+///
+/// Created by [`Session::sftp()`](crate::Session::sftp). Remote paths are
+/// interpreted by the server, relative paths usually being relative to the
+/// login user's home directory. Every request is bounded by the session
+/// timeout.
+pub struct SftpClient {
+    /// The underlying SFTP protocol session.
+    sftp: SftpSession,
+}
+
+impl SftpClient {
+    /// Start the SFTP protocol on a channel running the `sftp` subsystem.
+    pub(crate) async fn new(channel: russh::Channel<Msg>, timeout: Duration) -> Result<Self> {
+        let timeout_secs = timeout.as_secs().max(1);
+        let sftp = SftpSession::new_opts(channel.into_stream(), Some(timeout_secs))
+            .await
+            .map_err(|e| ChannelError::SubsystemFailed {
+                name: "sftp".to_string(),
+                message: e.to_string(),
+            })?;
+        debug!("sftp: session started");
+        Ok(Self { sftp })
+    }
+
+    /// Upload the local file `local` to `remote`, replacing any existing
+    /// file.
+    pub async fn upload(
+        &self,
+        local: impl AsRef<Path>,
+        remote: &str,
+        options: &TransferOptions,
+    ) -> Result<TransferReport> {
+        let local = local.as_ref();
+        let start = Instant::now();
+
+        let mut source = tokio::fs::File::open(local)
+            .await
+            .map_err(local_io(local))?;
+        let total = source.metadata().await.map_err(local_io(local))?.len();
+        let mut dest = self.sftp.create(remote).await.map_err(remote_err(remote))?;
+
+        let (bytes, sha256) = copy_hashed(
+            &mut source,
+            &mut dest,
+            &local.display().to_string(),
+            total,
+            options,
+            local_io(local),
+            remote_io(remote),
+        )
+        .await?;
+        debug!("sftp: uploaded {} bytes to {}", bytes, remote);
+
+        if options.verify_checksum {
+            let remote_sha256 = self.checksum(remote).await?;
+            ensure_match(remote, &sha256, &remote_sha256)?;
+        }
+
+        Ok(TransferReport {
+            bytes,
+            files: 1,
+            sha256,
+            verified: options.verify_checksum,
+            elapsed: start.elapsed(),
+        })
+    }
+
+    /// Download `remote` to the local file `local`, replacing any existing
+    /// file.
+    pub async fn download(
+        &self,
+        remote: &str,
+        local: impl AsRef<Path>,
+        options: &TransferOptions,
+    ) -> Result<TransferReport> {
+        let local = local.as_ref();
+        let start = Instant::now();
+
+        let mut source = self.sftp.open(remote).await.map_err(remote_err(remote))?;
+        let total = source.metadata().await.map_err(remote_err(remote))?.len();
+        let mut dest = tokio::fs::File::create(local)
+            .await
+            .map_err(local_io(local))?;
+
+        let (bytes, sha256) = copy_hashed(
+            &mut source,
+            &mut dest,
+            remote,
+            total,
+            options,
+            remote_io(remote),
+            local_io(local),
+        )
+        .await?;
+        source.shutdown().await.map_err(remote_io(remote))?;
+        debug!("sftp: downloaded {} bytes from {}", bytes, remote);
+
+        if options.verify_checksum {
+            let local_sha256 = local_sha256(local).await?;
+            ensure_match(remote, &local_sha256, &sha256)?;
+        }
+
+        Ok(TransferReport {
+            bytes,
+            files: 1,
+            sha256,
+            verified: options.verify_checksum,
+            elapsed: start.elapsed(),
+        })
+    }
+
+    /// Hex SHA-256 of a remote file, computed by reading it over SFTP.
+    pub async fn checksum(&self, path: &str) -> Result<String> {
+        let mut file = self.sftp.open(path).await.map_err(remote_err(path))?;
+        let sha256 = sha256_of(&mut file, remote_io(path)).await?;
+        file.shutdown().await.map_err(remote_io(path))?;
+        Ok(sha256)
+    }
+
+    /// Get the attributes of `path`, following symlinks.
+    pub async fn stat(&self, path: &str) -> Result<FileStat> {
+        let metadata = self.sftp.metadata(path).await.map_err(remote_err(path))?;
+        Ok(FileStat::from(&metadata))
+    }
+
+    /// Whether `path` exists on the server.
+    pub async fn exists(&self, path: &str) -> Result<bool> {
+        self.sftp.try_exists(path).await.map_err(remote_err(path))
+    }
+
+    /// List the entries of the directory `path`, excluding `.` and `..`.
+    pub async fn list_dir(&self, path: &str) -> Result<Vec<SftpEntry>> {
+        let entries = self.sftp.read_dir(path).await.map_err(remote_err(path))?;
+        Ok(entries
+            .map(|entry| SftpEntry {
+                name: entry.file_name(),
+                stat: FileStat::from(&entry.metadata()),
+            })
+            .collect())
+    }
+
+    /// Create the directory `path`. The parent must already exist.
+    pub async fn mkdir(&self, path: &str) -> Result<()> {
+        self.sftp.create_dir(path).await.map_err(remote_err(path))
+    }
+
+    /// Remove the file `path`.
+    pub async fn remove(&self, path: &str) -> Result<()> {
+        self.sftp.remove_file(path).await.map_err(remote_err(path))
+    }
+
+    /// Remove the directory `path`, which must be empty.
+    pub async fn remove_dir(&self, path: &str) -> Result<()> {
+        self.sftp.remove_dir(path).await.map_err(remote_err(path))
+    }
+
+    /// Rename `from` to `to`.
+    pub async fn rename(&self, from: &str, to: &str) -> Result<()> {
+        self.sftp.rename(from, to).await.map_err(remote_err(from))
+    }
+
+    /// End the SFTP session and close its channel.
+    ///
+    /// The SSH session and any other channels stay open.
+    pub async fn close(self) -> Result<()> {
+        self.sftp.close().await.map_err(remote_err("sftp"))
+    }
+}
+
+/// Map an SFTP protocol error on `path` to [`ChannelError::Transfer`].
+fn remote_err(path: &str) -> impl Fn(russh_sftp::client::error::Error) -> Error + '_ {
+    move |e| {
+        ChannelError::Transfer {
+            path: path.to_string(),
+            message: e.to_string(),
+        }
+        .into()
+    }
+}
+
+/// Map an I/O error on an open remote file to [`ChannelError::Transfer`].
+fn remote_io(path: &str) -> impl Fn(std::io::Error) -> Error + '_ {
+    move |e| {
+        ChannelError::Transfer {
+            path: path.to_string(),
+            message: e.to_string(),
+        }
+        .into()
+    }
+}
+
+/// Fail with [`ChannelError::ChecksumMismatch`] unless the checksums agree.
+fn ensure_match(path: &str, local: &str, remote: &str) -> Result<()> {
+    if local == remote {
+        return Ok(());
+    }
+    Err(ChannelError::ChecksumMismatch {
+        path: path.to_string(),
+        local: local.to_string(),
+        remote: remote.to_string(),
+    }
+    .into())
+}
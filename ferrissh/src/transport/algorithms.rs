@@ -0,0 +1,350 @@
+//! Key-exchange, host-key, cipher and MAC algorithm preferences.
+//!
+//! Algorithms are named as on the wire (and in OpenSSH's `KexAlgorithms`,
+//! `HostKeyAlgorithms`, `Ciphers` and `MACs` options), most preferred
+//! first. Three presets cover the common cases; individual lists can be
+//! overridden on top of any of them.
+
+use std::borrow::Cow;
+
+use russh::keys::Algorithm;
+use russh::{Names, Preferred, cipher, kex, mac};
+
+use crate::error::TransportError;
+
+/// Ordered algorithm preferences offered during key exchange.
+///
+/// # Example
+///
+/// ```rust
+/// use ferrissh::transport::AlgorithmPreferences;
+///
+/// // Old SR OS box: legacy preset, but never CBC ciphers.
+/// let prefs = AlgorithmPreferences::legacy()
+///     .ciphers(["aes256-ctr", "aes128-ctr"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlgorithmPreferences {
+    /// Key exchange algorithms (e.g. `curve25519-sha256`).
+    pub kex: Vec<String>,
+    /// Server host key algorithms (e.g. `ssh-ed25519`, `rsa-sha2-512`).
+    pub host_keys: Vec<String>,
+    /// Symmetric ciphers (e.g. `chacha20-poly1305@openssh.com`).
+    pub ciphers: Vec<String>,
+    /// MAC algorithms (e.g. `hmac-sha2-256-etm@openssh.com`).
+    pub macs: Vec<String>,
+}
+
+impl AlgorithmPreferences {
+    /// Only modern algorithms: Curve25519 / ML-KEM key exchange, Ed25519,
+    /// ECDSA and SHA-2 RSA host keys, AEAD ciphers and encrypt-then-MAC.
+    pub fn modern() -> Self {
+        Self {
+            kex: names(&[
+                "mlkem768x25519-sha256",
+                "curve25519-sha256",
+                "curve25519-sha256@libssh.org",
+            ]),
+            host_keys: names(&[
+                "ssh-ed25519",
+                "ecdsa-sha2-nistp256",
+                "ecdsa-sha2-nistp384",
+                "ecdsa-sha2-nistp521",
+                "rsa-sha2-512",
+                "rsa-sha2-256",
+            ]),
+            ciphers: names(&[
+                "chacha20-poly1305@openssh.com",
+                "aes256-gcm@openssh.com",
+                "aes128-gcm@openssh.com",
+            ]),
+            macs: names(&[
+                "hmac-sha2-512-etm@openssh.com",
+                "hmac-sha2-256-etm@openssh.com",
+            ]),
+        }
+    }
+
+    /// The russh defaults: modern algorithms first, plus SHA-2 Diffie-Hellman
+    /// groups, CTR ciphers, `ssh-rsa` host keys and SHA-1 MACs. No SHA-1 key
+    /// exchange or CBC ciphers. This is the default.
+    pub fn compatible() -> Self {
+        Self {
+            kex: names(&[
+                "mlkem768x25519-sha256",
+                "curve25519-sha256",
+                "curve25519-sha256@libssh.org",
+                "diffie-hellman-group-exchange-sha256",
+                "diffie-hellman-group18-sha512",
+                "diffie-hellman-group17-sha512",
+                "diffie-hellman-group16-sha512",
+                "diffie-hellman-group15-sha512",
+                "diffie-hellman-group14-sha256",
+            ]),
+            host_keys: names(&[
+                "ssh-ed25519",
+                "ecdsa-sha2-nistp256",
+                "ecdsa-sha2-nistp384",
+                "ecdsa-sha2-nistp521",
+                "rsa-sha2-512",
+                "rsa-sha2-256",
+                "ssh-rsa",
+            ]),
+            ciphers: names(&[
+                "chacha20-poly1305@openssh.com",
+                "aes256-gcm@openssh.com",
+                "aes256-ctr",
+                "aes192-ctr",
+                "aes128-ctr",
+            ]),
+            macs: names(&[
+                "hmac-sha2-512-etm@openssh.com",
+                "hmac-sha2-256-etm@openssh.com",
+                "hmac-sha2-512",
+                "hmac-sha2-256",
+                "hmac-sha1-etm@openssh.com",
+                "hmac-sha1",
+            ]),
+        }
+    }
+
+    /// [`compatible()`](Self::compatible) plus algorithms needed by old
+    /// network gear: ECDH and SHA-1 Diffie-Hellman key exchange
+    /// (`diffie-hellman-group14-sha1`, `diffie-hellman-group1-sha1`) and
+    /// CBC ciphers. Modern algorithms are still preferred when the server
+    /// supports them.
+    pub fn legacy() -> Self {
+        let mut prefs = Self::compatible();
+        prefs.kex.extend(names(&[
+            "ecdh-sha2-nistp256",
+            "ecdh-sha2-nistp384",
+            "ecdh-sha2-nistp521",
+            "diffie-hellman-group-exchange-sha1",
+            "diffie-hellman-group14-sha1",
+            "diffie-hellman-group1-sha1",
+        ]));
+        prefs
+            .ciphers
+            .extend(names(&["aes256-cbc", "aes192-cbc", "aes128-cbc"]));
+        prefs
+    }
+
+    /// Override the key exchange algorithms.
+    pub fn kex<I, S>(mut self, algorithms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.kex = algorithms.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Override the server host key algorithms.
+    pub fn host_keys<I, S>(mut self, algorithms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.host_keys = algorithms.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Override the ciphers.
+    pub fn ciphers<I, S>(mut self, algorithms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ciphers = algorithms.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Override the MAC algorithms.
+    pub fn macs<I, S>(mut self, algorithms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.macs = algorithms.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Convert to russh's preference lists, rejecting unknown names and
+    /// empty lists.
+    pub(crate) fn to_preferred(&self) -> Result<Preferred, TransportError> {
+        let mut kex = parse("key exchange", &self.kex, |n| kex::Name::try_from(n).ok())?;
+        // Client-side extension markers: server-sig-algs and strict kex.
+        kex.extend([
+            kex::EXTENSION_SUPPORT_AS_CLIENT,
+            kex::EXTENSION_OPENSSH_STRICT_KEX_AS_CLIENT,
+        ]);
+        let key = parse("host key", &self.host_keys, |n| {
+            Algorithm::new(n)
+                .ok()
+                .filter(|a| !matches!(a, Algorithm::Other(_)))
+        })?;
+        let cipher = parse("cipher", &self.ciphers, |n| cipher::Name::try_from(n).ok())?;
+        let mac = parse("MAC", &self.macs, |n| mac::Name::try_from(n).ok())?;
+
+        Ok(Preferred {
+            kex: Cow::Owned(kex),
+            key: Cow::Owned(key),
+            cipher: Cow::Owned(cipher),
+            mac: Cow::Owned(mac),
+            ..Preferred::default()
+        })
+    }
+}
+
+impl Default for AlgorithmPreferences {
+    fn default() -> Self {
+        Self::compatible()
+    }
+}
+
+/// The algorithms agreed with the server during key exchange.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NegotiatedAlgorithms {
+    /// Key exchange algorithm.
+    pub kex: String,
+    /// Server host key algorithm.
+    pub host_key: String,
+    /// Cipher (the same in both directions).
+    pub cipher: String,
+    /// MAC for client-to-server traffic (`none` with AEAD ciphers).
+    pub mac_client_to_server: String,
+    /// MAC for server-to-client traffic (`none` with AEAD ciphers).
+    pub mac_server_to_client: String,
+}
+
+impl From<&Names> for NegotiatedAlgorithms {
+    fn from(names: &Names) -> Self {
+        Self {
+            kex: names.kex.as_ref().to_string(),
+            host_key: names.key.to_string(),
+            cipher: names.cipher.as_ref().to_string(),
+            mac_client_to_server: names.client_mac.as_ref().to_string(),
+            mac_server_to_client: names.server_mac.as_ref().to_string(),
+        }
+    }
+}
+
+fn names(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+/// Resolve each name with `lookup`, naming the first unsupported one.
+fn parse<T>(
+    kind: &str,
+    list: &[String],
+    lookup: impl Fn(&str) -> Option<T>,
+) -> Result<Vec<T>, TransportError> {
+    if list.is_empty() {
+        return Err(TransportError::Algorithms(format!(
+            "no {kind} algorithms configured"
+        )));
+    }
+    list.iter()
+        .map(|name| {
+            lookup(name).ok_or_else(|| {
+                TransportError::Algorithms(format!("unsupported {kind} algorithm '{name}'"))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_are_supported() {
+        for prefs in [
+            AlgorithmPreferences::modern(),
+            AlgorithmPreferences::compatible(),
+            AlgorithmPreferences::legacy(),
+        ] {
+            prefs.to_preferred().expect("preset should be valid");
+        }
+    }
+
+    #[test]
+    fn test_compatible_matches_russh_defaults() {
+        let ours = AlgorithmPreferences::compatible().to_preferred().unwrap();
+        let theirs = Preferred::default();
+        let kex: Vec<&str> = ours.kex.iter().map(|n| n.as_ref()).collect();
+        let default_kex: Vec<&str> = theirs
+            .kex
+            .iter()
+            .map(|n| n.as_ref())
+            .filter(|n| !n.starts_with("ext-info-s") && !n.starts_with("kex-strict-s"))
+            .collect();
+        assert_eq!(kex, default_kex);
+        assert_eq!(ours.key, theirs.key);
+        assert_eq!(ours.cipher, theirs.cipher);
+        assert_eq!(ours.mac, theirs.mac);
+    }
+
+    #[test]
+    fn test_legacy_adds_sha1_and_cbc() {
+        let legacy = AlgorithmPreferences::legacy();
+        assert!(
+            legacy
+                .kex
+                .iter()
+                .any(|n| n == "diffie-hellman-group14-sha1")
+        );
+        assert!(legacy.ciphers.iter().any(|n| n == "aes128-cbc"));
+        assert!(legacy.host_keys.iter().any(|n| n == "ssh-rsa"));
+        // Only CBC is added: AEAD ciphers are not legacy.
+        let compatible = AlgorithmPreferences::compatible();
+        assert!(
+            legacy.ciphers[compatible.ciphers.len()..]
+                .iter()
+                .all(|n| n.ends_with("-cbc"))
+        );
+
+        let modern = AlgorithmPreferences::modern();
+        assert!(!modern.host_keys.iter().any(|n| n == "ssh-rsa"));
+        assert!(!modern.macs.iter().any(|n| n.contains("sha1")));
+    }
+
+    #[test]
+    fn test_overrides_replace_single_list() {
+        let prefs = AlgorithmPreferences::modern().ciphers(["aes128-ctr"]);
+        assert_eq!(prefs.ciphers, ["aes128-ctr"]);
+        assert_eq!(prefs.kex, AlgorithmPreferences::modern().kex);
+
+        let preferred = prefs.to_preferred().unwrap();
+        assert_eq!(preferred.cipher.as_ref(), [cipher::AES_128_CTR]);
+        assert!(
+            preferred
+                .kex
+                .contains(&kex::EXTENSION_OPENSSH_STRICT_KEX_AS_CLIENT)
+        );
+    }
+
+    #[test]
+    fn test_unknown_and_empty_lists_rejected() {
+        let err = AlgorithmPreferences::default()
+            .ciphers(["aes128-cbc", "rot13"])
+            .to_preferred()
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unsupported cipher algorithm 'rot13'")
+        );
+
+        let err = AlgorithmPreferences::default()
+            .host_keys(["ssh-foo"])
+            .to_preferred()
+            .unwrap_err();
+        assert!(err.to_string().contains("host key"), "got: {err}");
+
+        let err = AlgorithmPreferences::default()
+            .kex(Vec::<String>::new())
+            .to_preferred()
+            .unwrap_err();
+        assert!(err.to_string().contains("no key exchange algorithms"));
+    }
+}
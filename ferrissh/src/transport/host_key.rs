@@ -0,0 +1,84 @@
+//! Host key verification hooks beyond known_hosts files.
+//!
+//! [`HostKeyVerifier`] lets callers check server keys against their own
+//! source of truth (an inventory database, a CMDB, a vault); pinned
+//! fingerprints cover the simpler case of a fixed, known key.
+
+pub use russh::keys::PublicKey;
+use russh::keys::ssh_key::HashAlg;
+
+/// Verifies a server's host key during the SSH handshake.
+///
+/// Used via [`HostKeyVerification::Custom`](super::HostKeyVerification::Custom).
+/// Called once per connection (and once per jump host configured with it).
+/// The call is synchronous and runs inside the handshake, so load the
+/// expected keys up front rather than querying a slow backend here.
+///
+/// # Example
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// use ferrissh::transport::{HostKeyVerifier, PublicKey, sha256_fingerprint};
+///
+/// struct Inventory {
+///     fingerprints: HashMap<String, String>,
+/// }
+///
+/// impl HostKeyVerifier for Inventory {
+///     fn verify(&self, host: &str, _port: u16, key: &PublicKey) -> Result<(), String> {
+///         match self.fingerprints.get(host) {
+///             Some(expected) if *expected == sha256_fingerprint(key) => Ok(()),
+///             Some(_) => Err("fingerprint does not match inventory".into()),
+///             None => Err("host not in inventory".into()),
+///         }
+///     }
+/// }
+/// ```
+pub trait HostKeyVerifier: Send + Sync {
+    /// Accept the key with `Ok(())`, or reject it with a reason that is
+    /// reported in [`TransportError::HostKeyRejected`](crate::error::TransportError::HostKeyRejected).
+    fn verify(&self, host: &str, port: u16, key: &PublicKey) -> Result<(), String>;
+}
+
+/// The OpenSSH-style SHA256 fingerprint of `key`, e.g. `SHA256:uNiVzt...`.
+pub fn sha256_fingerprint(key: &PublicKey) -> String {
+    key.fingerprint(HashAlg::Sha256).to_string()
+}
+
+/// Compare a pinned fingerprint against an actual one.
+///
+/// The `SHA256:` prefix and base64 padding are optional in `pinned`.
+pub(crate) fn fingerprint_matches(pinned: &str, actual: &str) -> bool {
+    let normalize = |s: &str| {
+        let s = s.trim();
+        s.strip_prefix("SHA256:")
+            .unwrap_or(s)
+            .trim_end_matches('=')
+            .to_string()
+    };
+    normalize(pinned) == normalize(actual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_matches_normalizes() {
+        let actual = "SHA256:47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU";
+        assert!(fingerprint_matches(actual, actual));
+        assert!(fingerprint_matches(
+            "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=",
+            actual
+        ));
+        assert!(fingerprint_matches(
+            " SHA256:47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU\n",
+            actual
+        ));
+        assert!(!fingerprint_matches(
+            "SHA256:47deqpj8hbsa+/timw+5jceuqerkm5nmpjwzg3hsufu",
+            actual
+        ));
+    }
+}
@@ -0,0 +1,52 @@
+//! Metadata about an established connection.
+
+use std::net::SocketAddr;
+
+use super::algorithms::NegotiatedAlgorithms;
+use super::config::Protocol;
+
+/// What was learned about the server while connecting.
+///
+/// Useful for audit logs, and for telling platforms apart before a shell
+/// is opened: the SSH identification string and the pre-login banner often
+/// name the vendor and OS.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// The protocol the connection uses.
+    pub protocol: Protocol,
+
+    /// The server's SSH identification string (e.g.
+    /// `SSH-2.0-OpenSSH_9.6`). `None` for Telnet.
+    pub server_id: Option<String>,
+
+    /// Text shown before login: the SSH authentication banner, or what a
+    /// Telnet device prints before its first login prompt.
+    pub banner: Option<String>,
+
+    /// Algorithms agreed during key exchange. All empty for Telnet.
+    pub algorithms: NegotiatedAlgorithms,
+
+    /// Address of the socket's far end — the proxy's, if one is used.
+    /// `None` when the connection is tunnelled through a jump host.
+    pub peer_addr: Option<SocketAddr>,
+
+    /// Local address of the socket. `None` when the connection is
+    /// tunnelled through a jump host.
+    pub local_addr: Option<SocketAddr>,
+}
+
+impl ConnectionInfo {
+    /// Info for a connection over `protocol`, with nothing learned yet.
+    pub(crate) fn new(protocol: Protocol) -> Self {
+        Self {
+            protocol,
+            ..Default::default()
+        }
+    }
+
+    /// Record the addresses of the socket the connection runs over.
+    pub(crate) fn set_addresses(&mut self, stream: &tokio::net::TcpStream) {
+        self.peer_addr = stream.peer_addr().ok();
+        self.local_addr = stream.local_addr().ok();
+    }
+}
@@ -0,0 +1,155 @@
+//! Keyboard-interactive authentication responders.
+//!
+//! Keyboard-interactive (RFC 4256) lets the server ask arbitrary questions
+//! over one or more rounds — password, OTP code, a disclaimer to
+//! acknowledge. A [`KeyboardInteractiveResponder`] supplies the answers.
+
+use secrecy::{ExposeSecret, SecretString};
+
+/// A single question in a keyboard-interactive round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardInteractivePrompt {
+    /// Prompt text, e.g. `"Password: "` or `"OTP code: "`.
+    pub prompt: String,
+    /// Whether the server asks for the answer to be echoed (non-secret input).
+    pub echo: bool,
+}
+
+/// Supplies answers to keyboard-interactive prompts (MFA, OTP, disclaimers).
+///
+/// Called once per round the server sends. Must return one answer per
+/// prompt, in order. Rounds with no prompts (e.g. an informational
+/// banner) are still delivered and should be answered with an empty `Vec`.
+///
+/// # Example
+///
+/// ```rust
+/// use ferrissh::transport::{KeyboardInteractivePrompt, KeyboardInteractiveResponder};
+///
+/// struct OtpResponder {
+///     password: String,
+/// }
+///
+/// impl KeyboardInteractiveResponder for OtpResponder {
+///     fn respond(
+///         &self,
+///         _name: &str,
+///         _instructions: &str,
+///         prompts: &[KeyboardInteractivePrompt],
+///     ) -> Vec<String> {
+///         prompts
+///             .iter()
+///             .map(|p| {
+///                 if p.prompt.to_lowercase().contains("otp") {
+///                     "123456".to_string() // e.g. from a TOTP generator
+///                 } else {
+///                     self.password.clone()
+///                 }
+///             })
+///             .collect()
+///     }
+/// }
+/// ```
+pub trait KeyboardInteractiveResponder: Send + Sync {
+    /// Answer one round of prompts.
+    fn respond(
+        &self,
+        name: &str,
+        instructions: &str,
+        prompts: &[KeyboardInteractivePrompt],
+    ) -> Vec<String>;
+}
+
+/// The default responder: answers prompts containing "password" with the
+/// password and every other prompt with an empty string.
+///
+/// This is what password authentication falls back to when the server
+/// only offers keyboard-interactive.
+#[derive(Clone)]
+pub struct PasswordResponder {
+    password: SecretString,
+}
+
+impl PasswordResponder {
+    /// Create a responder that answers password prompts with `password`.
+    pub fn new(password: impl Into<String>) -> Self {
+        Self {
+            password: SecretString::from(password.into()),
+        }
+    }
+}
+
+impl From<SecretString> for PasswordResponder {
+    fn from(password: SecretString) -> Self {
+        Self { password }
+    }
+}
+
+impl KeyboardInteractiveResponder for PasswordResponder {
+    fn respond(
+        &self,
+        _name: &str,
+        _instructions: &str,
+        prompts: &[KeyboardInteractivePrompt],
+    ) -> Vec<String> {
+        prompts
+            .iter()
+            .map(|p| {
+                if p.prompt.to_lowercase().contains("password") {
+                    self.password.expose_secret().to_string()
+                } else {
+                    String::new()
+                }
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for PasswordResponder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PasswordResponder(****)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(text: &str, echo: bool) -> KeyboardInteractivePrompt {
+        KeyboardInteractivePrompt {
+            prompt: text.to_string(),
+            echo,
+        }
+    }
+
+    #[test]
+    fn test_password_responder_answers_password_prompts_only() {
+        let responder = PasswordResponder::new("secret");
+        let answers = responder.respond(
+            "",
+            "",
+            &[
+                prompt("Password: ", false),
+                prompt("Press enter to accept the banner", true),
+                prompt("RADIUS PASSWORD:", false),
+            ],
+        );
+        assert_eq!(answers, vec!["secret", "", "secret"]);
+    }
+
+    #[test]
+    fn test_password_responder_empty_round() {
+        let responder = PasswordResponder::new("secret");
+        assert!(
+            responder
+                .respond("Banner", "Authorized use only", &[])
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_password_responder_debug_redacts() {
+        let debug_output = format!("{:?}", PasswordResponder::new("secret"));
+        assert!(!debug_output.contains("secret"));
+    }
+}
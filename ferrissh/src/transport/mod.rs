@@ -1,12 +1,36 @@
-//! SSH transport layer wrapping russh.
+//! Transport layer: SSH (wrapping russh) and Telnet.
 //!
-//! This module provides the low-level SSH connection management,
-//! handling connection setup, authentication, and channel creation.
+//! This module provides the low-level connection management, handling
+//! connection setup, authentication, and channel creation. Both protocols
+//! sit behind the [`Transport`] trait.
 
+mod algorithms;
 pub mod config;
+mod host_key;
+mod info;
+mod keyboard_interactive;
+mod openssh_config;
+mod proxy;
 mod ssh;
+mod tcp;
+mod telnet;
+mod traits;
 
-pub use config::{AuthMethod, HostKeyVerification, SshConfig};
+pub use algorithms::{AlgorithmPreferences, NegotiatedAlgorithms};
+pub use config::{
+    AuthMethod, CertificateSource, HostKeyVerification, JumpHost, KeySource, Protocol, ProxyAuth,
+    ProxyConfig, SshConfig, TerminalOptions,
+};
+pub use host_key::{HostKeyVerifier, PublicKey, sha256_fingerprint};
+pub use info::ConnectionInfo;
+pub use keyboard_interactive::{
+    KeyboardInteractivePrompt, KeyboardInteractiveResponder, PasswordResponder,
+};
+pub use openssh_config::{HostConfig, OpenSshConfig};
+pub use russh::{Pty, Sig};
 pub use ssh::SshTransport;
+pub use telnet::TelnetTransport;
+pub(crate) use traits::connect;
+pub use traits::{ShellChannel, ShellOutput, Transport};
 
 pub use crate::error::DisconnectReason;
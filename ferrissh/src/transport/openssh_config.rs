@@ -0,0 +1,840 @@
+//! OpenSSH client configuration (`~/.ssh/config`) parser.
+//!
+//! Supports `Host` blocks (with `*`/`?` wildcards and `!` negation) and
+//! `Include`. `Match` blocks are not evaluated and are skipped. As in
+//! OpenSSH, the first value obtained for a keyword wins, except for
+//! `IdentityFile` and `CertificateFile`, which accumulate.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use ferrissh::transport::OpenSshConfig;
+//!
+//! # fn example() -> Result<(), ferrissh::Error> {
+//! let config = OpenSshConfig::load_default()?;
+//! let host = config.resolve("core-router")?;
+//! println!("{:?} {:?}", host.host_name, host.user);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use log::debug;
+
+use super::config::{AuthMethod, CertificateSource, HostKeyVerification, JumpHost, KeySource};
+use crate::error::{DriverError, Result};
+
+/// Maximum `Include` nesting depth (matches OpenSSH's `READCONF_MAX_DEPTH`).
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// A parsed OpenSSH client configuration.
+#[derive(Debug, Clone, Default)]
+pub struct OpenSshConfig {
+    items: Vec<Item>,
+}
+
+/// Settings that apply to one host alias after evaluating the config.
+#[derive(Debug, Clone, Default)]
+pub struct HostConfig {
+    /// `HostName`: the real host to connect to.
+    pub host_name: Option<String>,
+    /// `Port`.
+    pub port: Option<u16>,
+    /// `User`.
+    pub user: Option<String>,
+    /// `IdentityFile` entries, in the order given.
+    pub identity_files: Vec<PathBuf>,
+    /// `CertificateFile` entries, in the order given.
+    pub certificate_files: Vec<PathBuf>,
+    /// `UserKnownHostsFile` entries.
+    pub user_known_hosts_files: Vec<PathBuf>,
+    /// `StrictHostKeyChecking`.
+    pub strict_host_key_checking: Option<HostKeyVerification>,
+    /// `ServerAliveInterval`. `Some(None)` means keepalives are disabled (`0`).
+    pub server_alive_interval: Option<Option<Duration>>,
+    /// `ServerAliveCountMax`.
+    pub server_alive_count_max: Option<usize>,
+    /// `ConnectTimeout`.
+    pub connect_timeout: Option<Duration>,
+    /// `BindAddress`.
+    pub bind_address: Option<IpAddr>,
+    /// `BindInterface`.
+    pub bind_interface: Option<String>,
+    /// `ProxyJump`, with each hop resolved against the same config.
+    pub jump_hosts: Vec<JumpHost>,
+}
+
+impl HostConfig {
+    /// The identity file to authenticate with: the first that exists on
+    /// disk, falling back to the first listed.
+    pub fn identity_file(&self) -> Option<&Path> {
+        self.identity_files
+            .iter()
+            .find(|path| path.exists())
+            .or(self.identity_files.first())
+            .map(PathBuf::as_path)
+    }
+
+    /// Authentication methods for the `IdentityFile` and `CertificateFile`
+    /// entries, in the order to try them.
+    ///
+    /// Each certificate comes first, paired with the identity file it was
+    /// issued for (`<key>-cert.pub`) or else the first identity file. Each
+    /// identity file's key follows on its own. Identity files missing from
+    /// disk are skipped, unless all are, in which case the first is kept so
+    /// that the failure names it.
+    pub fn auth_methods(&self) -> Vec<AuthMethod> {
+        let mut keys: Vec<&PathBuf> = self.identity_files.iter().filter(|p| p.exists()).collect();
+        if keys.is_empty() {
+            keys.extend(self.identity_files.first());
+        }
+
+        let mut methods = Vec::new();
+        for cert in &self.certificate_files {
+            let issued_for = cert
+                .to_str()
+                .and_then(|cert| cert.strip_suffix("-cert.pub"))
+                .map(PathBuf::from);
+            let Some(key) = keys
+                .iter()
+                .find(|key| issued_for.as_ref() == Some(**key))
+                .or(keys.first())
+            else {
+                debug!("no identity file for certificate {}", cert.display());
+                continue;
+            };
+            methods.push(AuthMethod::Certificate {
+                key: KeySource::Path((*key).clone()),
+                passphrase: None,
+                certificate: CertificateSource::Path(cert.clone()),
+            });
+        }
+        methods.extend(keys.into_iter().map(|path| AuthMethod::PrivateKey {
+            path: path.clone(),
+            passphrase: None,
+        }));
+        methods
+    }
+
+    /// The builder settings this config supplies for `alias`.
+    pub(crate) fn into_settings(self, alias: &str) -> HostSettings {
+        let auth_methods = self.auth_methods();
+        HostSettings {
+            host: self.host_name.unwrap_or_else(|| alias.to_string()),
+            auth_methods,
+            port: self.port,
+            username: self.user,
+            known_hosts_path: self.user_known_hosts_files.into_iter().next(),
+            host_key_verification: self.strict_host_key_checking,
+            keepalive_interval: self.server_alive_interval,
+            keepalive_max: self.server_alive_count_max,
+            connect_timeout: self.connect_timeout,
+            source_address: self.bind_address,
+            bind_interface: self.bind_interface,
+            jump_hosts: self.jump_hosts,
+        }
+    }
+}
+
+/// What [`SessionBuilder`](crate::SessionBuilder) and
+/// [`DriverBuilder`](crate::DriverBuilder) take from a [`HostConfig`].
+pub(crate) struct HostSettings {
+    pub host: String,
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    /// Used when the caller sets no authentication method.
+    pub auth_methods: Vec<AuthMethod>,
+    pub known_hosts_path: Option<PathBuf>,
+    pub host_key_verification: Option<HostKeyVerification>,
+    pub keepalive_interval: Option<Option<Duration>>,
+    pub keepalive_max: Option<usize>,
+    pub connect_timeout: Option<Duration>,
+    pub source_address: Option<IpAddr>,
+    pub bind_interface: Option<String>,
+    pub jump_hosts: Vec<JumpHost>,
+}
+
+#[derive(Debug, Clone)]
+enum Item {
+    /// `Host` line; starts a new block.
+    Host(Vec<String>),
+    /// `Match` line; its block is skipped.
+    Match,
+    /// A `Keyword args...` line.
+    Setting {
+        keyword: String,
+        args: Vec<String>,
+        origin: String,
+    },
+    /// The contents of the files named by an `Include` line.
+    Include(Vec<Item>),
+}
+
+/// First-wins raw values collected while walking the config.
+#[derive(Default)]
+struct Collected {
+    values: Vec<(String, Vec<String>, String)>,
+}
+
+impl Collected {
+    fn first(&self, keyword: &str) -> Option<(&[String], &str)> {
+        self.values
+            .iter()
+            .find(|(k, _, _)| k == keyword)
+            .map(|(_, args, origin)| (args.as_slice(), origin.as_str()))
+    }
+
+    fn all(&self, keyword: &str) -> impl Iterator<Item = &[String]> {
+        self.values
+            .iter()
+            .filter(move |(k, _, _)| k == keyword)
+            .map(|(_, args, _)| args.as_slice())
+    }
+}
+
+impl OpenSshConfig {
+    /// Parse configuration text. Relative `Include` paths are resolved
+    /// against `~/.ssh`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let base = home_dir().map(|home| home.join(".ssh")).unwrap_or_default();
+        Ok(Self {
+            items: parse_text(text, "<config>", &base, 0)?,
+        })
+    }
+
+    /// Load and parse a config file. Relative `Include` paths are resolved
+    /// against the directory containing `path`.
+    ///
+    /// A missing file yields an empty config, as in OpenSSH.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            items: parse_file(path.as_ref(), 0)?,
+        })
+    }
+
+    /// Load `~/.ssh/config`.
+    pub fn load_default() -> Result<Self> {
+        match home_dir() {
+            Some(home) => Self::load(home.join(".ssh").join("config")),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Evaluate the config for `alias`.
+    pub fn resolve(&self, alias: &str) -> Result<HostConfig> {
+        let mut host = self.resolve_without_jumps(alias)?;
+
+        if let Some((args, _)) = self.collect(alias).first("proxyjump") {
+            let spec = args.join(" ");
+            if !spec.eq_ignore_ascii_case("none") {
+                for hop in spec.split(',') {
+                    host.jump_hosts.push(self.jump_host(hop.trim())?);
+                }
+            }
+        }
+
+        Ok(host)
+    }
+
+    fn collect(&self, alias: &str) -> Collected {
+        let mut collected = Collected::default();
+        walk(&self.items, alias, true, &mut collected);
+        collected
+    }
+
+    fn resolve_without_jumps(&self, alias: &str) -> Result<HostConfig> {
+        let collected = self.collect(alias);
+        let mut host = HostConfig::default();
+
+        if let Some((args, _)) = collected.first("hostname") {
+            host.host_name = Some(expand_tokens(&args[0], alias, alias, None, None));
+        }
+        if let Some((args, origin)) = collected.first("port") {
+            host.port = Some(parse_value(&args[0], "Port", origin)?);
+        }
+        if let Some((args, _)) = collected.first("user") {
+            host.user = Some(args[0].clone());
+        }
+
+        let remote = host.host_name.as_deref().unwrap_or(alias);
+        let expand = |arg: &str| {
+            expand_path(&expand_tokens(
+                arg,
+                remote,
+                alias,
+                host.port,
+                host.user.as_deref(),
+            ))
+        };
+
+        for args in collected.all("identityfile") {
+            host.identity_files.push(expand(&args[0]));
+        }
+        for args in collected.all("certificatefile") {
+            host.certificate_files.push(expand(&args[0]));
+        }
+        if let Some((args, _)) = collected.first("userknownhostsfile") {
+            host.user_known_hosts_files = args
+                .iter()
+                .filter(|arg| !arg.eq_ignore_ascii_case("none"))
+                .map(|arg| expand(arg))
+                .collect();
+        }
+        if let Some((args, origin)) = collected.first("stricthostkeychecking") {
+            host.strict_host_key_checking = Some(match args[0].to_ascii_lowercase().as_str() {
+                "yes" | "ask" => HostKeyVerification::Strict,
+                "accept-new" => HostKeyVerification::AcceptNew,
+                "no" | "off" => HostKeyVerification::Disabled,
+                other => {
+                    return Err(invalid(
+                        origin,
+                        format!("invalid StrictHostKeyChecking '{other}'"),
+                    ));
+                }
+            });
+        }
+        if let Some((args, origin)) = collected.first("serveraliveinterval") {
+            let secs: u64 = parse_value(&args[0], "ServerAliveInterval", origin)?;
+            host.server_alive_interval = Some((secs > 0).then(|| Duration::from_secs(secs)));
+        }
+        if let Some((args, origin)) = collected.first("serveralivecountmax") {
+            host.server_alive_count_max =
+                Some(parse_value(&args[0], "ServerAliveCountMax", origin)?);
+        }
+        if let Some((args, origin)) = collected.first("connecttimeout") {
+            let secs: u64 = parse_value(&args[0], "ConnectTimeout", origin)?;
+            host.connect_timeout = Some(Duration::from_secs(secs));
+        }
+        if let Some((args, origin)) = collected.first("bindaddress") {
+            host.bind_address = Some(parse_value(&args[0], "BindAddress", origin)?);
+        }
+        if let Some((args, _)) = collected.first("bindinterface") {
+            host.bind_interface = Some(args[0].clone());
+        }
+
+        Ok(host)
+    }
+
+    /// Build a jump host from one `ProxyJump` entry (`[user@]host[:port]`).
+    fn jump_host(&self, spec: &str) -> Result<JumpHost> {
+        let spec = spec.strip_prefix("ssh://").unwrap_or(spec);
+        let (user, host_port) = match spec.rsplit_once('@') {
+            Some((user, rest)) => (Some(user), rest),
+            None => (None, spec),
+        };
+        let (alias, port) =
+            split_host_port(host_port).ok_or_else(|| DriverError::InvalidConfig {
+                message: format!("invalid ProxyJump entry '{spec}'"),
+            })?;
+
+        let resolved = self.resolve_without_jumps(alias)?;
+        let mut jump = JumpHost::new(resolved.host_name.as_deref().unwrap_or(alias))
+            .port(port.or(resolved.port).unwrap_or(22));
+        if let Some(user) = user.map(str::to_string).or(resolved.user.clone()) {
+            jump = jump.username(user);
+        }
+        for method in resolved.auth_methods() {
+            jump = jump.auth(method);
+        }
+        if let Some(path) = resolved.user_known_hosts_files.first() {
+            jump = jump.known_hosts_path(path);
+        }
+        if let Some(mode) = resolved.strict_host_key_checking {
+            jump = jump.host_key_verification(mode);
+        }
+        Ok(jump)
+    }
+}
+
+/// Walk `items`, collecting settings from blocks that apply to `alias`.
+///
+/// `active` is passed by value into includes, so a `Host` line inside an
+/// included file does not leak out to the including file.
+fn walk(items: &[Item], alias: &str, mut active: bool, out: &mut Collected) {
+    for item in items {
+        match item {
+            Item::Host(patterns) => active = host_matches(patterns, alias),
+            Item::Match => active = false,
+            Item::Setting {
+                keyword,
+                args,
+                origin,
+            } if active => out
+                .values
+                .push((keyword.clone(), args.clone(), origin.clone())),
+            Item::Include(included) if active => walk(included, alias, active, out),
+            _ => {}
+        }
+    }
+}
+
+fn parse_file(path: &Path, depth: usize) -> Result<Vec<Item>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(DriverError::InvalidConfig {
+                message: format!("failed to read {}: {e}", path.display()),
+            }
+            .into());
+        }
+    };
+    let base = path.parent().unwrap_or(Path::new("."));
+    parse_text(&text, &path.display().to_string(), base, depth)
+}
+
+fn parse_text(text: &str, name: &str, base: &Path, depth: usize) -> Result<Vec<Item>> {
+    let mut items = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let origin = format!("{name}:{}", index + 1);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let split = line
+            .find(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or(line.len());
+        let keyword = line[..split].to_ascii_lowercase();
+        let rest = line[split..].trim_start();
+        let rest = rest.strip_prefix('=').unwrap_or(rest);
+        let args = split_args(rest).ok_or_else(|| invalid(&origin, "unbalanced quotes".into()))?;
+
+        match keyword.as_str() {
+            "host" => items.push(Item::Host(args)),
+            "match" => {
+                debug!("{origin}: Match blocks are not supported, skipping");
+                items.push(Item::Match);
+            }
+            "include" => {
+                if depth >= MAX_INCLUDE_DEPTH {
+                    return Err(invalid(&origin, "Include nested too deeply".into()));
+                }
+                let mut included = Vec::new();
+                for arg in &args {
+                    for path in glob(&base.join(expand_path(arg))) {
+                        included.extend(parse_file(&path, depth + 1)?);
+                    }
+                }
+                items.push(Item::Include(included));
+            }
+            _ if args.is_empty() => {
+                return Err(invalid(
+                    &origin,
+                    format!("missing argument for '{keyword}'"),
+                ));
+            }
+            _ => items.push(Item::Setting {
+                keyword,
+                args,
+                origin,
+            }),
+        }
+    }
+
+    Ok(items)
+}
+
+/// Split arguments on whitespace, honouring double quotes.
+fn split_args(text: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+
+    for c in text.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if in_quotes {
+        return None;
+    }
+    if has_arg {
+        args.push(current);
+    }
+    Some(args)
+}
+
+/// A `Host` line matches if any positive pattern matches and no negated
+/// pattern does.
+fn host_matches(patterns: &[String], alias: &str) -> bool {
+    let alias = alias.to_ascii_lowercase();
+    let mut matched = false;
+    for pattern in patterns {
+        let pattern = pattern.to_ascii_lowercase();
+        match pattern.strip_prefix('!') {
+            Some(negated) if wildcard_match(negated, &alias) => return false,
+            Some(_) => {}
+            None => matched |= wildcard_match(&pattern, &alias),
+        }
+    }
+    matched
+}
+
+/// Match `text` against a pattern with `*` and `?` wildcards.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Expand wildcards in the final path component, sorted by name.
+fn glob(path: &Path) -> Vec<PathBuf> {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return vec![path.to_path_buf()];
+    };
+    if !name.contains(['*', '?']) {
+        return vec![path.to_path_buf()];
+    }
+
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut matches: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|file| wildcard_match(name, file))
+        })
+        .map(|entry| entry.path())
+        .collect();
+    matches.sort();
+    matches
+}
+
+/// Expand `%` tokens: `%h` remote host, `%n` original alias, `%p` port,
+/// `%r` remote user, `%u` local user, `%d` home directory, `%%`.
+fn expand_tokens(
+    text: &str,
+    host: &str,
+    alias: &str,
+    port: Option<u16>,
+    user: Option<&str>,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let local_user = std::env::var("USER").unwrap_or_default();
+        match chars.next() {
+            Some('h') => out.push_str(host),
+            Some('n') => out.push_str(alias),
+            Some('p') => out.push_str(&port.unwrap_or(22).to_string()),
+            Some('r') => out.push_str(user.unwrap_or(&local_user)),
+            Some('u') => out.push_str(&local_user),
+            Some('d') => {
+                if let Some(home) = home_dir() {
+                    out.push_str(&home.to_string_lossy());
+                }
+            }
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+/// Expand a leading `~/` to the home directory.
+fn expand_path(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Split `host[:port]`, accepting `[v6addr]:port`.
+fn split_host_port(text: &str) -> Option<(&str, Option<u16>)> {
+    if let Some(rest) = text.strip_prefix('[') {
+        let (host, after) = rest.split_once(']')?;
+        return match after.strip_prefix(':') {
+            Some(port) => Some((host, Some(port.parse().ok()?))),
+            None if after.is_empty() => Some((host, None)),
+            None => None,
+        };
+    }
+    match text.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => Some((host, Some(port.parse().ok()?))),
+        _ if text.is_empty() => None,
+        _ => Some((text, None)),
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+fn parse_value<T: std::str::FromStr>(value: &str, keyword: &str, origin: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid(origin, format!("invalid {keyword} '{value}'")))
+}
+
+fn invalid(origin: &str, message: String) -> crate::error::Error {
+    DriverError::InvalidConfig {
+        message: format!("{origin}: {message}"),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+# Lab routers
+Host core-* !core-backup
+    HostName %h.lab.example
+    User netops
+    Port 2222
+    IdentityFile /keys/lab_ed25519
+
+Host core-backup
+    HostName 10.9.9.9
+
+Host bastion
+    HostName bastion.example.com
+    User jump
+    IdentityFile /keys/jump_ed25519
+
+Host behind-*
+    ProxyJump bastion,alice@relay:2200
+    StrictHostKeyChecking accept-new
+
+Match exec "true"
+    User ignored
+
+Host *
+    User default
+    Port=22
+    ServerAliveInterval 15
+    ServerAliveCountMax 5
+    ConnectTimeout 7
+    BindAddress 10.1.1.5
+    BindInterface eth1
+    IdentityFile "/keys/with space"
+    UserKnownHostsFile /tmp/known_hosts_%h
+"#;
+
+    #[test]
+    fn test_first_value_wins_and_identity_files_accumulate() {
+        let config = OpenSshConfig::parse(CONFIG).unwrap();
+        let host = config.resolve("core-r1").unwrap();
+
+        assert_eq!(host.host_name.as_deref(), Some("core-r1.lab.example"));
+        assert_eq!(host.user.as_deref(), Some("netops"));
+        assert_eq!(host.port, Some(2222));
+        assert_eq!(
+            host.identity_files,
+            vec![
+                PathBuf::from("/keys/lab_ed25519"),
+                PathBuf::from("/keys/with space")
+            ]
+        );
+        assert_eq!(
+            host.user_known_hosts_files,
+            vec![PathBuf::from("/tmp/known_hosts_core-r1.lab.example")]
+        );
+        assert_eq!(
+            host.server_alive_interval,
+            Some(Some(Duration::from_secs(15)))
+        );
+        assert_eq!(host.server_alive_count_max, Some(5));
+        assert_eq!(host.connect_timeout, Some(Duration::from_secs(7)));
+        assert_eq!(host.bind_address, Some(IpAddr::from([10, 1, 1, 5])));
+        assert_eq!(host.bind_interface.as_deref(), Some("eth1"));
+        assert!(host.jump_hosts.is_empty());
+    }
+
+    #[test]
+    fn test_auth_methods_use_every_identity_and_certificate() {
+        use crate::transport::AuthMethod;
+
+        let dir = std::env::temp_dir().join(format!("ferrissh-identities-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["id_rsa", "id_ed25519"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        let host = HostConfig {
+            identity_files: vec![
+                dir.join("missing"),
+                dir.join("id_rsa"),
+                dir.join("id_ed25519"),
+            ],
+            certificate_files: vec![dir.join("id_ed25519-cert.pub")],
+            ..HostConfig::default()
+        };
+        let methods = host.auth_methods();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(matches!(
+            &methods[..],
+            [
+                AuthMethod::Certificate { key: KeySource::Path(cert_key), .. },
+                AuthMethod::PrivateKey { path: first, .. },
+                AuthMethod::PrivateKey { path: second, .. },
+            ] if *cert_key == dir.join("id_ed25519")
+                && *first == dir.join("id_rsa")
+                && *second == dir.join("id_ed25519")
+        ));
+
+        // With no identity file on disk, the first is still tried.
+        let host = HostConfig {
+            identity_files: vec![dir.join("gone"), dir.join("also-gone")],
+            ..HostConfig::default()
+        };
+        assert_eq!(host.auth_methods().len(), 1);
+    }
+
+    #[test]
+    fn test_negated_pattern_excludes_host() {
+        let config = OpenSshConfig::parse(CONFIG).unwrap();
+        let host = config.resolve("core-backup").unwrap();
+
+        assert_eq!(host.host_name.as_deref(), Some("10.9.9.9"));
+        assert_eq!(host.user.as_deref(), Some("default"));
+        assert_eq!(host.port, Some(22));
+    }
+
+    #[test]
+    fn test_unmatched_alias_gets_wildcard_defaults() {
+        let config = OpenSshConfig::parse(CONFIG).unwrap();
+        let host = config.resolve("switch1").unwrap();
+
+        assert_eq!(host.host_name, None);
+        assert_eq!(host.user.as_deref(), Some("default"));
+    }
+
+    #[test]
+    fn test_proxy_jump_resolves_hops() {
+        let config = OpenSshConfig::parse(CONFIG).unwrap();
+        let host = config.resolve("behind-fw").unwrap();
+
+        assert_eq!(host.jump_hosts.len(), 2);
+        let bastion = &host.jump_hosts[0];
+        assert_eq!(bastion.host, "bastion.example.com");
+        assert_eq!(bastion.port, 22);
+        assert_eq!(bastion.username.as_deref(), Some("jump"));
+        assert!(matches!(
+            bastion.auth_methods[..],
+            [crate::transport::AuthMethod::PrivateKey { ref path, .. }]
+                if path == Path::new("/keys/jump_ed25519")
+        ));
+
+        let relay = &host.jump_hosts[1];
+        assert_eq!(relay.host, "relay");
+        assert_eq!(relay.port, 2200);
+        assert_eq!(relay.username.as_deref(), Some("alice"));
+        assert!(matches!(
+            host.strict_host_key_checking,
+            Some(HostKeyVerification::AcceptNew)
+        ));
+    }
+
+    #[test]
+    fn test_include_is_conditional_and_scoped() {
+        let dir = std::env::temp_dir().join(format!("ferrissh-sshcfg-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("conf.d")).unwrap();
+        std::fs::write(
+            dir.join("conf.d/10-lab.conf"),
+            "Host lab1\n    HostName 192.0.2.1\nHost *\n    Port 830\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("conf.d/20-extra.conf"), "User included\n").unwrap();
+        std::fs::write(
+            dir.join("config"),
+            "Host lab*\n    Include conf.d/1*.conf\n    User outer\nHost other\n    Include conf.d/20-extra.conf\n",
+        )
+        .unwrap();
+
+        let config = OpenSshConfig::load(dir.join("config")).unwrap();
+
+        let lab1 = config.resolve("lab1").unwrap();
+        assert_eq!(lab1.host_name.as_deref(), Some("192.0.2.1"));
+        assert_eq!(lab1.port, Some(830));
+        // The included `Host *` does not leak out: `User outer` still applies
+        assert_eq!(lab1.user.as_deref(), Some("outer"));
+
+        let other = config.resolve("other").unwrap();
+        assert_eq!(other.user.as_deref(), Some("included"));
+        assert_eq!(other.port, None);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        let config = OpenSshConfig::load("/nonexistent/ferrissh/config").unwrap();
+        assert!(config.resolve("anything").unwrap().host_name.is_none());
+    }
+
+    #[test]
+    fn test_invalid_value_reports_line() {
+        let config = OpenSshConfig::parse("Host r1\n  Port abc\n").unwrap();
+        let err = config.resolve("r1").unwrap_err().to_string();
+        assert!(err.contains("<config>:2"), "got: {err}");
+        assert!(err.contains("invalid Port 'abc'"), "got: {err}");
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*", "anything"));
+        assert!(wildcard_match("core-?", "core-1"));
+        assert!(!wildcard_match("core-?", "core-12"));
+        assert!(wildcard_match("*.lab.*", "r1.lab.example"));
+        assert!(!wildcard_match("*.lab", "r1.lab.example"));
+    }
+
+    #[test]
+    fn test_split_host_port() {
+        assert_eq!(split_host_port("relay:2200"), Some(("relay", Some(2200))));
+        assert_eq!(split_host_port("relay"), Some(("relay", None)));
+        assert_eq!(split_host_port("[::1]:22"), Some(("::1", Some(22))));
+        assert_eq!(split_host_port("relay:abc"), None);
+    }
+}
@@ -0,0 +1,354 @@
+//! SOCKS5 and HTTP CONNECT proxy tunnelling.
+//!
+//! Establishes a TCP stream to the SSH server through a proxy. The russh
+//! handshake then runs over the returned stream as if it were a direct
+//! connection.
+
+use std::net::IpAddr;
+
+use base64ct::{Base64, Encoding};
+use log::debug;
+use secrecy::ExposeSecret;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use super::config::{ProxyAuth, ProxyConfig, SshConfig};
+use super::tcp;
+use crate::error::TransportError;
+
+/// Upper bound on the size of an HTTP CONNECT response header.
+const MAX_HTTP_HEADER: usize = 8192;
+
+/// Open a tunnel to `host:port` through `proxy`, connecting to the proxy
+/// with `config`'s source address and interface.
+pub(crate) async fn connect(
+    proxy: &ProxyConfig,
+    host: &str,
+    port: u16,
+    config: &SshConfig,
+) -> Result<TcpStream, TransportError> {
+    debug!(
+        "connecting to {}:{} via proxy {}:{}",
+        host,
+        port,
+        proxy.host(),
+        proxy.port()
+    );
+
+    let mut stream = match tcp::connect(proxy.host(), proxy.port(), config).await {
+        Ok(stream) => stream,
+        Err(TransportError::ConnectionFailed {
+            host, port, source, ..
+        }) => {
+            return Err(TransportError::ProxyConnectionFailed { host, port, source });
+        }
+        Err(e) => return Err(e),
+    };
+
+    match proxy {
+        ProxyConfig::Socks5 { auth, .. } => {
+            socks5_handshake(&mut stream, proxy, auth.as_ref(), host, port).await?
+        }
+        ProxyConfig::HttpConnect { auth, .. } => {
+            http_connect_handshake(&mut stream, proxy, auth.as_ref(), host, port).await?
+        }
+    }
+
+    debug!("proxy tunnel to {}:{} established", host, port);
+    Ok(stream)
+}
+
+/// Run the SOCKS5 greeting, optional RFC 1929 auth, and CONNECT request.
+async fn socks5_handshake<S>(
+    stream: &mut S,
+    proxy: &ProxyConfig,
+    auth: Option<&ProxyAuth>,
+    host: &str,
+    port: u16,
+) -> Result<(), TransportError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let io = |e: std::io::Error| proxy_error(proxy, format!("I/O error: {e}"));
+
+    // Greeting: offer "no auth", plus username/password if configured
+    let greeting: &[u8] = if auth.is_some() {
+        &[0x05, 0x02, 0x00, 0x02]
+    } else {
+        &[0x05, 0x01, 0x00]
+    };
+    stream.write_all(greeting).await.map_err(io)?;
+
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await.map_err(io)?;
+    if reply[0] != 0x05 {
+        return Err(proxy_error(proxy, "not a SOCKS5 proxy".into()));
+    }
+
+    match (reply[1], auth) {
+        (0x00, _) => {}
+        (0x02, Some(auth)) => {
+            let username = auth.username.as_bytes();
+            let password = auth.password.expose_secret().as_bytes();
+            if username.len() > 255 || password.len() > 255 {
+                return Err(proxy_error(
+                    proxy,
+                    "SOCKS5 username and password must be at most 255 bytes".into(),
+                ));
+            }
+
+            let mut request = Vec::with_capacity(3 + username.len() + password.len());
+            request.push(0x01);
+            request.push(username.len() as u8);
+            request.extend_from_slice(username);
+            request.push(password.len() as u8);
+            request.extend_from_slice(password);
+            stream.write_all(&request).await.map_err(io)?;
+
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).await.map_err(io)?;
+            if status[1] != 0x00 {
+                return Err(auth_failed(proxy));
+            }
+        }
+        // 0xFF: no acceptable method, or the proxy wants auth we don't have
+        (0x02, None) | (0xFF, _) => return Err(auth_failed(proxy)),
+        (method, _) => {
+            return Err(proxy_error(
+                proxy,
+                format!("unsupported SOCKS5 auth method 0x{method:02x}"),
+            ));
+        }
+    }
+
+    // CONNECT request
+    let mut request = vec![0x05, 0x01, 0x00];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(0x01);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(0x04);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            if host.len() > 255 {
+                return Err(proxy_error(proxy, "hostname too long for SOCKS5".into()));
+            }
+            request.push(0x03);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await.map_err(io)?;
+
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).await.map_err(io)?;
+    if header[1] != 0x00 {
+        return Err(proxy_error(
+            proxy,
+            format!("SOCKS5 connect failed: {}", socks5_reply_message(header[1])),
+        ));
+    }
+
+    // Discard the bound address
+    let addr_len = match header[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await.map_err(io)?;
+            len[0] as usize
+        }
+        atyp => {
+            return Err(proxy_error(
+                proxy,
+                format!("invalid SOCKS5 address type 0x{atyp:02x}"),
+            ));
+        }
+    };
+    let mut bound = vec![0u8; addr_len + 2];
+    stream.read_exact(&mut bound).await.map_err(io)?;
+
+    Ok(())
+}
+
+/// Send an HTTP `CONNECT` request and wait for a 2xx response.
+async fn http_connect_handshake<S>(
+    stream: &mut S,
+    proxy: &ProxyConfig,
+    auth: Option<&ProxyAuth>,
+    host: &str,
+    port: u16,
+) -> Result<(), TransportError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let io = |e: std::io::Error| proxy_error(proxy, format!("I/O error: {e}"));
+
+    let authority = if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    };
+
+    let mut request = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+    if let Some(auth) = auth {
+        let credentials = format!("{}:{}", auth.username, auth.password.expose_secret());
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            Base64::encode_string(credentials.as_bytes())
+        ));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await.map_err(io)?;
+
+    // Read the response header one byte at a time so nothing past the
+    // blank line (i.e. the SSH banner) is consumed.
+    let mut response = Vec::with_capacity(256);
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_HTTP_HEADER {
+            return Err(proxy_error(proxy, "HTTP response header too large".into()));
+        }
+        let byte = stream.read_u8().await.map_err(io)?;
+        response.push(byte);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    let status = status_line
+        .strip_prefix("HTTP/1.")
+        .and_then(|rest| rest.get(2..5))
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| proxy_error(proxy, format!("invalid HTTP response '{status_line}'")))?;
+
+    match status {
+        200..=299 => Ok(()),
+        407 => Err(auth_failed(proxy)),
+        _ => Err(proxy_error(
+            proxy,
+            format!("CONNECT refused: {status_line}"),
+        )),
+    }
+}
+
+/// Human-readable text for a SOCKS5 reply code (RFC 1928 §6).
+fn socks5_reply_message(code: u8) -> &'static str {
+    match code {
+        0x01 => "general SOCKS server failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+fn proxy_error(proxy: &ProxyConfig, message: String) -> TransportError {
+    TransportError::Proxy {
+        host: proxy.host().to_string(),
+        port: proxy.port(),
+        message,
+    }
+}
+
+fn auth_failed(proxy: &ProxyConfig) -> TransportError {
+    TransportError::ProxyAuthenticationFailed {
+        host: proxy.host().to_string(),
+        port: proxy.port(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_socks5_domain_request_encoding() {
+        let (mut client, mut server) = tokio::io::duplex(256);
+        let proxy = ProxyConfig::socks5("proxy", 1080);
+
+        let handshake = tokio::spawn(async move {
+            socks5_handshake(&mut client, &proxy, None, "router1", 22).await
+        });
+
+        let mut greeting = [0u8; 3];
+        server.read_exact(&mut greeting).await.unwrap();
+        assert_eq!(greeting, [0x05, 0x01, 0x00]);
+        server.write_all(&[0x05, 0x00]).await.unwrap();
+
+        let mut request = [0u8; 5 + 7 + 2];
+        server.read_exact(&mut request).await.unwrap();
+        assert_eq!(&request[..5], &[0x05, 0x01, 0x00, 0x03, 7]);
+        assert_eq!(&request[5..12], b"router1");
+        assert_eq!(&request[12..], &22u16.to_be_bytes());
+
+        server
+            .write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+            .await
+            .unwrap();
+        handshake.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_socks5_reply_error() {
+        let (mut client, mut server) = tokio::io::duplex(256);
+        let proxy = ProxyConfig::socks5("proxy", 1080);
+
+        let handshake = tokio::spawn(async move {
+            socks5_handshake(&mut client, &proxy, None, "10.0.0.1", 22).await
+        });
+
+        let mut greeting = [0u8; 3];
+        server.read_exact(&mut greeting).await.unwrap();
+        server.write_all(&[0x05, 0x00]).await.unwrap();
+        let mut request = [0u8; 10];
+        server.read_exact(&mut request).await.unwrap();
+        assert_eq!(request[3], 0x01);
+        server
+            .write_all(&[0x05, 0x05, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+            .await
+            .unwrap();
+
+        let err = handshake.await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("connection refused"), "got: {err}");
+    }
+
+    #[tokio::test]
+    async fn test_http_connect_leaves_trailing_bytes() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        let proxy = ProxyConfig::http_connect("proxy", 3128).with_auth("alice", "secret");
+
+        server
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\nSSH-2.0-test\r\n")
+            .await
+            .unwrap();
+        http_connect_handshake(
+            &mut client,
+            &proxy,
+            Some(&ProxyAuth {
+                username: "alice".into(),
+                password: "secret".into(),
+            }),
+            "fe80::1",
+            22,
+        )
+        .await
+        .unwrap();
+
+        let mut banner = [0u8; 14];
+        client.read_exact(&mut banner).await.unwrap();
+        assert_eq!(&banner, b"SSH-2.0-test\r\n");
+
+        let mut request = vec![0u8; 256];
+        let n = server.read(&mut request).await.unwrap();
+        let request = String::from_utf8_lossy(&request[..n]);
+        assert!(request.starts_with("CONNECT [fe80::1]:22 HTTP/1.1\r\n"));
+        assert!(request.contains("Proxy-Authorization: Basic YWxpY2U6c2VjcmV0\r\n"));
+    }
+}
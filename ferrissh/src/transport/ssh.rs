@@ -1,40 +1,139 @@
 //! SSH transport implementation using russh.
 
-use std::path::PathBuf;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
 use log::{debug, warn};
-use russh::Channel;
-use russh::client::{self, Handle, KeyboardInteractiveAuthResponse, Msg};
-use russh::keys::{PrivateKeyWithHashAlg, PublicKey, load_secret_key};
-use tokio::sync::watch;
+use russh::client::{self, AuthResult, Handle, KeyboardInteractiveAuthResponse, Msg};
+#[cfg(unix)]
+use russh::keys::agent::client::AgentClient;
+use russh::keys::{
+    Certificate, PrivateKey, PrivateKeyWithHashAlg, PublicKey, decode_secret_key,
+    load_openssh_certificate, load_secret_key,
+};
+use russh::{Channel, ChannelMsg, MethodKind, MethodSet, Sig};
+#[cfg(unix)]
+use tokio::net::UnixStream;
+use tokio::sync::{RwLock, watch};
 
-use secrecy::ExposeSecret;
+use secrecy::{ExposeSecret, SecretString};
 
-use super::config::{AuthMethod, HostKeyVerification, SshConfig};
-use crate::error::{DisconnectReason, Result, TransportError};
+use super::algorithms::NegotiatedAlgorithms;
+use super::config::{
+    AuthMethod, CertificateSource, HostKeyVerification, KeySource, Protocol, SshConfig,
+    TerminalOptions,
+};
+use super::host_key::{fingerprint_matches, sha256_fingerprint};
+use super::info::ConnectionInfo;
+use super::keyboard_interactive::{
+    KeyboardInteractivePrompt, KeyboardInteractiveResponder, PasswordResponder,
+};
+use super::proxy;
+use super::tcp;
+use super::traits::{ShellChannel, ShellOutput, Transport};
+use crate::error::{ChannelError, DisconnectReason, Result, TransportError};
+use crate::forward::RemoteForwards;
 
 /// SSH transport wrapping russh client.
 pub struct SshTransport {
-    /// The russh session handle.
-    session: Handle<SshHandler>,
+    /// The russh session handle. Only `tcpip-forward` requests need it
+    /// mutably; everything else takes a read lock.
+    session: RwLock<Handle<SshHandler>>,
+
+    /// Jump host sessions the connection is tunnelled through, outermost
+    /// first. Kept alive for as long as the target session.
+    jumps: Vec<Handle<SshHandler>>,
 
     /// Configuration used for this connection.
     config: SshConfig,
 
+    /// What was learned about the target while connecting.
+    info: ConnectionInfo,
+
     /// Sender for disconnect notifications (shared with SshHandler).
     disconnect_tx: Arc<watch::Sender<Option<DisconnectReason>>>,
 
     /// Receiver for disconnect notifications.
     disconnect_rx: watch::Receiver<Option<DisconnectReason>>,
+
+    /// Remote forwards on this connection (shared with SshHandler).
+    remote_forwards: Arc<RemoteForwards>,
 }
 
 impl SshTransport {
     /// Connect to the SSH server and authenticate.
+    ///
+    /// If `config.jump_hosts` is non-empty, each jump host is connected and
+    /// authenticated in turn, and the next hop is reached through a
+    /// `direct-tcpip` channel on the previous one.
     pub async fn connect(config: SshConfig) -> Result<Self> {
-        debug!("connecting to {}:{}", config.host, config.port);
+        let (disconnect_tx, disconnect_rx) = watch::channel(None);
+        let disconnect_tx = Arc::new(disconnect_tx);
+
+        let mut jumps: Vec<Handle<SshHandler>> = Vec::with_capacity(config.jump_hosts.len());
+        for jump in &config.jump_hosts {
+            let hop_config = jump.to_ssh_config(&config);
+            let (session, _) = Self::connect_hop(
+                &hop_config,
+                jumps.last(),
+                true,
+                &disconnect_tx,
+                Arc::default(),
+            )
+            .await
+            .map_err(|e| TransportError::JumpHost {
+                host: jump.host.clone(),
+                port: jump.port,
+                source: Box::new(e),
+            })?;
+            jumps.push(session);
+        }
+
+        let remote_forwards: Arc<RemoteForwards> = Arc::default();
+        let (session, info) = Self::connect_hop(
+            &config,
+            jumps.last(),
+            false,
+            &disconnect_tx,
+            remote_forwards.clone(),
+        )
+        .await?;
+
+        Ok(Self {
+            session: RwLock::new(session),
+            jumps,
+            config,
+            info,
+            disconnect_tx,
+            disconnect_rx,
+            remote_forwards,
+        })
+    }
+
+    /// Connect and authenticate a single hop.
+    ///
+    /// Uses a TCP socket (through `config.proxy` if set) when `via` is
+    /// `None`, otherwise a `direct-tcpip` channel on the previous hop's
+    /// session. Returns the session and what was learned about the server.
+    async fn connect_hop(
+        config: &SshConfig,
+        via: Option<&Handle<SshHandler>>,
+        jump: bool,
+        disconnect_tx: &Arc<watch::Sender<Option<DisconnectReason>>>,
+        remote_forwards: Arc<RemoteForwards>,
+    ) -> std::result::Result<(Handle<SshHandler>, ConnectionInfo), TransportError> {
+        debug!(
+            "connecting to {}:{}{}",
+            config.host,
+            config.port,
+            if via.is_some() { " (tunnelled)" } else { "" }
+        );
 
         let mut russh_cfg = client::Config {
+            preferred: config.algorithms.to_preferred()?,
             inactivity_timeout: config.inactivity_timeout,
             keepalive_interval: config.keepalive_interval,
             keepalive_max: config.keepalive_max,
@@ -52,72 +151,132 @@ impl SshTransport {
         let ssh_config = Arc::new(russh_cfg);
 
         let host_key_error: Arc<Mutex<Option<TransportError>>> = Arc::new(Mutex::new(None));
-
-        let (disconnect_tx, disconnect_rx) = watch::channel(None);
-        let disconnect_tx = Arc::new(disconnect_tx);
+        let info = Arc::new(Mutex::new(ConnectionInfo::new(Protocol::Ssh)));
 
         let handler = SshHandler {
             host: config.host.clone(),
             port: config.port,
+            jump,
             host_key_verification: config.host_key_verification.clone(),
             known_hosts_path: config.known_hosts_path.clone(),
             host_key_error: host_key_error.clone(),
+            info: info.clone(),
             disconnect_tx: disconnect_tx.clone(),
+            remote_forwards,
         };
 
-        // Connect to the server
-        let mut session = tokio::time::timeout(
-            config.timeout,
-            client::connect(ssh_config, (config.host.as_str(), config.port), handler),
-        )
-        .await
-        .map_err(|_| TransportError::Timeout(config.timeout))?
-        .map_err(|e| {
-            // If check_server_key stored a detailed error, use that instead
-            // of the generic russh::Error::UnknownKey
-            if let Some(hk_err) = host_key_error.lock().unwrap().take() {
-                hk_err
-            } else {
-                TransportError::Ssh(e)
+        let connect = async {
+            match (via, &config.proxy) {
+                (Some(previous), _) => {
+                    let channel = previous
+                        .channel_open_direct_tcpip(
+                            config.host.clone(),
+                            config.port.into(),
+                            "127.0.0.1",
+                            0,
+                        )
+                        .await
+                        .map_err(|e| TransportError::ConnectionFailed {
+                            host: config.host.clone(),
+                            port: config.port,
+                            source: std::io::Error::other(format!(
+                                "jump host refused direct-tcpip channel: {e}"
+                            )),
+                            attempts: Vec::new(),
+                        })?;
+                    client::connect_stream(ssh_config, channel.into_stream(), handler).await
+                }
+                (None, proxy_config) => {
+                    let stream = match proxy_config {
+                        Some(proxy_config) => {
+                            proxy::connect(proxy_config, &config.host, config.port, config).await?
+                        }
+                        None => tcp::connect(&config.host, config.port, config).await?,
+                    };
+                    let _ = stream.set_nodelay(true);
+                    info.lock().unwrap().set_addresses(&stream);
+                    client::connect_stream(ssh_config, stream, handler).await
+                }
             }
-        })?;
+            .map_err(|e| Self::handshake_error(e, &host_key_error))
+        };
+
+        // Connect to the server
+        let mut session = tokio::time::timeout(config.connect_timeout, connect)
+            .await
+            .map_err(|_| TransportError::Timeout(config.connect_timeout))??;
 
         debug!("connected to {}:{}", config.host, config.port);
 
+        {
+            let info = info.lock().unwrap();
+            let negotiated = &info.algorithms;
+            debug!(
+                "server {:?}; negotiated kex={} host_key={} cipher={} mac={}",
+                info.server_id.as_deref().unwrap_or_default(),
+                negotiated.kex,
+                negotiated.host_key,
+                negotiated.cipher,
+                negotiated.mac_client_to_server
+            );
+        }
+
         // Authenticate
-        Self::authenticate(&mut session, &config).await?;
+        Self::authenticate(&mut session, config).await?;
 
-        Ok(Self {
-            session,
-            config,
-            disconnect_tx,
-            disconnect_rx,
-        })
+        // The authentication banner arrives during authentication.
+        let info = info.lock().unwrap().clone();
+        Ok((session, info))
+    }
+
+    /// Map a handshake failure to a transport error.
+    ///
+    /// If check_server_key stored a detailed error, use that instead of the
+    /// generic russh::Error::UnknownKey.
+    fn handshake_error(
+        error: russh::Error,
+        host_key_error: &Mutex<Option<TransportError>>,
+    ) -> TransportError {
+        match host_key_error.lock().unwrap().take() {
+            Some(hk_err) => hk_err,
+            None => TransportError::Ssh(error),
+        }
     }
 
     /// Open a new PTY channel on this connection.
-    pub async fn open_channel(&self) -> Result<Channel<Msg>> {
+    pub async fn open_channel(&self, terminal: &TerminalOptions) -> Result<Channel<Msg>> {
         debug!(
-            "opening PTY channel ({}x{})",
-            self.config.terminal_width, self.config.terminal_height
+            "opening PTY channel ({}, {}x{})",
+            terminal.term, terminal.width, terminal.height
         );
 
         let channel = self
             .session
+            .read()
+            .await
             .channel_open_session()
             .await
             .map_err(TransportError::Ssh)?;
 
+        // Environment first: servers apply it when the shell starts.
+        // No reply wanted; names the server doesn't accept are dropped.
+        for (name, value) in &terminal.env {
+            channel
+                .set_env(false, name.as_str(), value.as_str())
+                .await
+                .map_err(TransportError::Ssh)?;
+        }
+
         // Request PTY
         channel
             .request_pty(
                 true,
-                "xterm",
-                self.config.terminal_width,
-                self.config.terminal_height,
+                &terminal.term,
+                terminal.width,
+                terminal.height,
                 0,
                 0,
-                &[],
+                &terminal.modes,
             )
             .await
             .map_err(TransportError::Ssh)?;
@@ -131,82 +290,418 @@ impl SshTransport {
         Ok(channel)
     }
 
+    /// Open a new session channel running `command` without a PTY.
+    pub async fn open_exec_channel(&self, command: &str) -> Result<Channel<Msg>> {
+        debug!("opening exec channel: {:?}", command);
+
+        let channel = self
+            .session
+            .read()
+            .await
+            .channel_open_session()
+            .await
+            .map_err(TransportError::Ssh)?;
+
+        channel
+            .exec(true, command)
+            .await
+            .map_err(TransportError::Ssh)?;
+
+        Ok(channel)
+    }
+
+    /// Open a new session channel running the `name` subsystem (e.g. `sftp`).
+    pub async fn open_subsystem_channel(&self, name: &str) -> Result<Channel<Msg>> {
+        debug!("opening {} subsystem channel", name);
+
+        let mut channel = self
+            .session
+            .read()
+            .await
+            .channel_open_session()
+            .await
+            .map_err(TransportError::Ssh)?;
+
+        channel
+            .request_subsystem(true, name)
+            .await
+            .map_err(TransportError::Ssh)?;
+
+        // Unlike a shell, a subsystem speaks first only after the client
+        // does, so wait for the server's verdict before handing it over.
+        let verdict = async {
+            loop {
+                match channel.wait().await {
+                    Some(ChannelMsg::Success) => return Ok(()),
+                    Some(ChannelMsg::Failure) => {
+                        return Err(ChannelError::SubsystemFailed {
+                            name: name.to_string(),
+                            message: "rejected by server".to_string(),
+                        });
+                    }
+                    Some(ChannelMsg::Close) | None => {
+                        return Err(ChannelError::SubsystemFailed {
+                            name: name.to_string(),
+                            message: "channel closed before the server replied".to_string(),
+                        });
+                    }
+                    Some(_) => {}
+                }
+            }
+        };
+        tokio::time::timeout(self.config.timeout, verdict)
+            .await
+            .map_err(|_| TransportError::Timeout(self.config.timeout))??;
+        Ok(channel)
+    }
+
+    /// Open a `direct-tcpip` channel to `host:port` as seen from the server,
+    /// on behalf of a local connection from `originator`.
+    pub async fn open_direct_tcpip(
+        &self,
+        host: &str,
+        port: u16,
+        originator: SocketAddr,
+    ) -> Result<Channel<Msg>> {
+        debug!("opening direct-tcpip channel to {}:{}", host, port);
+
+        self.session
+            .read()
+            .await
+            .channel_open_direct_tcpip(
+                host,
+                port.into(),
+                originator.ip().to_string(),
+                originator.port().into(),
+            )
+            .await
+            .map_err(|e| {
+                TransportError::ForwardRejected {
+                    target: format!("{host}:{port}"),
+                    reason: e.to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// Ask the server to listen on `address:port` (`tcpip-forward`).
+    ///
+    /// Returns the port the server bound, which differs from `port` only
+    /// when 0 was requested.
+    pub async fn request_remote_forward(&self, address: &str, port: u32) -> Result<u32> {
+        debug!("requesting remote forward on {}:{}", address, port);
+
+        let bound = self
+            .session
+            .write()
+            .await
+            .tcpip_forward(address, port)
+            .await
+            .map_err(|e| TransportError::ForwardRejected {
+                target: format!("{address}:{port}"),
+                reason: e.to_string(),
+            })?;
+        Ok(if port == 0 { bound } else { port })
+    }
+
+    /// Ask the server to stop listening on `address:port`.
+    pub async fn cancel_remote_forward(&self, address: &str, port: u32) -> Result<()> {
+        debug!("cancelling remote forward on {}:{}", address, port);
+
+        self.session
+            .read()
+            .await
+            .cancel_tcpip_forward(address, port)
+            .await
+            .map_err(|e| TransportError::Ssh(e).into())
+    }
+
+    /// Remote forwards registered on this connection.
+    pub(crate) fn remote_forwards(&self) -> &RemoteForwards {
+        &self.remote_forwards
+    }
+
     /// Authenticate with the server.
-    async fn authenticate(session: &mut Handle<SshHandler>, config: &SshConfig) -> Result<()> {
-        let auth_method = match &config.auth {
-            AuthMethod::None => "none",
-            AuthMethod::Password(_) => "password",
-            AuthMethod::PrivateKey { .. } => "key",
+    ///
+    /// Tries each configured method in order. Once the server has replied,
+    /// methods it does not list as able to continue are skipped; a partial
+    /// success simply moves on to the next method.
+    async fn authenticate(
+        session: &mut Handle<SshHandler>,
+        config: &SshConfig,
+    ) -> std::result::Result<(), TransportError> {
+        let methods = match config.auth_methods.as_slice() {
+            [] => &[AuthMethod::None][..],
+            methods => methods,
         };
-        debug!(
-            "authenticating user '{}' via {}",
-            config.username, auth_method
-        );
 
-        let success = match &config.auth {
+        let mut remaining: Option<MethodSet> = None;
+        let mut attempted = Vec::new();
+
+        for method in methods {
+            if let Some(offered) = &remaining
+                && !Self::is_offered(method, offered)
+            {
+                debug!("skipping {}: not offered by server", method.label());
+                continue;
+            }
+
+            debug!(
+                "authenticating user '{}' via {}",
+                config.username,
+                method.label()
+            );
+            attempted.push(method.name().to_string());
+
+            match Self::try_method(session, &config.username, method, remaining.as_ref()).await? {
+                AuthResult::Success => {
+                    debug!("authentication successful for '{}'", config.username);
+                    return Ok(());
+                }
+                AuthResult::Failure {
+                    remaining_methods,
+                    partial_success,
+                } => {
+                    if partial_success {
+                        debug!(
+                            "{} partially accepted, server continues with {:?}",
+                            method.label(),
+                            &*remaining_methods
+                        );
+                    }
+                    remaining = Some(remaining_methods);
+                }
+            }
+        }
+
+        Err(TransportError::AuthenticationFailed {
+            user: config.username.clone(),
+            methods: attempted,
+        })
+    }
+
+    /// Whether the server lists `method` among those that can continue.
+    fn is_offered(method: &AuthMethod, offered: &MethodSet) -> bool {
+        match method {
+            AuthMethod::None => offered.contains(&MethodKind::None),
+            // Password falls back to keyboard-interactive
+            AuthMethod::Password(_) => {
+                offered.contains(&MethodKind::Password)
+                    || offered.contains(&MethodKind::KeyboardInteractive)
+            }
+            AuthMethod::PrivateKey { .. }
+            | AuthMethod::PrivateKeyPem { .. }
+            | AuthMethod::Certificate { .. }
+            | AuthMethod::Agent { .. } => offered.contains(&MethodKind::PublicKey),
+            AuthMethod::KeyboardInteractive(_) => {
+                offered.contains(&MethodKind::KeyboardInteractive)
+            }
+        }
+    }
+
+    /// Attempt a single authentication method.
+    async fn try_method(
+        session: &mut Handle<SshHandler>,
+        username: &str,
+        method: &AuthMethod,
+        offered: Option<&MethodSet>,
+    ) -> std::result::Result<AuthResult, TransportError> {
+        match method {
             AuthMethod::None => session
-                .authenticate_none(&config.username)
+                .authenticate_none(username)
                 .await
-                .map_err(TransportError::Ssh)?
-                .success(),
+                .map_err(TransportError::Ssh),
             AuthMethod::Password(password) => {
-                let pwd = password.expose_secret();
-                let result = session
-                    .authenticate_password(&config.username, pwd)
-                    .await
-                    .map_err(TransportError::Ssh)?;
-
-                if result.success() {
-                    true
+                let result = if offered.is_none_or(|m| m.contains(&MethodKind::Password)) {
+                    session
+                        .authenticate_password(username, password.expose_secret())
+                        .await
+                        .map_err(TransportError::Ssh)?
                 } else {
-                    // Password auth failed — try keyboard-interactive as fallback
-                    debug!("Password auth failed, trying keyboard-interactive");
-                    Self::authenticate_keyboard_interactive(session, &config.username, pwd).await?
+                    AuthResult::Failure {
+                        remaining_methods: offered.cloned().unwrap_or_else(MethodSet::all),
+                        partial_success: false,
+                    }
+                };
+
+                match result {
+                    AuthResult::Failure {
+                        ref remaining_methods,
+                        partial_success: false,
+                    } if remaining_methods.contains(&MethodKind::KeyboardInteractive) => {
+                        // Password auth refused — try keyboard-interactive as fallback
+                        debug!("Password auth failed, trying keyboard-interactive");
+                        let responder = PasswordResponder::from(password.clone());
+                        Self::authenticate_keyboard_interactive(session, username, &responder).await
+                    }
+                    result => Ok(result),
                 }
             }
             AuthMethod::PrivateKey { path, passphrase } => {
-                let key = load_secret_key(path, passphrase.as_ref().map(|s| s.expose_secret()))
-                    .map_err(|e| TransportError::Key(e.to_string()))?;
-
-                // Get the best RSA hash algorithm supported by the server
-                let hash_alg = session
-                    .best_supported_rsa_hash()
-                    .await
-                    .map_err(TransportError::Ssh)?
-                    .flatten();
+                let key = Self::load_key(&KeySource::Path(path.clone()), passphrase.as_ref())?;
+                Self::authenticate_key(session, username, key).await
+            }
+            AuthMethod::PrivateKeyPem { pem, passphrase } => {
+                let key = Self::load_key(&KeySource::Pem(pem.clone()), passphrase.as_ref())?;
+                Self::authenticate_key(session, username, key).await
+            }
+            AuthMethod::Certificate {
+                key,
+                passphrase,
+                certificate,
+            } => {
+                let key = Self::load_key(key, passphrase.as_ref())?;
+                let cert = match certificate {
+                    CertificateSource::Path(path) => load_openssh_certificate(path),
+                    CertificateSource::OpenSsh(text) => Certificate::from_openssh(text),
+                }
+                .map_err(|e| TransportError::Key(format!("invalid certificate: {e}")))?;
 
                 session
-                    .authenticate_publickey(
-                        &config.username,
-                        PrivateKeyWithHashAlg::new(Arc::new(key), hash_alg),
-                    )
+                    .authenticate_openssh_cert(username, Arc::new(key), cert)
                     .await
-                    .map_err(TransportError::Ssh)?
-                    .success()
+                    .map_err(TransportError::Ssh)
+            }
+            AuthMethod::KeyboardInteractive(responder) => {
+                Self::authenticate_keyboard_interactive(session, username, responder.as_ref()).await
             }
+            AuthMethod::Agent { socket } => {
+                Self::authenticate_agent(session, username, socket.as_deref(), offered).await
+            }
+        }
+    }
+
+    /// Attempt public key authentication with a loaded private key.
+    async fn authenticate_key(
+        session: &mut Handle<SshHandler>,
+        username: &str,
+        key: PrivateKey,
+    ) -> std::result::Result<AuthResult, TransportError> {
+        // Get the best RSA hash algorithm supported by the server
+        let hash_alg = session
+            .best_supported_rsa_hash()
+            .await
+            .map_err(TransportError::Ssh)?
+            .flatten();
+
+        session
+            .authenticate_publickey(
+                username,
+                PrivateKeyWithHashAlg::new(Arc::new(key), hash_alg),
+            )
+            .await
+            .map_err(TransportError::Ssh)
+    }
+
+    /// Load a private key from a file or from in-memory key material.
+    fn load_key(
+        source: &KeySource,
+        passphrase: Option<&SecretString>,
+    ) -> std::result::Result<PrivateKey, TransportError> {
+        let passphrase = passphrase.map(|s| s.expose_secret());
+        let key = match source {
+            KeySource::Path(path) => load_secret_key(path, passphrase),
+            KeySource::Pem(pem) => decode_secret_key(pem.expose_secret(), passphrase),
         };
+        key.map_err(|e| TransportError::Key(e.to_string()))
+    }
 
-        if !success {
-            return Err(TransportError::AuthenticationFailed {
-                user: config.username.clone(),
+    /// Attempt public key authentication with each identity held by an SSH agent.
+    ///
+    /// Returns a failure if the agent holds no identities or the server
+    /// rejects all of them.
+    #[cfg(unix)]
+    async fn authenticate_agent(
+        session: &mut Handle<SshHandler>,
+        username: &str,
+        socket: Option<&Path>,
+        offered: Option<&MethodSet>,
+    ) -> std::result::Result<AuthResult, TransportError> {
+        let mut agent = Self::connect_agent(socket).await?;
+
+        let identities = agent
+            .request_identities()
+            .await
+            .map_err(|e| TransportError::Agent(e.to_string()))?;
+        debug!("agent offered {} identities", identities.len());
+
+        let mut result = AuthResult::Failure {
+            remaining_methods: offered.cloned().unwrap_or_else(MethodSet::all),
+            partial_success: false,
+        };
+        if identities.is_empty() {
+            return Ok(result);
+        }
+
+        // Get the best RSA hash algorithm supported by the server
+        let rsa_hash = session
+            .best_supported_rsa_hash()
+            .await
+            .map_err(TransportError::Ssh)?
+            .flatten();
+
+        for key in identities {
+            let hash_alg = if key.algorithm().is_rsa() {
+                rsa_hash
+            } else {
+                None
+            };
+            debug!("trying agent identity {}", key.algorithm());
+
+            result = session
+                .authenticate_publickey_with(username, key, hash_alg, &mut agent)
+                .await
+                .map_err(|e| TransportError::Agent(e.to_string()))?;
+
+            if matches!(
+                result,
+                AuthResult::Success
+                    | AuthResult::Failure {
+                        partial_success: true,
+                        ..
+                    }
+            ) {
+                break;
             }
-            .into());
         }
 
-        debug!("authentication successful for '{}'", config.username);
-        Ok(())
+        Ok(result)
+    }
+
+    /// SSH agent authentication is only supported over Unix sockets.
+    #[cfg(not(unix))]
+    async fn authenticate_agent(
+        _session: &mut Handle<SshHandler>,
+        _username: &str,
+        _socket: Option<&Path>,
+        _offered: Option<&MethodSet>,
+    ) -> std::result::Result<AuthResult, TransportError> {
+        Err(TransportError::Agent(
+            "SSH agent sockets are only supported on Unix".into(),
+        ))
+    }
+
+    /// Connect to the SSH agent at `socket`, or at `SSH_AUTH_SOCK` if `None`.
+    #[cfg(unix)]
+    async fn connect_agent(
+        socket: Option<&Path>,
+    ) -> std::result::Result<AgentClient<UnixStream>, TransportError> {
+        match socket {
+            Some(path) => AgentClient::connect_uds(path).await,
+            None => AgentClient::connect_env().await,
+        }
+        .map_err(|e| TransportError::Agent(e.to_string()))
     }
 
     /// Attempt keyboard-interactive authentication.
     ///
-    /// For each prompt the server sends, if it looks like a password prompt
-    /// we send the password; otherwise we send an empty string (servers can
-    /// send empty or informational prompts).
+    /// Each round the server sends is passed to `responder`, whose answers
+    /// are sent back until the server accepts or rejects.
     async fn authenticate_keyboard_interactive(
         session: &mut Handle<SshHandler>,
         username: &str,
-        password: &str,
-    ) -> Result<bool> {
+        responder: &dyn KeyboardInteractiveResponder,
+    ) -> std::result::Result<AuthResult, TransportError> {
         let mut response = session
             .authenticate_keyboard_interactive_start(username, None)
             .await
@@ -214,19 +709,34 @@ impl SshTransport {
 
         loop {
             match response {
-                KeyboardInteractiveAuthResponse::Success => return Ok(true),
-                KeyboardInteractiveAuthResponse::Failure { .. } => return Ok(false),
-                KeyboardInteractiveAuthResponse::InfoRequest { prompts, .. } => {
-                    let answers: Vec<String> = prompts
-                        .iter()
-                        .map(|p| {
-                            if p.prompt.to_lowercase().contains("password") {
-                                password.to_string()
-                            } else {
-                                String::new()
-                            }
+                KeyboardInteractiveAuthResponse::Success => return Ok(AuthResult::Success),
+                KeyboardInteractiveAuthResponse::Failure {
+                    remaining_methods,
+                    partial_success,
+                } => {
+                    return Ok(AuthResult::Failure {
+                        remaining_methods,
+                        partial_success,
+                    });
+                }
+                KeyboardInteractiveAuthResponse::InfoRequest {
+                    name,
+                    instructions,
+                    prompts,
+                } => {
+                    let prompts: Vec<KeyboardInteractivePrompt> = prompts
+                        .into_iter()
+                        .map(|p| KeyboardInteractivePrompt {
+                            prompt: p.prompt,
+                            echo: p.echo,
                         })
                         .collect();
+                    debug!(
+                        "keyboard-interactive round '{}' with {} prompts",
+                        name,
+                        prompts.len()
+                    );
+                    let answers = responder.respond(&name, &instructions, &prompts);
 
                     response = session
                         .authenticate_keyboard_interactive_respond(answers)
@@ -237,12 +747,20 @@ impl SshTransport {
         }
     }
 
+    /// Algorithms agreed with the server during key exchange.
+    pub fn negotiated_algorithms(&self) -> &NegotiatedAlgorithms {
+        &self.info.algorithms
+    }
+
     /// Check if the SSH session is still alive.
     ///
     /// Returns `false` if the background session task has exited (due to
     /// keepalive timeout, I/O error, or server disconnect).
     pub fn is_alive(&self) -> bool {
-        !self.session.is_closed()
+        // A writer only holds the lock while a forward request is in flight.
+        self.session
+            .try_read()
+            .map_or(true, |session| !session.is_closed())
     }
 
     /// Get a reference to the disconnect sender.
@@ -267,12 +785,27 @@ impl SshTransport {
         );
 
         self.session
+            .read()
+            .await
             .disconnect(russh::Disconnect::ByApplication, "", "en")
             .await
             .map_err(TransportError::Ssh)?;
+        self.disconnect_jumps().await;
         Ok(())
     }
 
+    /// Tear down jump host sessions, innermost first.
+    async fn disconnect_jumps(&self) {
+        for jump in self.jumps.iter().rev() {
+            if let Err(e) = jump
+                .disconnect(russh::Disconnect::ByApplication, "", "en")
+                .await
+            {
+                debug!("jump host disconnect failed: {}", e);
+            }
+        }
+    }
+
     /// Close the connection.
     pub async fn close(self) -> Result<()> {
         debug!(
@@ -291,27 +824,156 @@ impl SshTransport {
         });
 
         self.session
+            .read()
+            .await
             .disconnect(russh::Disconnect::ByApplication, "", "en")
             .await
             .map_err(TransportError::Ssh)?;
+        self.disconnect_jumps().await;
         Ok(())
     }
 }
 
+impl Transport for SshTransport {
+    fn protocol(&self) -> Protocol {
+        Protocol::Ssh
+    }
+
+    fn connection_info(&self) -> &ConnectionInfo {
+        &self.info
+    }
+
+    fn open_shell<'a>(
+        &'a self,
+        terminal: &'a TerminalOptions,
+    ) -> BoxFuture<'a, Result<Box<dyn ShellChannel>>> {
+        Box::pin(async move {
+            let channel = self.open_channel(terminal).await?;
+            Ok(Box::new(channel) as Box<dyn ShellChannel>)
+        })
+    }
+
+    fn is_alive(&self) -> bool {
+        SshTransport::is_alive(self)
+    }
+
+    fn disconnect_tx(&self) -> &Arc<watch::Sender<Option<DisconnectReason>>> {
+        &self.disconnect_tx
+    }
+
+    fn disconnect_rx(&self) -> &watch::Receiver<Option<DisconnectReason>> {
+        &self.disconnect_rx
+    }
+
+    fn disconnect(&self) -> BoxFuture<'_, std::result::Result<(), TransportError>> {
+        Box::pin(SshTransport::disconnect(self))
+    }
+
+    fn as_ssh(&self) -> Option<&SshTransport> {
+        Some(self)
+    }
+}
+
+impl ShellChannel for Channel<Msg> {
+    fn write<'a>(&'a mut self, data: &'a [u8]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.data(data).await.map_err(ChannelError::Ssh)?;
+            Ok(())
+        })
+    }
+
+    fn read(&mut self) -> BoxFuture<'_, Option<ShellOutput>> {
+        Box::pin(async move {
+            loop {
+                match self.wait().await? {
+                    ChannelMsg::Data { data } => {
+                        return Some(ShellOutput::Data(Bytes::copy_from_slice(&data)));
+                    }
+                    ChannelMsg::ExtendedData { data, ext: 1 } => {
+                        return Some(ShellOutput::Stderr(Bytes::copy_from_slice(&data)));
+                    }
+                    ChannelMsg::Eof => return Some(ShellOutput::Eof),
+                    _ => {}
+                }
+            }
+        })
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            self.window_change(width, height, 0, 0)
+                .await
+                .map_err(ChannelError::Ssh)?;
+            Ok(())
+        })
+    }
+
+    /// russh cannot send the RFC 4335 `break` request, so this always
+    /// fails with [`TransportError::Unsupported`].
+    fn send_break(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            Err(TransportError::Unsupported {
+                feature: "break".to_string(),
+                protocol: Protocol::Ssh.to_string(),
+            }
+            .into())
+        })
+    }
+
+    fn signal(&mut self, signal: Sig) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            Channel::signal(self, signal)
+                .await
+                .map_err(ChannelError::Ssh)?;
+            Ok(())
+        })
+    }
+}
+
 /// SSH client handler for russh.
 struct SshHandler {
     host: String,
     port: u16,
+    /// Whether this hop is a jump host rather than the target. Disconnect
+    /// reasons from jump hosts are wrapped so callers can tell which hop
+    /// failed.
+    jump: bool,
     host_key_verification: HostKeyVerification,
     known_hosts_path: Option<PathBuf>,
     /// Stores a detailed host-key error so connect() can surface it
     /// instead of the generic russh::Error::UnknownKey.
     host_key_error: Arc<Mutex<Option<TransportError>>>,
+    /// Server identification, banner and algorithms from the latest key
+    /// exchange, read by connect().
+    info: Arc<Mutex<ConnectionInfo>>,
     /// Sender for disconnect notifications.
     disconnect_tx: Arc<watch::Sender<Option<DisconnectReason>>>,
+    /// Where `forwarded-tcpip` channels from the server are relayed.
+    remote_forwards: Arc<RemoteForwards>,
 }
 
 impl SshHandler {
+    /// Publish a disconnect reason (no-op if one was already published).
+    fn signal_disconnect(&self, reason: DisconnectReason) {
+        let reason = if self.jump {
+            DisconnectReason::JumpHost {
+                host: self.host.clone(),
+                port: self.port,
+                reason: Box::new(reason),
+            }
+        } else {
+            reason
+        };
+        self.disconnect_tx.send_if_modified(|value| {
+            if value.is_none() {
+                *value = Some(reason);
+                true
+            } else {
+                false
+            }
+        });
+    }
+
     /// Check the host key against known_hosts.
     ///
     /// Returns `Ok(true)` if matched, `Ok(false)` if host not found,
@@ -334,6 +996,16 @@ impl SshHandler {
         }
     }
 
+    /// Store a [`TransportError::HostKeyRejected`] for connect() to surface.
+    fn reject_host_key(&self, fingerprint: String, reason: String) {
+        *self.host_key_error.lock().unwrap() = Some(TransportError::HostKeyRejected {
+            host: self.host.clone(),
+            port: self.port,
+            fingerprint,
+            reason,
+        });
+    }
+
     /// Save a new host key to known_hosts.
     fn learn_host_key(&self, pubkey: &PublicKey) -> std::result::Result<(), TransportError> {
         let result = if let Some(ref path) = self.known_hosts_path {
@@ -349,20 +1021,41 @@ impl SshHandler {
 impl Drop for SshHandler {
     fn drop(&mut self) {
         // Safety net: if disconnected() was never called, signal transport loss.
-        self.disconnect_tx.send_if_modified(|value| {
-            if value.is_none() {
-                *value = Some(DisconnectReason::TransportError("connection lost".into()));
-                true
-            } else {
-                false
-            }
-        });
+        self.signal_disconnect(DisconnectReason::TransportError("connection lost".into()));
     }
 }
 
 impl client::Handler for SshHandler {
     type Error = russh::Error;
 
+    async fn kex_done(
+        &mut self,
+        _shared_secret: Option<&[u8]>,
+        names: &russh::Names,
+        session: &mut client::Session,
+    ) -> std::result::Result<(), Self::Error> {
+        let mut info = self.info.lock().unwrap();
+        info.algorithms = NegotiatedAlgorithms::from(names);
+        info.server_id = Some(String::from_utf8_lossy(session.remote_sshid()).into_owned());
+        Ok(())
+    }
+
+    async fn auth_banner(
+        &mut self,
+        banner: &str,
+        _session: &mut client::Session,
+    ) -> std::result::Result<(), Self::Error> {
+        debug!("authentication banner from {}:{}", self.host, self.port);
+        // Servers may send several banners; keep them all.
+        self.info
+            .lock()
+            .unwrap()
+            .banner
+            .get_or_insert_default()
+            .push_str(banner);
+        Ok(())
+    }
+
     async fn disconnected(
         &mut self,
         reason: client::DisconnectReason<Self::Error>,
@@ -373,28 +1066,13 @@ impl client::Handler for SshHandler {
                     "server disconnect: code={:?}, message={:?}",
                     info.reason_code, info.message
                 );
-                self.disconnect_tx.send_if_modified(|value| {
-                    if value.is_none() {
-                        *value = Some(DisconnectReason::ServerDisconnect {
-                            message: info.message,
-                        });
-                        true
-                    } else {
-                        false
-                    }
+                self.signal_disconnect(DisconnectReason::ServerDisconnect {
+                    message: info.message,
                 });
             }
             client::DisconnectReason::Error(ref e) => {
                 debug!("transport error disconnect: {}", e);
-                let msg = e.to_string();
-                self.disconnect_tx.send_if_modified(|value| {
-                    if value.is_none() {
-                        *value = Some(DisconnectReason::TransportError(msg));
-                        true
-                    } else {
-                        false
-                    }
-                });
+                self.signal_disconnect(DisconnectReason::TransportError(e.to_string()));
             }
         }
         Ok(())
@@ -404,7 +1082,7 @@ impl client::Handler for SshHandler {
         &mut self,
         server_public_key: &PublicKey,
     ) -> std::result::Result<bool, Self::Error> {
-        match self.host_key_verification {
+        match &self.host_key_verification {
             HostKeyVerification::Disabled => Ok(true),
 
             HostKeyVerification::AcceptNew => {
@@ -444,6 +1122,50 @@ impl client::Handler for SshHandler {
                     }
                 }
             }
+
+            HostKeyVerification::Pinned(fingerprints) => {
+                let fingerprint = sha256_fingerprint(server_public_key);
+                if fingerprints
+                    .iter()
+                    .any(|pinned| fingerprint_matches(pinned, &fingerprint))
+                {
+                    return Ok(true);
+                }
+                self.reject_host_key(fingerprint, "fingerprint is not pinned".into());
+                Ok(false)
+            }
+
+            HostKeyVerification::Custom(verifier) => {
+                match verifier.verify(&self.host, self.port, server_public_key) {
+                    Ok(()) => Ok(true),
+                    Err(reason) => {
+                        self.reject_host_key(sha256_fingerprint(server_public_key), reason);
+                        Ok(false)
+                    }
+                }
+            }
         }
     }
+
+    async fn server_channel_open_forwarded_tcpip(
+        &mut self,
+        channel: Channel<Msg>,
+        connected_address: &str,
+        connected_port: u32,
+        originator_address: &str,
+        originator_port: u32,
+        _session: &mut client::Session,
+    ) -> std::result::Result<(), Self::Error> {
+        debug!(
+            "forwarded connection on {}:{} from {}:{}",
+            connected_address, connected_port, originator_address, originator_port
+        );
+        self.remote_forwards.dispatch(
+            channel,
+            connected_address,
+            connected_port,
+            self.disconnect_tx.subscribe(),
+        );
+        Ok(())
+    }
 }
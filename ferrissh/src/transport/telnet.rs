@@ -0,0 +1,740 @@
+//! Telnet transport for console servers and legacy devices.
+//!
+//! Negotiates the options an interactive CLI needs (window size, terminal
+//! type, remote echo and suppress-go-ahead) and refuses the rest. Telnet
+//! has no authentication of its own, so the transport logs in by answering
+//! the device's username and password prompts.
+//!
+//! Telnet is unencrypted; prefer SSH wherever the device offers it.
+
+use std::sync::{Arc, LazyLock, Mutex};
+
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use log::{debug, trace};
+use regex::bytes::Regex;
+use russh::Sig;
+use secrecy::ExposeSecret;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+
+use super::config::{AuthMethod, Protocol, SshConfig, TerminalOptions};
+use super::info::ConnectionInfo;
+use super::proxy;
+use super::tcp;
+use super::traits::{ShellChannel, ShellOutput, Transport};
+use crate::channel::{PatternBuffer, PromptMatcher, regex_matchers};
+use crate::error::{ChannelError, DisconnectReason, Result, TransportError};
+use crate::platform::PlatformDefinition;
+
+/// Interpret As Command: starts every Telnet command sequence.
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+/// Subnegotiation begin / end.
+const SB: u8 = 250;
+const SE: u8 = 240;
+/// Interrupt Process.
+const IP: u8 = 244;
+/// Break.
+const BRK: u8 = 243;
+
+/// Option codes.
+const ECHO: u8 = 1;
+const SGA: u8 = 3;
+const TTYPE: u8 = 24;
+const NAWS: u8 = 31;
+
+/// `TERMINAL-TYPE` subnegotiation commands (RFC 1091).
+const TTYPE_IS: u8 = 0;
+const TTYPE_SEND: u8 = 1;
+
+/// Longest subnegotiation kept for parsing; none we handle come close.
+const MAX_SUBNEGOTIATION: usize = 64;
+
+/// Output chunks buffered between the socket reader and the shell.
+const OUTPUT_CAPACITY: usize = 64;
+
+/// Login prompts answered during the in-band login.
+static USERNAME_PROMPT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i-u)(user ?name|login)\s*:\s*$").unwrap());
+static PASSWORD_PROMPT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i-u)pass(word|code)\s*:\s*$").unwrap());
+
+/// Write half of the socket, shared by the shell and the option negotiator.
+type Writer = Arc<tokio::sync::Mutex<OwnedWriteHalf>>;
+
+/// Option state, shared by the reader (negotiation) and the shell (resize).
+type Options = Arc<Mutex<TelnetOptions>>;
+
+/// Telnet transport.
+///
+/// A Telnet connection carries exactly one shell, so
+/// [`Session::open_channel()`](crate::Session::open_channel) succeeds once
+/// per session.
+pub struct TelnetTransport {
+    /// Configuration used for this connection.
+    config: SshConfig,
+
+    /// Addresses and login banner.
+    info: ConnectionInfo,
+
+    /// Write half of the socket.
+    writer: Writer,
+
+    /// Reads the socket, answers option negotiation and forwards output.
+    reader: JoinHandle<()>,
+
+    /// The connection's shell, until it is opened.
+    shell: Mutex<Option<TelnetShell>>,
+
+    /// Sender for disconnect notifications (shared with the reader).
+    disconnect_tx: Arc<watch::Sender<Option<DisconnectReason>>>,
+
+    /// Receiver for disconnect notifications.
+    disconnect_rx: watch::Receiver<Option<DisconnectReason>>,
+}
+
+impl TelnetTransport {
+    /// Connect to the device and log in.
+    ///
+    /// Answers username and password prompts with `config.username` and the
+    /// first password in `config.auth_methods`, until one of the platform's
+    /// prompts appears. Seeing a login prompt again means the device
+    /// rejected the credentials. Devices that show a prompt straight away
+    /// are used without logging in.
+    pub async fn connect(config: SshConfig, platform: &PlatformDefinition) -> Result<Self> {
+        debug!("connecting to {}:{} (telnet)", config.host, config.port);
+
+        let connect = async {
+            match &config.proxy {
+                Some(proxy_config) => {
+                    proxy::connect(proxy_config, &config.host, config.port, &config).await
+                }
+                None => tcp::connect(&config.host, config.port, &config).await,
+            }
+        };
+        let stream = tokio::time::timeout(config.connect_timeout, connect)
+            .await
+            .map_err(|_| TransportError::Timeout(config.connect_timeout))??;
+        let _ = stream.set_nodelay(true);
+        let mut info = ConnectionInfo::new(Protocol::Telnet);
+        info.set_addresses(&stream);
+
+        let (read_half, write_half) = stream.into_split();
+        let writer: Writer = Arc::new(tokio::sync::Mutex::new(write_half));
+
+        let options = Arc::new(Mutex::new(TelnetOptions::new(
+            &config.terminal_type,
+            config.terminal_width,
+            config.terminal_height,
+        )));
+        let offer = options.lock().unwrap().offer();
+        writer
+            .lock()
+            .await
+            .write_all(&offer)
+            .await
+            .map_err(TransportError::Io)?;
+
+        let (disconnect_tx, disconnect_rx) = watch::channel(None);
+        let disconnect_tx = Arc::new(disconnect_tx);
+        let (output_tx, output_rx) = mpsc::channel(OUTPUT_CAPACITY);
+        let reader = tokio::spawn(read_loop(
+            read_half,
+            writer.clone(),
+            options.clone(),
+            output_tx,
+            disconnect_tx.clone(),
+        ));
+
+        // Built before logging in so a failed login still stops the reader.
+        let mut transport = Self {
+            config,
+            info,
+            writer: writer.clone(),
+            reader,
+            shell: Mutex::new(None),
+            disconnect_tx,
+            disconnect_rx,
+        };
+
+        let mut shell = TelnetShell {
+            output: output_rx,
+            writer,
+            options,
+            pending: None,
+        };
+        let patterns: Vec<Regex> = platform
+            .privilege_levels
+            .values()
+            .map(|level| level.pattern.clone())
+            .collect();
+        let prompts = regex_matchers(&patterns);
+        let timeout = transport.config.timeout;
+        transport.info.banner =
+            tokio::time::timeout(timeout, login(&mut shell, &transport.config, &prompts))
+                .await
+                .map_err(|_| TransportError::Timeout(timeout))??;
+        debug!(
+            "logged in to {}:{} (telnet)",
+            transport.config.host, transport.config.port
+        );
+
+        *transport.shell.lock().unwrap() = Some(shell);
+        Ok(transport)
+    }
+}
+
+impl Transport for TelnetTransport {
+    fn protocol(&self) -> Protocol {
+        Protocol::Telnet
+    }
+
+    fn connection_info(&self) -> &ConnectionInfo {
+        &self.info
+    }
+
+    /// The terminal type was negotiated while connecting, and modes and
+    /// environment variables have no Telnet equivalent; only a different
+    /// size is applied.
+    fn open_shell<'a>(
+        &'a self,
+        terminal: &'a TerminalOptions,
+    ) -> BoxFuture<'a, Result<Box<dyn ShellChannel>>> {
+        let shell = self.shell.lock().unwrap().take();
+        Box::pin(async move {
+            let Some(mut shell) = shell else {
+                return Err(ChannelError::PtyOpenFailed.into());
+            };
+            if (terminal.width, terminal.height)
+                != (self.config.terminal_width, self.config.terminal_height)
+            {
+                shell
+                    .set_window_size(terminal.width, terminal.height)
+                    .await?;
+            }
+            Ok(Box::new(shell) as Box<dyn ShellChannel>)
+        })
+    }
+
+    fn is_alive(&self) -> bool {
+        !self.reader.is_finished() && self.disconnect_rx.borrow().is_none()
+    }
+
+    fn disconnect_tx(&self) -> &Arc<watch::Sender<Option<DisconnectReason>>> {
+        &self.disconnect_tx
+    }
+
+    fn disconnect_rx(&self) -> &watch::Receiver<Option<DisconnectReason>> {
+        &self.disconnect_rx
+    }
+
+    fn disconnect(&self) -> BoxFuture<'_, std::result::Result<(), TransportError>> {
+        Box::pin(async move {
+            debug!(
+                "disconnecting from {}:{} (telnet)",
+                self.config.host, self.config.port
+            );
+            self.reader.abort();
+            self.writer
+                .lock()
+                .await
+                .shutdown()
+                .await
+                .map_err(TransportError::Io)
+        })
+    }
+}
+
+impl Drop for TelnetTransport {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// Answer login prompts until a shell prompt appears, returning the banner
+/// shown before the first prompt.
+///
+/// The output that contained the shell prompt is kept for the channel, which
+/// waits for that prompt when it opens.
+async fn login(
+    shell: &mut TelnetShell,
+    config: &SshConfig,
+    prompts: &[Arc<dyn PromptMatcher>],
+) -> Result<Option<String>> {
+    let password = config.auth_methods.iter().find_map(|method| match method {
+        AuthMethod::Password(password) => Some(password),
+        _ => None,
+    });
+    let rejected = || TransportError::AuthenticationFailed {
+        user: config.username.clone(),
+        methods: vec!["password".to_string()],
+    };
+
+    let mut buffer = PatternBuffer::new(1000);
+    let mut sent_username = false;
+    let mut sent_password = false;
+    let mut banner = None;
+    loop {
+        match shell.read().await {
+            Some(ShellOutput::Data(data)) => buffer.extend(&data),
+            Some(_) => {}
+            None => return Err(TransportError::Disconnected.into()),
+        }
+
+        let at_password = buffer.search_tail(&*PASSWORD_PROMPT).is_some();
+        let at_username = !at_password && buffer.search_tail(&*USERNAME_PROMPT).is_some();
+        if !at_password && !at_username && buffer.search_tail_any(prompts).is_none() {
+            continue;
+        }
+        if !sent_username && !sent_password {
+            banner = text_before_prompt(&buffer);
+        }
+
+        if at_password {
+            let Some(password) = password.filter(|_| !sent_password) else {
+                return Err(rejected().into());
+            };
+            trace!("telnet: answering password prompt");
+            shell.send_line(password.expose_secret()).await?;
+            sent_password = true;
+            buffer.clear();
+        } else if at_username {
+            if sent_username {
+                return Err(rejected().into());
+            }
+            trace!("telnet: answering username prompt");
+            shell.send_line(&config.username).await?;
+            sent_username = true;
+            buffer.clear();
+        } else {
+            shell.pending = Some(buffer.take().freeze());
+            return Ok(banner);
+        }
+    }
+}
+
+/// The lines before the prompt on the buffer's last line, if any.
+fn text_before_prompt(buffer: &PatternBuffer) -> Option<String> {
+    let text = buffer.as_str_lossy();
+    let text = text[..text.rfind('\n').unwrap_or(0)].replace('\r', "");
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Read the socket until it closes, answering option negotiation and
+/// forwarding everything else to the shell.
+async fn read_loop(
+    mut socket: OwnedReadHalf,
+    writer: Writer,
+    options: Options,
+    output: mpsc::Sender<ShellOutput>,
+    disconnect_tx: Arc<watch::Sender<Option<DisconnectReason>>>,
+) {
+    let mut buf = vec![0u8; 4096];
+    let mut data = Vec::new();
+    let mut reply = Vec::new();
+
+    let reason = loop {
+        let n = match socket.read(&mut buf).await {
+            Ok(0) => {
+                let _ = output.send(ShellOutput::Eof).await;
+                break DisconnectReason::ServerDisconnect {
+                    message: "connection closed by peer".to_string(),
+                };
+            }
+            Ok(n) => n,
+            Err(e) => break DisconnectReason::TransportError(e.to_string()),
+        };
+
+        options
+            .lock()
+            .unwrap()
+            .feed(&buf[..n], &mut data, &mut reply);
+        if !reply.is_empty() {
+            if let Err(e) = writer.lock().await.write_all(&reply).await {
+                break DisconnectReason::TransportError(e.to_string());
+            }
+            reply.clear();
+        }
+        if !data.is_empty() {
+            // Nobody listening once the shell is dropped; keep negotiating.
+            let _ = output
+                .send(ShellOutput::Data(Bytes::copy_from_slice(&data)))
+                .await;
+            data.clear();
+        }
+    };
+
+    debug!("telnet connection ended: {:?}", reason);
+    disconnect_tx.send_if_modified(|value| {
+        if value.is_none() {
+            *value = Some(reason);
+            true
+        } else {
+            false
+        }
+    });
+}
+
+/// The single shell of a Telnet connection.
+struct TelnetShell {
+    /// Output forwarded by the reader task.
+    output: mpsc::Receiver<ShellOutput>,
+    /// Write half of the socket.
+    writer: Writer,
+    /// Negotiated options, for window size updates.
+    options: Options,
+    /// Output consumed while logging in, replayed first.
+    pending: Option<Bytes>,
+}
+
+impl TelnetShell {
+    async fn send_line(&mut self, line: &str) -> Result<()> {
+        let mut data = Vec::with_capacity(line.len() + 1);
+        data.extend_from_slice(line.as_bytes());
+        data.push(b'\n');
+        self.send(&data).await
+    }
+
+    async fn send(&mut self, data: &[u8]) -> Result<()> {
+        self.send_raw(&encode(data)).await
+    }
+
+    /// Write bytes that are already on-the-wire Telnet.
+    async fn send_raw(&self, data: &[u8]) -> Result<()> {
+        self.writer.lock().await.write_all(data).await.map_err(|e| {
+            debug!("telnet write failed: {}", e);
+            ChannelError::Disconnected.into()
+        })
+    }
+
+    /// Record the new size and report it if the server accepted NAWS.
+    async fn set_window_size(&mut self, width: u32, height: u32) -> Result<()> {
+        let mut update = Vec::new();
+        self.options
+            .lock()
+            .unwrap()
+            .set_window_size(width, height, &mut update);
+        if update.is_empty() {
+            return Ok(());
+        }
+        self.send_raw(&update).await
+    }
+
+    async fn read(&mut self) -> Option<ShellOutput> {
+        if let Some(pending) = self.pending.take() {
+            return Some(ShellOutput::Data(pending));
+        }
+        self.output.recv().await
+    }
+}
+
+impl ShellChannel for TelnetShell {
+    fn write<'a>(&'a mut self, data: &'a [u8]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.send(data))
+    }
+
+    fn read(&mut self) -> BoxFuture<'_, Option<ShellOutput>> {
+        Box::pin(TelnetShell::read(self))
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> BoxFuture<'_, Result<()>> {
+        Box::pin(self.set_window_size(width, height))
+    }
+
+    /// `IAC BRK`.
+    fn send_break(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move { self.send_raw(&[IAC, BRK]).await })
+    }
+
+    /// Only `SIGINT` has a Telnet equivalent, `IAC IP` (Interrupt Process).
+    fn signal(&mut self, signal: Sig) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            match signal {
+                Sig::INT => self.send_raw(&[IAC, IP]).await,
+                other => Err(TransportError::Unsupported {
+                    feature: format!("signal {:?}", other),
+                    protocol: Protocol::Telnet.to_string(),
+                }
+                .into()),
+            }
+        })
+    }
+}
+
+/// Escape data for the wire: double `IAC` bytes and send line ends as
+/// CR LF, as the network virtual terminal expects.
+fn encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + 2);
+    let mut previous = 0;
+    for &byte in data {
+        match byte {
+            IAC => out.extend_from_slice(&[IAC, IAC]),
+            b'\n' if previous != b'\r' => out.extend_from_slice(b"\r\n"),
+            _ => out.push(byte),
+        }
+        previous = byte;
+    }
+    out
+}
+
+/// Where the option parser is within a command sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Data,
+    /// After a carriage return, where a NUL padding byte may follow.
+    Cr,
+    Iac,
+    /// After `IAC WILL/WONT/DO/DONT`, waiting for the option code.
+    Negotiate(u8),
+    Subnegotiation,
+    SubnegotiationIac,
+}
+
+/// Telnet option negotiation (RFC 854, 855).
+///
+/// Accepts the server's ECHO and SGA, offers SGA and NAWS, agrees to send
+/// the terminal type, and refuses every other option. Only state changes
+/// are answered, so negotiation cannot loop.
+struct TelnetOptions {
+    state: State,
+    /// Options enabled on our side (`WILL`).
+    local: [bool; 256],
+    /// Options enabled on the server's side (`DO`).
+    remote: [bool; 256],
+    /// Terminal type reported when the server asks.
+    term: String,
+    width: u16,
+    height: u16,
+    /// Parameters of the subnegotiation being read.
+    subnegotiation: Vec<u8>,
+}
+
+impl TelnetOptions {
+    fn new(term: &str, width: u32, height: u32) -> Self {
+        Self {
+            state: State::Data,
+            local: [false; 256],
+            remote: [false; 256],
+            term: term.to_string(),
+            width: width.try_into().unwrap_or(u16::MAX),
+            height: height.try_into().unwrap_or(u16::MAX),
+            subnegotiation: Vec::new(),
+        }
+    }
+
+    /// Change the window size, reporting it if NAWS is enabled.
+    fn set_window_size(&mut self, width: u32, height: u32, reply: &mut Vec<u8>) {
+        self.width = width.try_into().unwrap_or(u16::MAX);
+        self.height = height.try_into().unwrap_or(u16::MAX);
+        if self.local[NAWS as usize] {
+            self.window_size(reply);
+        }
+    }
+
+    /// What we ask for up front: to send our window size, and for the
+    /// server to suppress go-ahead.
+    fn offer(&mut self) -> Vec<u8> {
+        self.local[NAWS as usize] = true;
+        self.remote[SGA as usize] = true;
+        vec![IAC, WILL, NAWS, IAC, DO, SGA]
+    }
+
+    /// Split `input` into terminal `data` and the negotiation `reply` owed
+    /// to the server. Sequences may span calls.
+    fn feed(&mut self, input: &[u8], data: &mut Vec<u8>, reply: &mut Vec<u8>) {
+        for &byte in input {
+            self.state = match (self.state, byte) {
+                (State::Data | State::Cr, IAC) => State::Iac,
+                (State::Cr, 0) => State::Data,
+                (State::Data | State::Cr, b'\r') => {
+                    data.push(byte);
+                    State::Cr
+                }
+                (State::Data | State::Cr, _) => {
+                    data.push(byte);
+                    State::Data
+                }
+                (State::Iac, IAC) => {
+                    data.push(IAC);
+                    State::Data
+                }
+                (State::Iac, WILL | WONT | DO | DONT) => State::Negotiate(byte),
+                (State::Iac, SB) => {
+                    self.subnegotiation.clear();
+                    State::Subnegotiation
+                }
+                // NOP, GA, DM and friends carry no data for a CLI.
+                (State::Iac, _) => State::Data,
+                (State::Negotiate(verb), option) => {
+                    self.negotiate(verb, option, reply);
+                    State::Data
+                }
+                (State::Subnegotiation, IAC) => State::SubnegotiationIac,
+                (State::Subnegotiation, _) | (State::SubnegotiationIac, IAC) => {
+                    if self.subnegotiation.len() < MAX_SUBNEGOTIATION {
+                        self.subnegotiation.push(byte);
+                    }
+                    State::Subnegotiation
+                }
+                (State::SubnegotiationIac, SE) => {
+                    self.subnegotiate(reply);
+                    State::Data
+                }
+                (State::SubnegotiationIac, _) => State::Subnegotiation,
+            };
+        }
+    }
+
+    fn negotiate(&mut self, verb: u8, option: u8, reply: &mut Vec<u8>) {
+        let index = option as usize;
+        match verb {
+            WILL if matches!(option, ECHO | SGA) && !self.remote[index] => {
+                self.remote[index] = true;
+                reply.extend_from_slice(&[IAC, DO, option]);
+            }
+            // Already agreed; acknowledging again would loop.
+            WILL if matches!(option, ECHO | SGA) => {}
+            WILL => reply.extend_from_slice(&[IAC, DONT, option]),
+            WONT if self.remote[index] => {
+                self.remote[index] = false;
+                reply.extend_from_slice(&[IAC, DONT, option]);
+            }
+            DO if matches!(option, NAWS | SGA | TTYPE) => {
+                if !self.local[index] {
+                    self.local[index] = true;
+                    reply.extend_from_slice(&[IAC, WILL, option]);
+                }
+                if option == NAWS {
+                    self.window_size(reply);
+                }
+            }
+            DO => reply.extend_from_slice(&[IAC, WONT, option]),
+            DONT if self.local[index] => {
+                self.local[index] = false;
+                reply.extend_from_slice(&[IAC, WONT, option]);
+            }
+            _ => {}
+        }
+    }
+
+    /// Answer a completed subnegotiation. Only `TERMINAL-TYPE SEND` needs
+    /// one: `IAC SB TTYPE IS <type> IAC SE`.
+    fn subnegotiate(&mut self, reply: &mut Vec<u8>) {
+        if self.subnegotiation == [TTYPE, TTYPE_SEND] && self.local[TTYPE as usize] {
+            reply.extend_from_slice(&[IAC, SB, TTYPE, TTYPE_IS]);
+            reply.extend_from_slice(self.term.as_bytes());
+            reply.extend_from_slice(&[IAC, SE]);
+        }
+    }
+
+    /// `IAC SB NAWS <width> <height> IAC SE`, 16-bit big-endian each.
+    fn window_size(&self, reply: &mut Vec<u8>) {
+        reply.extend_from_slice(&[IAC, SB, NAWS]);
+        for byte in self
+            .width
+            .to_be_bytes()
+            .into_iter()
+            .chain(self.height.to_be_bytes())
+        {
+            reply.push(byte);
+            if byte == IAC {
+                reply.push(IAC);
+            }
+        }
+        reply.extend_from_slice(&[IAC, SE]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(options: &mut TelnetOptions, input: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let (mut data, mut reply) = (Vec::new(), Vec::new());
+        options.feed(input, &mut data, &mut reply);
+        (data, reply)
+    }
+
+    #[test]
+    fn test_accepts_echo_and_sga_refuses_others() {
+        let mut options = TelnetOptions::new("xterm", 80, 24);
+        let (data, reply) = feed(
+            &mut options,
+            &[IAC, WILL, ECHO, IAC, WILL, SGA, IAC, WILL, 42, IAC, DO, 39],
+        );
+        assert!(data.is_empty());
+        assert_eq!(
+            reply,
+            [IAC, DO, ECHO, IAC, DO, SGA, IAC, DONT, 42, IAC, WONT, 39]
+        );
+
+        // Repeating an accepted option changes nothing, so needs no answer.
+        let (_, reply) = feed(&mut options, &[IAC, WILL, ECHO]);
+        assert!(reply.is_empty());
+        let (_, reply) = feed(&mut options, &[IAC, WONT, ECHO]);
+        assert_eq!(reply, [IAC, DONT, ECHO]);
+    }
+
+    #[test]
+    fn test_naws_reports_window_size() {
+        let mut options = TelnetOptions::new("xterm", 511, 24);
+        let (_, reply) = feed(&mut options, &[IAC, DO, NAWS]);
+        assert_eq!(
+            reply,
+            [IAC, WILL, NAWS, IAC, SB, NAWS, 1, 255, 255, 0, 24, IAC, SE]
+        );
+
+        // Already offered: only the size is (re)sent.
+        let mut options = TelnetOptions::new("xterm", 80, 24);
+        options.offer();
+        let (_, reply) = feed(&mut options, &[IAC, DO, NAWS]);
+        assert_eq!(reply, [IAC, SB, NAWS, 0, 80, 0, 24, IAC, SE]);
+    }
+
+    #[test]
+    fn test_terminal_type_and_resize() {
+        let mut options = TelnetOptions::new("vt100", 80, 24);
+        let mut update = Vec::new();
+        options.set_window_size(132, 40, &mut update);
+        assert!(update.is_empty(), "NAWS not agreed yet");
+
+        let (_, reply) = feed(&mut options, &[IAC, DO, TTYPE]);
+        assert_eq!(reply, [IAC, WILL, TTYPE]);
+        let (_, reply) = feed(&mut options, &[IAC, SB, TTYPE, TTYPE_SEND, IAC, SE]);
+        assert_eq!(reply, b"\xff\xfa\x18\x00vt100\xff\xf0");
+
+        let (_, reply) = feed(&mut options, &[IAC, DO, NAWS]);
+        assert_eq!(
+            reply,
+            [IAC, WILL, NAWS, IAC, SB, NAWS, 0, 132, 0, 40, IAC, SE]
+        );
+        options.set_window_size(200, 50, &mut update);
+        assert_eq!(update, [IAC, SB, NAWS, 0, 200, 0, 50, IAC, SE]);
+    }
+
+    #[test]
+    fn test_data_unescaping_across_chunks() {
+        let mut options = TelnetOptions::new("xterm", 80, 24);
+        let (data, _) = feed(&mut options, b"a\r\0b\xff");
+        assert_eq!(data, b"a\rb");
+        let (data, reply) = feed(&mut options, &[IAC, b'c', IAC, SB, 24, 1, IAC]);
+        assert_eq!(data, [IAC, b'c']);
+        assert!(reply.is_empty());
+        let (data, _) = feed(&mut options, &[SE, b'd', IAC, 241, b'\r', b'\n']);
+        assert_eq!(data, b"d\r\n");
+    }
+
+    #[test]
+    fn test_encode_escapes_iac_and_line_ends() {
+        assert_eq!(encode(b"show run\n"), b"show run\r\n");
+        assert_eq!(encode(b"a\r\nb"), b"a\r\nb");
+        assert_eq!(encode(&[b'x', IAC]), [b'x', IAC, IAC]);
+    }
+}
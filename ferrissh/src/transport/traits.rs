@@ -0,0 +1,100 @@
+//! Protocol-independent transport abstraction.
+//!
+//! [`Session`](crate::Session) reaches the device through [`Transport`] and
+//! reads and writes shells through [`ShellChannel`], so prompt matching,
+//! privilege navigation and config sessions work the same whichever
+//! protocol carries the bytes.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use russh::Sig;
+use tokio::sync::watch;
+
+use super::config::{Protocol, SshConfig, TerminalOptions};
+use super::info::ConnectionInfo;
+use super::ssh::SshTransport;
+use super::telnet::TelnetTransport;
+use crate::error::{DisconnectReason, Result, TransportError};
+use crate::platform::PlatformDefinition;
+
+/// An established, authenticated connection to a device.
+///
+/// Implemented by [`SshTransport`] and [`TelnetTransport`].
+pub trait Transport: Send + Sync {
+    /// The protocol this transport speaks.
+    fn protocol(&self) -> Protocol;
+
+    /// What was learned about the server while connecting.
+    fn connection_info(&self) -> &ConnectionInfo;
+
+    /// Open an interactive shell on the connection.
+    ///
+    /// Over SSH this is a new PTY channel; a Telnet connection carries a
+    /// single shell, so it can only be opened once.
+    fn open_shell<'a>(
+        &'a self,
+        terminal: &'a TerminalOptions,
+    ) -> BoxFuture<'a, Result<Box<dyn ShellChannel>>>;
+
+    /// Whether the connection is still up.
+    fn is_alive(&self) -> bool;
+
+    /// Sender for disconnect notifications.
+    fn disconnect_tx(&self) -> &Arc<watch::Sender<Option<DisconnectReason>>>;
+
+    /// Receiver for disconnect notifications.
+    fn disconnect_rx(&self) -> &watch::Receiver<Option<DisconnectReason>>;
+
+    /// Tear the connection down.
+    fn disconnect(&self) -> BoxFuture<'_, std::result::Result<(), TransportError>>;
+
+    /// The SSH transport, for SSH-only features (exec, file transfer,
+    /// port forwarding). `None` for other protocols.
+    fn as_ssh(&self) -> Option<&SshTransport> {
+        None
+    }
+}
+
+/// Output read from a shell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShellOutput {
+    /// Terminal output.
+    Data(Bytes),
+    /// Output the protocol delivers separately (SSH extended data).
+    Stderr(Bytes),
+    /// The device ended the shell.
+    Eof,
+}
+
+/// The byte stream of one interactive shell.
+pub trait ShellChannel: Send + Sync {
+    /// Send raw bytes to the shell.
+    fn write<'a>(&'a mut self, data: &'a [u8]) -> BoxFuture<'a, Result<()>>;
+
+    /// Wait for the next output. `None` once the connection is gone.
+    fn read(&mut self) -> BoxFuture<'_, Option<ShellOutput>>;
+
+    /// Tell the device the terminal is now `width` x `height`.
+    fn resize(&mut self, width: u32, height: u32) -> BoxFuture<'_, Result<()>>;
+
+    /// Send a break, for devices (and console servers) that act on one.
+    fn send_break(&mut self) -> BoxFuture<'_, Result<()>>;
+
+    /// Deliver `signal` to the command running in the shell.
+    fn signal(&mut self, signal: Sig) -> BoxFuture<'_, Result<()>>;
+}
+
+/// Connect and authenticate with the protocol `config.protocol` selects.
+///
+/// The platform's prompts tell an in-band (Telnet) login when it is done.
+pub(crate) async fn connect(
+    config: &SshConfig,
+    platform: &PlatformDefinition,
+) -> Result<Box<dyn Transport>> {
+    Ok(match config.protocol {
+        Protocol::Ssh => Box::new(SshTransport::connect(config.clone()).await?),
+        Protocol::Telnet => Box::new(TelnetTransport::connect(config.clone(), platform).await?),
+    })
+}
@@ -0,0 +1,102 @@
+//! Integration tests for algorithm preferences and negotiated algorithms.
+//!
+//! Uses the mock SSH server (see `common/mod.rs`), which runs with the
+//! russh default algorithm lists.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test algorithms_integration
+//! ```
+
+mod common;
+
+use std::time::Duration;
+
+use ferrissh::error::TransportError;
+use ferrissh::transport::AlgorithmPreferences;
+use ferrissh::{Error, Platform, SessionBuilder};
+
+fn session_builder(port: u16, algorithms: AlgorithmPreferences) -> SessionBuilder {
+    SessionBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+        .algorithms(algorithms)
+}
+
+/// The modern preset negotiates only modern algorithms.
+#[tokio::test]
+async fn test_modern_preset_negotiates_modern_algorithms() {
+    let port = common::mock_server_port().await;
+    let modern = AlgorithmPreferences::modern();
+
+    let session = session_builder(port, modern.clone())
+        .connect()
+        .await
+        .expect("modern preset should connect");
+
+    let negotiated = session.negotiated_algorithms();
+    assert!(modern.kex.contains(&negotiated.kex), "{negotiated:?}");
+    assert!(
+        modern.ciphers.contains(&negotiated.cipher),
+        "{negotiated:?}"
+    );
+    assert_eq!(negotiated.host_key, "ssh-ed25519");
+    session.close().await.ok();
+}
+
+/// Per-list overrides are honoured and reported back.
+#[tokio::test]
+async fn test_overrides_are_negotiated() {
+    let port = common::mock_server_port().await;
+
+    let session = session_builder(
+        port,
+        AlgorithmPreferences::compatible()
+            .kex(["diffie-hellman-group14-sha256"])
+            .ciphers(["aes128-ctr"])
+            .macs(["hmac-sha2-256"]),
+    )
+    .connect()
+    .await
+    .expect("overridden algorithms should connect");
+
+    let negotiated = session.negotiated_algorithms();
+    assert_eq!(negotiated.kex, "diffie-hellman-group14-sha256");
+    assert_eq!(negotiated.cipher, "aes128-ctr");
+    assert_eq!(negotiated.mac_client_to_server, "hmac-sha2-256");
+    assert_eq!(negotiated.mac_server_to_client, "hmac-sha2-256");
+
+    let mut channel = session.open_channel().await.unwrap();
+    let response = channel.send_command("echo ctr_ok").await.unwrap();
+    assert!(response.result.contains("ctr_ok"));
+    channel.close().await.ok();
+    session.close().await.ok();
+}
+
+/// Restricting to an algorithm the server has disabled fails the handshake.
+#[tokio::test]
+async fn test_no_common_algorithm() {
+    let port = common::mock_server_port().await;
+
+    let Err(err) = session_builder(
+        port,
+        AlgorithmPreferences::legacy().kex(["diffie-hellman-group1-sha1"]),
+    )
+    .connect()
+    .await
+    else {
+        panic!("group1-sha1 is not offered by the server");
+    };
+    assert!(
+        matches!(
+            err,
+            Error::Transport(TransportError::Ssh(russh::Error::NoCommonAlgo { .. }))
+        ),
+        "got: {err}"
+    );
+}
@@ -0,0 +1,456 @@
+//! Integration tests for SSH authentication methods.
+//!
+//! Uses the in-process mock SSH server (see `common/mod.rs`). Agent tests
+//! additionally run a local SSH agent stand-in over a Unix socket.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test auth_integration
+//! ```
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use russh::keys::ssh_key::LineEnding;
+use russh::keys::ssh_key::certificate::Builder as CertificateBuilder;
+use russh::keys::{Algorithm, PrivateKey};
+
+use ferrissh::error::TransportError;
+use ferrissh::transport::{KeyboardInteractivePrompt, KeyboardInteractiveResponder};
+use ferrissh::{Driver, DriverBuilder, Error, Platform};
+
+fn random_key() -> PrivateKey {
+    PrivateKey::random(
+        &mut russh::keys::ssh_key::rand_core::OsRng,
+        Algorithm::Ed25519,
+    )
+    .expect("failed to generate key")
+}
+
+fn key_builder(port: u16) -> DriverBuilder {
+    DriverBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+}
+
+// =============================================================================
+// In-memory keys and certificates
+// =============================================================================
+
+/// A private key supplied as an OpenSSH string authenticates the session.
+#[tokio::test]
+async fn test_private_key_pem_auth() {
+    let port = common::mock_server_port().await;
+    let pem = random_key().to_openssh(LineEnding::LF).unwrap();
+
+    let mut driver = key_builder(port)
+        .private_key_pem(pem.as_str())
+        .build()
+        .unwrap();
+    driver
+        .open()
+        .await
+        .expect("in-memory key auth should succeed");
+
+    let response = driver.send_command("echo pem_ok").await.unwrap();
+    assert!(response.result.contains("pem_ok"));
+
+    driver.close().await.ok();
+}
+
+/// Malformed key material surfaces as a key error.
+#[tokio::test]
+async fn test_private_key_pem_invalid() {
+    let port = common::mock_server_port().await;
+
+    let mut driver = key_builder(port)
+        .private_key_pem("not a private key")
+        .build()
+        .unwrap();
+    let err = driver.open().await.expect_err("bad key should fail");
+    assert!(
+        matches!(err, Error::Transport(TransportError::Key(_))),
+        "got: {err}"
+    );
+}
+
+/// An in-memory key plus a CA-signed certificate authenticates the session.
+#[tokio::test]
+async fn test_certificate_auth() {
+    let port = common::mock_server_port().await;
+    let ca = random_key();
+    let user = random_key();
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let mut builder = CertificateBuilder::new_with_random_nonce(
+        &mut russh::keys::ssh_key::rand_core::OsRng,
+        user.public_key(),
+        now - 60,
+        now + 3600,
+    )
+    .unwrap();
+    builder.valid_principal("test").unwrap();
+    let cert = builder.sign(&ca).unwrap();
+
+    let mut driver = key_builder(port)
+        .private_key_pem(user.to_openssh(LineEnding::LF).unwrap().as_str())
+        .certificate_openssh(cert.to_openssh().unwrap())
+        .build()
+        .unwrap();
+    driver
+        .open()
+        .await
+        .expect("certificate auth should succeed");
+
+    let response = driver.send_command("echo cert_ok").await.unwrap();
+    assert!(response.result.contains("cert_ok"));
+
+    driver.close().await.ok();
+}
+
+/// A malformed certificate surfaces as a key error.
+#[tokio::test]
+async fn test_certificate_invalid() {
+    let port = common::mock_server_port().await;
+    let pem = random_key().to_openssh(LineEnding::LF).unwrap();
+
+    let mut driver = key_builder(port)
+        .private_key_pem(pem.as_str())
+        .certificate_openssh("ssh-ed25519-cert-v01@openssh.com garbage")
+        .build()
+        .unwrap();
+    let err = driver
+        .open()
+        .await
+        .expect_err("bad certificate should fail");
+    assert!(
+        matches!(err, Error::Transport(TransportError::Key(_))),
+        "got: {err}"
+    );
+}
+
+// =============================================================================
+// Keyboard-interactive authentication
+// =============================================================================
+
+/// Records every round and answers with a password and OTP code.
+#[derive(Default)]
+struct OtpResponder {
+    rounds: Mutex<Vec<(String, String, Vec<KeyboardInteractivePrompt>)>>,
+}
+
+impl KeyboardInteractiveResponder for OtpResponder {
+    fn respond(
+        &self,
+        name: &str,
+        instructions: &str,
+        prompts: &[KeyboardInteractivePrompt],
+    ) -> Vec<String> {
+        self.rounds.lock().unwrap().push((
+            name.to_string(),
+            instructions.to_string(),
+            prompts.to_vec(),
+        ));
+        prompts
+            .iter()
+            .map(|p| {
+                if p.prompt.starts_with("OTP") {
+                    "123456".to_string()
+                } else {
+                    "secret".to_string()
+                }
+            })
+            .collect()
+    }
+}
+
+fn kbd_builder(port: u16, user: &str) -> DriverBuilder {
+    key_builder(port).username(user)
+}
+
+/// Password auth falls back to keyboard-interactive with the default responder.
+#[tokio::test]
+async fn test_password_falls_back_to_keyboard_interactive() {
+    let port = common::mock_server_port().await;
+
+    let mut driver = kbd_builder(port, "kbd-password")
+        .password("secret")
+        .build()
+        .unwrap();
+    driver
+        .open()
+        .await
+        .expect("keyboard-interactive fallback should succeed");
+    driver.close().await.ok();
+}
+
+/// A custom responder sees every round, including the echo flags and an
+/// informational round with no prompts.
+#[tokio::test]
+async fn test_keyboard_interactive_responder() {
+    let port = common::mock_server_port().await;
+    let responder = Arc::new(OtpResponder::default());
+
+    let mut driver = kbd_builder(port, "kbd-otp")
+        .keyboard_interactive(responder.clone())
+        .build()
+        .unwrap();
+    driver.open().await.expect("OTP auth should succeed");
+    driver.close().await.ok();
+
+    let rounds = responder.rounds.lock().unwrap();
+    assert_eq!(rounds.len(), 2);
+    assert_eq!(
+        rounds[0].2,
+        vec![
+            KeyboardInteractivePrompt {
+                prompt: "Password: ".into(),
+                echo: false
+            },
+            KeyboardInteractivePrompt {
+                prompt: "OTP code: ".into(),
+                echo: true
+            },
+        ]
+    );
+    assert_eq!(rounds[1].0, "Notice");
+    assert_eq!(rounds[1].1, "Authorized use only");
+    assert!(rounds[1].2.is_empty());
+}
+
+/// The default responder cannot answer an OTP prompt.
+#[tokio::test]
+async fn test_default_responder_fails_otp() {
+    let port = common::mock_server_port().await;
+
+    let mut driver = kbd_builder(port, "kbd-otp")
+        .password("secret")
+        .build()
+        .unwrap();
+    let err = driver.open().await.expect_err("OTP prompt should fail");
+    assert!(
+        matches!(
+            err,
+            Error::Transport(TransportError::AuthenticationFailed { .. })
+        ),
+        "got: {err}"
+    );
+}
+
+// =============================================================================
+// Ordered fallback
+// =============================================================================
+
+fn key_pem() -> String {
+    random_key().to_openssh(LineEnding::LF).unwrap().to_string()
+}
+
+/// A publickey partial success continues with the next method (password).
+#[tokio::test]
+async fn test_partial_success_publickey_then_password() {
+    let port = common::mock_server_port().await;
+
+    let mut driver = key_builder(port)
+        .username("partial")
+        .private_key_pem(key_pem())
+        .password("secret")
+        .build()
+        .unwrap();
+    driver
+        .open()
+        .await
+        .expect("publickey + password should succeed");
+
+    let response = driver.send_command("echo partial_ok").await.unwrap();
+    assert!(response.result.contains("partial_ok"));
+    driver.close().await.ok();
+}
+
+/// A rejected key falls through to the next method.
+#[tokio::test]
+async fn test_fallback_after_rejected_key() {
+    let port = common::mock_server_port().await;
+
+    let mut driver = key_builder(port)
+        .username("pw-only")
+        .private_key_pem(key_pem())
+        .password("secret")
+        .build()
+        .unwrap();
+    driver
+        .open()
+        .await
+        .expect("password fallback should succeed");
+    driver.close().await.ok();
+}
+
+/// Methods the server does not offer are skipped and not reported.
+#[tokio::test]
+async fn test_unoffered_methods_are_skipped() {
+    let port = common::mock_server_port().await;
+
+    let mut driver = key_builder(port)
+        .username("pk-only")
+        .password("secret")
+        .keyboard_interactive(Arc::new(OtpResponder::default()))
+        .private_key_pem(key_pem())
+        .build()
+        .unwrap();
+    driver
+        .open()
+        .await
+        .expect("key after password should succeed");
+    driver.close().await.ok();
+
+    let mut driver = key_builder(port)
+        .username("pk-only")
+        .password("secret")
+        .keyboard_interactive(Arc::new(OtpResponder::default()))
+        .build()
+        .unwrap();
+    let err = driver.open().await.expect_err("no usable method");
+    assert!(
+        matches!(
+            err,
+            Error::Transport(TransportError::AuthenticationFailed { ref methods, .. })
+                if methods == &["password"]
+        ),
+        "got: {err}"
+    );
+}
+
+/// Every attempted method is listed when authentication fails.
+#[tokio::test]
+async fn test_authentication_failed_lists_methods() {
+    let port = common::mock_server_port().await;
+
+    let mut driver = key_builder(port)
+        .username("partial")
+        .private_key_pem(key_pem())
+        .build()
+        .unwrap();
+    let err = driver
+        .open()
+        .await
+        .expect_err("publickey alone is not enough");
+    assert!(
+        matches!(
+            err,
+            Error::Transport(TransportError::AuthenticationFailed { ref methods, .. })
+                if methods == &["publickey"]
+        ),
+        "got: {err}"
+    );
+    assert!(err.to_string().contains("(tried: publickey)"), "got: {err}");
+}
+
+// =============================================================================
+// SSH agent authentication
+// =============================================================================
+
+#[cfg(unix)]
+mod agent {
+    use super::*;
+
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use russh::keys::agent::client::AgentClient;
+    use tokio::net::UnixListener;
+
+    static AGENT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Start a local SSH agent on a fresh Unix socket, preloaded with `keys`.
+    async fn spawn_agent(keys: &[PrivateKey]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "ferrissh-agent-{}-{}.sock",
+            std::process::id(),
+            AGENT_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let listener = UnixListener::bind(&path).expect("failed to bind agent socket");
+        let incoming = futures_util::stream::unfold(listener, |listener| async move {
+            let stream = listener.accept().await.map(|(stream, _)| stream);
+            Some((stream, listener))
+        });
+        tokio::spawn(russh::keys::agent::server::serve(Box::pin(incoming), ()));
+
+        let mut client = AgentClient::connect_uds(&path)
+            .await
+            .expect("failed to connect to agent");
+        for key in keys {
+            client
+                .add_identity(key, &[])
+                .await
+                .expect("failed to add identity");
+        }
+
+        path
+    }
+
+    fn agent_driver(port: u16, socket: PathBuf) -> ferrissh::GenericDriver {
+        key_builder(port)
+            .agent_socket(socket)
+            .build()
+            .expect("driver build should succeed")
+    }
+
+    /// An identity held by the agent authenticates the session.
+    #[tokio::test]
+    async fn test_agent_auth_succeeds() {
+        let port = common::mock_server_port().await;
+        let socket = spawn_agent(&[random_key()]).await;
+
+        let mut driver = agent_driver(port, socket.clone());
+        driver.open().await.expect("agent auth should succeed");
+
+        let response = driver.send_command("echo agent_ok").await.unwrap();
+        assert!(response.result.contains("agent_ok"));
+
+        driver.close().await.ok();
+        let _ = std::fs::remove_file(socket);
+    }
+
+    /// An agent with no identities fails authentication cleanly.
+    #[tokio::test]
+    async fn test_agent_auth_no_identities() {
+        let port = common::mock_server_port().await;
+        let socket = spawn_agent(&[]).await;
+
+        let mut driver = agent_driver(port, socket.clone());
+        let err = driver.open().await.expect_err("empty agent should fail");
+        assert!(
+            matches!(
+                err,
+                Error::Transport(TransportError::AuthenticationFailed { .. })
+            ),
+            "got: {err}"
+        );
+
+        let _ = std::fs::remove_file(socket);
+    }
+
+    /// A missing agent socket surfaces as an agent error.
+    #[tokio::test]
+    async fn test_agent_auth_missing_socket() {
+        let port = common::mock_server_port().await;
+        let socket = std::env::temp_dir().join("ferrissh-agent-does-not-exist.sock");
+
+        let mut driver = agent_driver(port, socket);
+        let err = driver.open().await.expect_err("missing agent should fail");
+        assert!(
+            matches!(err, Error::Transport(TransportError::Agent(_))),
+            "got: {err}"
+        );
+    }
+}
@@ -6,13 +6,41 @@
 //! The server runs on a dedicated thread with its own tokio runtime so
 //! it outlives individual `#[tokio::test]` runtimes.
 
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::os::unix::fs::FileExt;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
-use russh::keys::{Algorithm, PrivateKey, PublicKey};
-use russh::server::{self, Auth, Msg, Session as ServerSession};
-use russh::{Channel, ChannelId, CryptoVec};
+use ferrissh::{Platform, Session, SessionBuilder};
+
+use russh::keys::{Algorithm, Certificate, PrivateKey, PublicKey};
+use russh::server::{self, Auth, Msg, Response, Session as ServerSession};
+use russh::{Channel, ChannelId, CryptoVec, MethodKind, MethodSet, Sig};
+use russh_sftp::protocol::{
+    Attrs, Data, File, FileAttributes, Handle, Name, OpenFlags, Status, StatusCode,
+};
+use tokio::io::AsyncReadExt;
+
+mod netconf;
 
 const PROMPT: &str = "user@mock:~$ ";
+const ROOT_PROMPT: &str = "root@mock:~# ";
+
+/// Pages the `paged` command shows, pausing at a `more`-style prompt
+/// between them.
+const PAGES: [&str; 3] = [
+    "line 1\nline 2\nline 3\n",
+    "  line 4\nline 5\nline 6\n",
+    "  line 7\nline 8\nline 9\n",
+];
+
+/// Identification string and authentication banner the server sends.
+pub const SERVER_ID: &str = "SSH-2.0-ferrissh_mock_1.0";
+pub const BANNER: &str = "Authorized use only\r\n";
 
 static MOCK_PORT: OnceLock<u16> = OnceLock::new();
 
@@ -33,6 +61,56 @@ pub async fn mock_server_port() -> u16 {
     })
 }
 
+// Not every test binary uses every helper below.
+
+/// A builder for a Linux session with the mock server as `test`, for
+/// tests that need to change a setting before connecting.
+#[allow(dead_code)]
+pub async fn linux_session_builder() -> SessionBuilder {
+    let port = mock_server_port().await;
+    SessionBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+}
+
+/// A Linux session with the mock server as `test`.
+#[allow(dead_code)]
+pub async fn linux_session() -> Session {
+    linux_session_builder()
+        .await
+        .connect()
+        .await
+        .expect("connect should succeed")
+}
+
+#[allow(dead_code)]
+static DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A fresh, empty scratch directory under the system temp dir, named
+/// `ferrissh-<prefix>-...`.
+#[allow(dead_code)]
+pub fn scratch_dir(prefix: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "ferrissh-{prefix}-{}-{}",
+        std::process::id(),
+        DIR_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A local path as the remote path the mock server sees (they're the same
+/// filesystem).
+#[allow(dead_code)]
+pub fn remote(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
 async fn start_server() -> u16 {
     let key = PrivateKey::random(
         &mut russh::keys::ssh_key::rand_core::OsRng,
@@ -42,6 +120,7 @@ async fn start_server() -> u16 {
 
     let config = Arc::new(russh::server::Config {
         keys: vec![key],
+        server_id: russh::SshId::Standard(SERVER_ID.to_string()),
         ..Default::default()
     });
 
@@ -70,64 +149,525 @@ async fn start_server() -> u16 {
 
 struct ShellHandler {
     buf: Vec<u8>,
+    /// Session channels whose request (shell, exec or subsystem) hasn't
+    /// arrived yet.
+    pending: HashMap<ChannelId, Channel<Msg>>,
+    /// `direct-tcpip` and subsystem channels, whose data is handled
+    /// elsewhere rather than executed.
+    forwarded: HashSet<ChannelId>,
+    /// Accept loops for `tcpip-forward` requests, by bound port.
+    listeners: HashMap<u32, tokio::task::JoinHandle<()>>,
+    /// Environment of each shell's commands: `env` requests, plus `TERM`,
+    /// `COLUMNS`, `LINES` and `MOCK_PTY_MODES` from the PTY request.
+    shell_env: HashMap<ChannelId, HashMap<String, String>>,
+    /// Shell commands still running, by channel, so Ctrl-C can stop them.
+    running: HashMap<ChannelId, tokio::task::JoinHandle<()>>,
+    /// Keyboard-interactive rounds completed so far.
+    kbd_round: usize,
+    /// Whether `partial` has completed its publickey step.
+    publickey_accepted: bool,
+    /// Whether `sudo -i` is waiting for its password.
+    sudo_pending: bool,
+    /// Whether the shell is at the root prompt (after `sudo -i`).
+    root: bool,
+    /// Next page of `paged` output, while its pager prompt is showing.
+    paging: Option<usize>,
+    /// User that authenticated with a password.
+    user: String,
+    /// NETCONF datastores shared by this connection's `netconf` channels.
+    netconf: netconf::SharedDatastores,
 }
 
 impl ShellHandler {
     fn new() -> Self {
-        Self { buf: Vec::new() }
+        Self {
+            buf: Vec::new(),
+            pending: HashMap::new(),
+            forwarded: HashSet::new(),
+            listeners: HashMap::new(),
+            shell_env: HashMap::new(),
+            running: HashMap::new(),
+            kbd_round: 0,
+            publickey_accepted: false,
+            sudo_pending: false,
+            root: false,
+            paging: None,
+            user: String::new(),
+            netconf: Default::default(),
+        }
+    }
+}
+
+impl Drop for ShellHandler {
+    fn drop(&mut self) {
+        for task in self.listeners.values().chain(self.running.values()) {
+            task.abort();
+        }
+    }
+}
+
+impl ShellHandler {
+    /// The prompt the shell currently shows.
+    fn prompt(&self) -> &'static str {
+        if self.root { ROOT_PROMPT } else { PROMPT }
+    }
+
+    /// Stop the command running on `channel` and show a fresh prompt, as
+    /// a shell does on Ctrl-C.
+    fn interrupt(
+        &mut self,
+        channel: ChannelId,
+        session: &mut ServerSession,
+    ) -> Result<(), russh::Error> {
+        if let Some(task) = self.running.remove(&channel) {
+            task.abort();
+        }
+        self.buf.clear();
+        let reply = format!("^C\n{}", self.prompt());
+        session.data(channel, CryptoVec::from(reply.into_bytes()))
+    }
+}
+
+impl ShellHandler {
+    /// Show page `page` of the `paged` output (or stop, on `quit`), then
+    /// the pager prompt or, after the last page, the shell prompt.
+    ///
+    /// Answered prompts are erased the way `more` does (carriage return
+    /// and line clear) on the first page, and with backspaces over spaces
+    /// on the second.
+    fn next_page(
+        &mut self,
+        channel: ChannelId,
+        page: usize,
+        quit: bool,
+        session: &mut ServerSession,
+    ) -> Result<(), russh::Error> {
+        let more = |page: usize| format!("--More--({}%)", page * 100 / PAGES.len());
+        match page {
+            0 => {}
+            1 => session.data(channel, CryptoVec::from(b"\r\x1b[K".to_vec()))?,
+            _ => {
+                let width = more(page).len();
+                let erase = format!("{0}{1}{0}", "\x08".repeat(width), " ".repeat(width));
+                session.data(channel, CryptoVec::from(erase.into_bytes()))?;
+            }
+        }
+
+        if quit {
+            self.paging = None;
+            return session.data(channel, CryptoVec::from(self.prompt().as_bytes().to_vec()));
+        }
+        session.data(channel, CryptoVec::from(PAGES[page].as_bytes().to_vec()))?;
+        if page + 1 == PAGES.len() {
+            self.paging = None;
+            session.data(channel, CryptoVec::from(self.prompt().as_bytes().to_vec()))
+        } else {
+            self.paging = Some(page + 1);
+            session.data(channel, CryptoVec::from(more(page + 1).into_bytes()))
+        }
+    }
+}
+
+/// Run a shell command in the background, streaming its stdout to
+/// `channel` and finishing with `prompt`. Aborting the task kills it.
+fn spawn_command(
+    handle: server::Handle,
+    channel: ChannelId,
+    command: String,
+    env: HashMap<String, String>,
+    prompt: &'static str,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let child = tokio::process::Command::new("bash")
+            .arg("-c")
+            .arg(&command)
+            .envs(env)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .spawn();
+        if let Ok(mut child) = child {
+            let mut stdout = child.stdout.take().unwrap();
+            let mut buf = vec![0u8; 4096];
+            while let Ok(n) = stdout.read(&mut buf).await
+                && n > 0
+            {
+                let _ = handle.data(channel, CryptoVec::from(&buf[..n])).await;
+            }
+            let _ = child.wait().await;
+        }
+        let _ = handle
+            .data(channel, CryptoVec::from(prompt.as_bytes().to_vec()))
+            .await;
+    })
+}
+
+/// Reject, advertising only `methods` as able to continue.
+fn reject_except(methods: &[MethodKind], partial_success: bool) -> Auth {
+    Auth::Reject {
+        proceed_with_methods: Some(MethodSet::from(methods)),
+        partial_success,
     }
 }
 
 impl server::Handler for ShellHandler {
     type Error = russh::Error;
 
-    async fn auth_password(&mut self, _: &str, _: &str) -> Result<Auth, Self::Error> {
-        Ok(Auth::Accept)
+    async fn authentication_banner(&mut self) -> Result<Option<String>, Self::Error> {
+        Ok(Some(BANNER.to_string()))
+    }
+
+    /// Users named `kbd-*` must use keyboard-interactive, `pk-only` must
+    /// use publickey, and `partial` must complete publickey first; everyone
+    /// else is accepted.
+    async fn auth_password(&mut self, user: &str, _: &str) -> Result<Auth, Self::Error> {
+        self.user = user.to_string();
+        match user {
+            "pk-only" => Ok(reject_except(&[MethodKind::PublicKey], false)),
+            "partial" if !self.publickey_accepted => {
+                Ok(reject_except(&[MethodKind::PublicKey], false))
+            }
+            _ if user.starts_with("kbd-") => Ok(Auth::reject()),
+            _ => Ok(Auth::Accept),
+        }
+    }
+
+    /// `kbd-password` asks for a password; `kbd-otp` asks for a password and
+    /// an OTP code (`secret` / `123456`), then shows a notice.
+    async fn auth_keyboard_interactive<'a>(
+        &'a mut self,
+        user: &str,
+        _submethods: &str,
+        response: Option<Response<'a>>,
+    ) -> Result<Auth, Self::Error> {
+        let answers: Vec<String> = response
+            .map(|r| {
+                r.map(|b| String::from_utf8_lossy(&b).into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.kbd_round += 1;
+
+        let prompt = |name: &'static str, instructions: &'static str, prompts| Auth::Partial {
+            name: Cow::Borrowed(name),
+            instructions: Cow::Borrowed(instructions),
+            prompts: Cow::Owned(prompts),
+        };
+
+        Ok(match (user, self.kbd_round) {
+            ("kbd-password", 1) => prompt("", "", vec![(Cow::Borrowed("Password: "), false)]),
+            ("kbd-password", 2) if answers == ["secret"] => Auth::Accept,
+            ("kbd-otp", 1) => prompt(
+                "",
+                "",
+                vec![
+                    (Cow::Borrowed("Password: "), false),
+                    (Cow::Borrowed("OTP code: "), true),
+                ],
+            ),
+            ("kbd-otp", 2) if answers == ["secret", "123456"] => {
+                prompt("Notice", "Authorized use only", vec![])
+            }
+            ("kbd-otp", 3) => Auth::Accept,
+            _ => {
+                self.kbd_round = 0;
+                Auth::reject()
+            }
+        })
     }
 
-    async fn auth_publickey(&mut self, _: &str, _: &PublicKey) -> Result<Auth, Self::Error> {
+    /// `pw-only` must use a password; `partial` needs a password as well
+    /// (publickey is a partial success).
+    async fn auth_publickey(&mut self, user: &str, _: &PublicKey) -> Result<Auth, Self::Error> {
+        match user {
+            "pw-only" => Ok(reject_except(&[MethodKind::Password], false)),
+            "partial" => {
+                self.publickey_accepted = true;
+                Ok(reject_except(&[MethodKind::Password], true))
+            }
+            _ => Ok(Auth::Accept),
+        }
+    }
+
+    async fn auth_openssh_certificate(
+        &mut self,
+        _: &str,
+        _: &Certificate,
+    ) -> Result<Auth, Self::Error> {
         Ok(Auth::Accept)
     }
 
     async fn channel_open_session(
         &mut self,
-        _channel: Channel<Msg>,
+        channel: Channel<Msg>,
         _session: &mut ServerSession,
     ) -> Result<bool, Self::Error> {
+        self.pending.insert(channel.id(), channel);
+        Ok(true)
+    }
+
+    /// Forward `direct-tcpip` channels so the server can act as a jump host.
+    async fn channel_open_direct_tcpip(
+        &mut self,
+        channel: Channel<Msg>,
+        host_to_connect: &str,
+        port_to_connect: u32,
+        _originator_address: &str,
+        _originator_port: u32,
+        _session: &mut ServerSession,
+    ) -> Result<bool, Self::Error> {
+        let Ok(mut upstream) =
+            tokio::net::TcpStream::connect((host_to_connect, port_to_connect as u16)).await
+        else {
+            return Ok(false);
+        };
+        self.forwarded.insert(channel.id());
+        tokio::spawn(async move {
+            let mut stream = channel.into_stream();
+            let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+        });
+        Ok(true)
+    }
+
+    /// Listen on `address:port` and open a `forwarded-tcpip` channel back
+    /// to the client for each connection.
+    async fn tcpip_forward(
+        &mut self,
+        address: &str,
+        port: &mut u32,
+        session: &mut ServerSession,
+    ) -> Result<bool, Self::Error> {
+        let Ok(listener) = tokio::net::TcpListener::bind((address, *port as u16)).await else {
+            return Ok(false);
+        };
+        *port = listener.local_addr().unwrap().port().into();
+        let (handle, address, bound) = (session.handle(), address.to_string(), *port);
+        let accept = tokio::spawn(async move {
+            while let Ok((mut stream, peer)) = listener.accept().await {
+                let Ok(channel) = handle
+                    .channel_open_forwarded_tcpip(
+                        address.clone(),
+                        bound,
+                        peer.ip().to_string(),
+                        peer.port().into(),
+                    )
+                    .await
+                else {
+                    continue;
+                };
+                tokio::spawn(async move {
+                    let mut remote = channel.into_stream();
+                    let _ = tokio::io::copy_bidirectional(&mut stream, &mut remote).await;
+                });
+            }
+        });
+        self.listeners.insert(bound, accept);
         Ok(true)
     }
 
+    async fn cancel_tcpip_forward(
+        &mut self,
+        _address: &str,
+        port: u32,
+        _session: &mut ServerSession,
+    ) -> Result<bool, Self::Error> {
+        match self.listeners.remove(&port) {
+            Some(listener) => {
+                listener.abort();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn env_request(
+        &mut self,
+        channel: ChannelId,
+        variable_name: &str,
+        variable_value: &str,
+        session: &mut ServerSession,
+    ) -> Result<(), Self::Error> {
+        self.shell_env
+            .entry(channel)
+            .or_default()
+            .insert(variable_name.to_string(), variable_value.to_string());
+        session.channel_success(channel)?;
+        Ok(())
+    }
+
     async fn pty_request(
         &mut self,
         channel: ChannelId,
-        _term: &str,
-        _col_width: u32,
-        _row_height: u32,
+        term: &str,
+        col_width: u32,
+        row_height: u32,
         _pix_width: u32,
         _pix_height: u32,
-        _modes: &[(russh::Pty, u32)],
+        modes: &[(russh::Pty, u32)],
         session: &mut ServerSession,
     ) -> Result<(), Self::Error> {
+        let modes: Vec<String> = modes
+            .iter()
+            .map(|(mode, value)| format!("{}={}", *mode as u8, value))
+            .collect();
+        let env = self.shell_env.entry(channel).or_default();
+        env.insert("TERM".into(), term.to_string());
+        env.insert("COLUMNS".into(), col_width.to_string());
+        env.insert("LINES".into(), row_height.to_string());
+        env.insert("MOCK_PTY_MODES".into(), modes.join(","));
         session.channel_success(channel)?;
         Ok(())
     }
 
+    async fn window_change_request(
+        &mut self,
+        channel: ChannelId,
+        col_width: u32,
+        row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        _session: &mut ServerSession,
+    ) -> Result<(), Self::Error> {
+        let env = self.shell_env.entry(channel).or_default();
+        env.insert("COLUMNS".into(), col_width.to_string());
+        env.insert("LINES".into(), row_height.to_string());
+        Ok(())
+    }
+
+    /// Signals that end a process stop the running command.
+    async fn signal(
+        &mut self,
+        channel: ChannelId,
+        signal: Sig,
+        session: &mut ServerSession,
+    ) -> Result<(), Self::Error> {
+        if matches!(signal, Sig::INT | Sig::TERM | Sig::KILL)
+            && self.running.get(&channel).is_some_and(|t| !t.is_finished())
+        {
+            self.interrupt(channel, session)?;
+        }
+        Ok(())
+    }
+
     async fn shell_request(
         &mut self,
         channel: ChannelId,
         session: &mut ServerSession,
     ) -> Result<(), Self::Error> {
+        self.pending.remove(&channel);
         session.data(channel, CryptoVec::from(PROMPT.as_bytes().to_vec()))?;
         session.channel_success(channel)?;
         Ok(())
     }
 
+    /// Run `exec` requests with `bash -c`, reporting stdout, stderr (as
+    /// extended data) and the exit status or killing signal.
+    async fn exec_request(
+        &mut self,
+        channel: ChannelId,
+        data: &[u8],
+        session: &mut ServerSession,
+    ) -> Result<(), Self::Error> {
+        let pending = self.pending.remove(&channel);
+        let command = String::from_utf8_lossy(data).into_owned();
+
+        // `scp` talks to the client over stdin/stdout, so it needs a live
+        // process rather than one run to completion.
+        if command.starts_with("scp ")
+            && let Some(scp_channel) = pending
+        {
+            self.forwarded.insert(channel);
+            session.channel_success(channel)?;
+            let command = if self.user == "scp-hostile" {
+                HOSTILE_SCP_SOURCE.to_string()
+            } else {
+                command
+            };
+            tokio::spawn(run_interactive(scp_channel, session.handle(), command));
+            return Ok(());
+        }
+
+        let Ok(output) = tokio::process::Command::new("bash")
+            .arg("-c")
+            .arg(&command)
+            .output()
+            .await
+        else {
+            session.channel_failure(channel)?;
+            return Ok(());
+        };
+        session.channel_success(channel)?;
+
+        if !output.stdout.is_empty() {
+            session.data(channel, CryptoVec::from(output.stdout))?;
+        }
+        if !output.stderr.is_empty() {
+            session.extended_data(channel, 1, CryptoVec::from(output.stderr))?;
+        }
+        match (output.status.code(), output.status.signal()) {
+            (Some(code), _) => session.exit_status_request(channel, code as u32)?,
+            (None, Some(signal)) => {
+                let sig = match signal {
+                    9 => Sig::KILL,
+                    15 => Sig::TERM,
+                    other => Sig::Custom(other.to_string()),
+                };
+                session.exit_signal_request(channel, sig, false, "", "")?;
+            }
+            (None, None) => {}
+        }
+        session.eof(channel)?;
+        session.close(channel)?;
+        Ok(())
+    }
+
+    /// Serve the `sftp` subsystem from the local filesystem, and the
+    /// `netconf` subsystem from in-memory datastores. User `mute-subsystem`
+    /// gets no reply at all.
+    async fn subsystem_request(
+        &mut self,
+        channel: ChannelId,
+        name: &str,
+        session: &mut ServerSession,
+    ) -> Result<(), Self::Error> {
+        if self.user == "mute-subsystem" {
+            return Ok(());
+        }
+        match self.pending.remove(&channel) {
+            Some(sftp_channel) if name == "sftp" => {
+                self.forwarded.insert(channel);
+                session.channel_success(channel)?;
+                russh_sftp::server::run(sftp_channel.into_stream(), SftpHandler::default()).await;
+            }
+            Some(netconf_channel) if name == "netconf" => {
+                self.forwarded.insert(channel);
+                session.channel_success(channel)?;
+                let stream = netconf_channel.into_stream();
+                let state = self.netconf.clone();
+                let base_1_1 = self.user != "netconf-1.0";
+                tokio::spawn(netconf::serve(stream, state, base_1_1));
+            }
+            _ => session.channel_failure(channel)?,
+        }
+        Ok(())
+    }
+
     async fn data(
         &mut self,
         channel: ChannelId,
         data: &[u8],
         session: &mut ServerSession,
     ) -> Result<(), Self::Error> {
+        if self.forwarded.contains(&channel) {
+            return Ok(());
+        }
+        if data.contains(&0x03) {
+            return self.interrupt(channel, session);
+        }
+        if let Some(page) = self.paging {
+            return self.next_page(channel, page, data.contains(&b'q'), session);
+        }
         self.buf.extend_from_slice(data);
 
         while let Some(nl_pos) = self.buf.iter().position(|&b| b == b'\n') {
@@ -136,30 +676,277 @@ impl server::Handler for ShellHandler {
                 .to_string();
             self.buf.drain(..=nl_pos);
 
+            // `sudo -i` password: accepted without echo
+            if self.sudo_pending {
+                self.sudo_pending = false;
+                self.root = true;
+                session.data(channel, CryptoVec::from(b"\n".to_vec()))?;
+                session.data(channel, CryptoVec::from(ROOT_PROMPT.as_bytes().to_vec()))?;
+                continue;
+            }
+
             // Echo the command (simulates PTY echo)
             session.data(
                 channel,
                 CryptoVec::from(format!("{}\n", command).into_bytes()),
             )?;
 
-            // Execute the command
-            if !command.is_empty() {
-                if let Ok(output) = tokio::process::Command::new("bash")
-                    .arg("-c")
-                    .arg(&command)
-                    .output()
-                    .await
-                {
-                    if !output.stdout.is_empty() {
-                        session.data(channel, CryptoVec::from(output.stdout))?;
-                    }
+            match command.as_str() {
+                // Escalate (Linux platform `root` level): prompt for a password
+                "sudo -i" => {
+                    self.sudo_pending = true;
+                    session.data(channel, CryptoVec::from(b"Password: ".to_vec()))?;
+                    continue;
+                }
+                // Paged output, like piping through `more`
+                "paged" => {
+                    self.next_page(channel, 0, false, session)?;
+                    continue;
+                }
+                "exit" if self.root => self.root = false,
+                "" => {}
+                // Execute the command; it sends the prompt when done
+                _ => {
+                    let env = self.shell_env.get(&channel).cloned().unwrap_or_default();
+                    let task =
+                        spawn_command(session.handle(), channel, command, env, self.prompt());
+                    self.running.insert(channel, task);
+                    continue;
                 }
             }
 
             // Send prompt
-            session.data(channel, CryptoVec::from(PROMPT.as_bytes().to_vec()))?;
+            session.data(channel, CryptoVec::from(self.prompt().as_bytes().to_vec()))?;
         }
 
         Ok(())
     }
 }
+
+/// What user `scp-hostile` gets for any `scp` download: a source that
+/// sends `requested` and then a `.bashrc` nobody asked for.
+const HOSTILE_SCP_SOURCE: &str = r"ack() { head -c1 >/dev/null; }
+ack
+printf 'C0644 3 requested\n'; ack; printf 'abc\0'; ack
+printf 'C0644 5 .bashrc\n'; ack; printf 'evil\n\0'; ack";
+
+/// Run `command` with its stdin, stdout and stderr wired to `channel`.
+async fn run_interactive(channel: Channel<Msg>, handle: server::Handle, command: String) {
+    let id = channel.id();
+    let Ok(mut child) = tokio::process::Command::new("bash")
+        .arg("-c")
+        .arg(&command)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+    else {
+        let _ = handle.close(id).await;
+        return;
+    };
+    let (mut stdin, mut stdout, mut stderr) = (
+        child.stdin.take().unwrap(),
+        child.stdout.take().unwrap(),
+        child.stderr.take().unwrap(),
+    );
+
+    let (mut read_half, write_half) = channel.split();
+    let mut out = write_half.make_writer();
+    let mut err = write_half.make_writer_ext(Some(1));
+    tokio::spawn(async move {
+        while let Some(msg) = read_half.wait().await {
+            match msg {
+                russh::ChannelMsg::Data { data }
+                    if tokio::io::AsyncWriteExt::write_all(&mut stdin, &data)
+                        .await
+                        .is_err() =>
+                {
+                    break;
+                }
+                russh::ChannelMsg::Eof | russh::ChannelMsg::Close => break,
+                _ => {}
+            }
+        }
+    });
+    let _ = tokio::join!(
+        tokio::io::copy(&mut stdout, &mut out),
+        tokio::io::copy(&mut stderr, &mut err)
+    );
+
+    let code = child
+        .wait()
+        .await
+        .ok()
+        .and_then(|status| status.code())
+        .unwrap_or(255);
+    let _ = handle.exit_status_request(id, code as u32).await;
+    let _ = handle.eof(id).await;
+    let _ = handle.close(id).await;
+}
+
+/// SFTP server backed by the local filesystem.
+#[derive(Default)]
+struct SftpHandler {
+    next_handle: u64,
+    files: HashMap<String, std::fs::File>,
+    /// Directory listings not yet returned by `readdir`.
+    dirs: HashMap<String, Option<Vec<File>>>,
+}
+
+impl SftpHandler {
+    fn handle(&mut self) -> String {
+        self.next_handle += 1;
+        self.next_handle.to_string()
+    }
+}
+
+fn status_of(err: std::io::Error) -> StatusCode {
+    match err.kind() {
+        std::io::ErrorKind::NotFound => StatusCode::NoSuchFile,
+        std::io::ErrorKind::PermissionDenied => StatusCode::PermissionDenied,
+        _ => StatusCode::Failure,
+    }
+}
+
+fn ok(id: u32) -> Status {
+    Status {
+        id,
+        status_code: StatusCode::Ok,
+        error_message: "Ok".to_string(),
+        language_tag: "en-US".to_string(),
+    }
+}
+
+impl russh_sftp::server::Handler for SftpHandler {
+    type Error = StatusCode;
+
+    fn unimplemented(&self) -> Self::Error {
+        StatusCode::OpUnsupported
+    }
+
+    async fn open(
+        &mut self,
+        id: u32,
+        filename: String,
+        pflags: OpenFlags,
+        _attrs: FileAttributes,
+    ) -> Result<Handle, Self::Error> {
+        let file = std::fs::OpenOptions::from(pflags)
+            .open(&filename)
+            .map_err(status_of)?;
+        let handle = self.handle();
+        self.files.insert(handle.clone(), file);
+        Ok(Handle { id, handle })
+    }
+
+    async fn close(&mut self, id: u32, handle: String) -> Result<Status, Self::Error> {
+        self.files.remove(&handle);
+        self.dirs.remove(&handle);
+        Ok(ok(id))
+    }
+
+    async fn read(
+        &mut self,
+        id: u32,
+        handle: String,
+        offset: u64,
+        len: u32,
+    ) -> Result<Data, Self::Error> {
+        let file = self.files.get(&handle).ok_or(StatusCode::Failure)?;
+        let mut data = vec![0u8; len as usize];
+        let n = file.read_at(&mut data, offset).map_err(status_of)?;
+        if n == 0 {
+            return Err(StatusCode::Eof);
+        }
+        data.truncate(n);
+        Ok(Data { id, data })
+    }
+
+    async fn write(
+        &mut self,
+        id: u32,
+        handle: String,
+        offset: u64,
+        data: Vec<u8>,
+    ) -> Result<Status, Self::Error> {
+        let file = self.files.get(&handle).ok_or(StatusCode::Failure)?;
+        file.write_all_at(&data, offset).map_err(status_of)?;
+        Ok(ok(id))
+    }
+
+    async fn stat(&mut self, id: u32, path: String) -> Result<Attrs, Self::Error> {
+        let metadata = std::fs::metadata(path).map_err(status_of)?;
+        Ok(Attrs {
+            id,
+            attrs: FileAttributes::from(&metadata),
+        })
+    }
+
+    async fn lstat(&mut self, id: u32, path: String) -> Result<Attrs, Self::Error> {
+        let metadata = std::fs::symlink_metadata(path).map_err(status_of)?;
+        Ok(Attrs {
+            id,
+            attrs: FileAttributes::from(&metadata),
+        })
+    }
+
+    async fn fstat(&mut self, id: u32, handle: String) -> Result<Attrs, Self::Error> {
+        let file = self.files.get(&handle).ok_or(StatusCode::Failure)?;
+        let metadata = file.metadata().map_err(status_of)?;
+        Ok(Attrs {
+            id,
+            attrs: FileAttributes::from(&metadata),
+        })
+    }
+
+    async fn opendir(&mut self, id: u32, path: String) -> Result<Handle, Self::Error> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&path).map_err(status_of)? {
+            let entry = entry.map_err(status_of)?;
+            let metadata = entry.metadata().map_err(status_of)?;
+            let filename = entry.file_name().to_string_lossy().into_owned();
+            files.push(File::new(filename, FileAttributes::from(&metadata)));
+        }
+        let handle = self.handle();
+        self.dirs.insert(handle.clone(), Some(files));
+        Ok(Handle { id, handle })
+    }
+
+    async fn readdir(&mut self, id: u32, handle: String) -> Result<Name, Self::Error> {
+        let listing = self.dirs.get_mut(&handle).ok_or(StatusCode::Failure)?;
+        match listing.take() {
+            Some(files) => Ok(Name { id, files }),
+            None => Err(StatusCode::Eof),
+        }
+    }
+
+    async fn remove(&mut self, id: u32, filename: String) -> Result<Status, Self::Error> {
+        std::fs::remove_file(filename).map_err(status_of)?;
+        Ok(ok(id))
+    }
+
+    async fn mkdir(
+        &mut self,
+        id: u32,
+        path: String,
+        _attrs: FileAttributes,
+    ) -> Result<Status, Self::Error> {
+        std::fs::create_dir(path).map_err(status_of)?;
+        Ok(ok(id))
+    }
+
+    async fn rmdir(&mut self, id: u32, path: String) -> Result<Status, Self::Error> {
+        std::fs::remove_dir(path).map_err(status_of)?;
+        Ok(ok(id))
+    }
+
+    async fn rename(
+        &mut self,
+        id: u32,
+        oldpath: String,
+        newpath: String,
+    ) -> Result<Status, Self::Error> {
+        std::fs::rename(oldpath, newpath).map_err(status_of)?;
+        Ok(ok(id))
+    }
+}
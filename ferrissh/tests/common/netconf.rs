@@ -0,0 +1,310 @@
+//! NETCONF subsystem for the mock server.
+//!
+//! Keeps `running` and `candidate` datastores in memory per SSH connection,
+//! so NETCONF channels on the same connection see each other's changes and
+//! locks. Configuration is opaque text: `edit-config` appends to the target
+//! (or replaces it with `<default-operation>replace`), `get` echoes its
+//! filter back as data, and `validate` fails on an `<invalid/>` element and
+//! warns about a `<deprecated/>` one.
+//!
+//! Users named `netconf-1.0` get a server that only speaks base:1.0.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const NAMESPACE: &str = "urn:ietf:params:xml:ns:netconf:base:1.0";
+const BASE_1_0: &str = "urn:ietf:params:netconf:base:1.0";
+const BASE_1_1: &str = "urn:ietf:params:netconf:base:1.1";
+const END_OF_MESSAGE: &[u8] = b"]]>]]>";
+
+/// Datastores and locks shared by one connection's NETCONF channels.
+#[derive(Default)]
+pub struct Datastores {
+    running: String,
+    candidate: String,
+    /// Lock holder's session id, by datastore.
+    locks: HashMap<&'static str, u32>,
+    last_session_id: u32,
+}
+
+pub type SharedDatastores = Arc<Mutex<Datastores>>;
+
+/// Serve one NETCONF session until `close-session` or end of input.
+pub async fn serve<S>(mut stream: S, state: SharedDatastores, base_1_1: bool)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let session_id = {
+        let mut state = state.lock().unwrap();
+        state.last_session_id += 1;
+        state.last_session_id
+    };
+
+    let mut capabilities = vec![
+        BASE_1_0,
+        "urn:ietf:params:netconf:capability:candidate:1.0",
+        "urn:ietf:params:netconf:capability:validate:1.1",
+        "urn:ietf:params:netconf:capability:writable-running:1.0?module=mock",
+    ];
+    if base_1_1 {
+        capabilities.push(BASE_1_1);
+    }
+    let capabilities: String = capabilities
+        .iter()
+        .map(|capability| format!("<capability>{capability}</capability>"))
+        .collect();
+    let hello = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <hello xmlns=\"{NAMESPACE}\"><capabilities>{capabilities}</capabilities>\
+         <session-id>{session_id}</session-id></hello>"
+    );
+
+    let mut reader = Reader::default();
+    if write(&mut stream, &hello, false).await.is_err() {
+        return;
+    }
+    let Some(client_hello) = reader.next(&mut stream, false).await else {
+        return;
+    };
+    let chunked = base_1_1 && client_hello.contains(BASE_1_1);
+
+    while let Some(request) = reader.next(&mut stream, chunked).await {
+        let (body, close) = handle(&request, &state, session_id);
+        let message_id = attr(&request, "message-id").unwrap_or_default();
+        let reply = format!(
+            "<rpc-reply message-id=\"{message_id}\" xmlns=\"{NAMESPACE}\">{body}</rpc-reply>"
+        );
+        if write(&mut stream, &reply, chunked).await.is_err() || close {
+            break;
+        }
+    }
+
+    state
+        .lock()
+        .unwrap()
+        .locks
+        .retain(|_, holder| *holder != session_id);
+    let _ = stream.shutdown().await;
+}
+
+/// The reply body for `request`, and whether to end the session.
+fn handle(request: &str, state: &SharedDatastores, session_id: u32) -> (String, bool) {
+    let mut state = state.lock().unwrap();
+    let datastore = || {
+        if request.contains("<candidate/>") {
+            "candidate"
+        } else {
+            "running"
+        }
+    };
+    let ok = "<ok/>".to_string();
+
+    let body = if request.contains("<get-config>") {
+        let data = match datastore() {
+            "candidate" => &state.candidate,
+            _ => &state.running,
+        };
+        format!("<data>{data}</data>")
+    } else if request.contains("<get>") {
+        let filter = between(request, "<filter type=\"subtree\">", "</filter>").unwrap_or("");
+        format!("<data>{filter}</data>")
+    } else if request.contains("<edit-config>") {
+        let target = datastore();
+        match state.locks.get(target) {
+            Some(&holder) if holder != session_id => rpc_error(
+                "protocol",
+                "in-use",
+                &format!("{target} is locked by session {holder}"),
+            ),
+            _ => {
+                let config = between(request, "<config>", "</config>").unwrap_or("");
+                let store = match target {
+                    "candidate" => &mut state.candidate,
+                    _ => &mut state.running,
+                };
+                if request.contains("<default-operation>replace</default-operation>") {
+                    store.clear();
+                }
+                store.push_str(config);
+                ok
+            }
+        }
+    } else if request.contains("<lock>") {
+        let target = datastore();
+        match state.locks.get(target) {
+            Some(&holder) => format!(
+                "<rpc-error><error-type>protocol</error-type>\
+                 <error-tag>lock-denied</error-tag>\
+                 <error-severity>error</error-severity>\
+                 <error-info><session-id>{holder}</session-id></error-info>\
+                 <error-message xml:lang=\"en\">Lock failed, lock is already held</error-message>\
+                 </rpc-error>"
+            ),
+            None => {
+                state.locks.insert(target, session_id);
+                ok
+            }
+        }
+    } else if request.contains("<unlock>") {
+        let target = datastore();
+        if state.locks.get(target) == Some(&session_id) {
+            state.locks.remove(target);
+            ok
+        } else {
+            rpc_error("protocol", "operation-failed", "not locked by this session")
+        }
+    } else if request.contains("<commit/>") {
+        state.running = state.candidate.clone();
+        ok
+    } else if request.contains("<discard-changes/>") {
+        state.candidate = state.running.clone();
+        ok
+    } else if request.contains("<validate>") {
+        let config = match datastore() {
+            "candidate" => &state.candidate,
+            _ => &state.running,
+        };
+        if config.contains("<invalid/>") {
+            // Prefixed, as some servers send it.
+            "<nc:rpc-error xmlns:nc=\"urn:ietf:params:xml:ns:netconf:base:1.0\">\
+             <nc:error-type>application</nc:error-type>\
+             <nc:error-tag>invalid-value</nc:error-tag>\
+             <nc:error-severity>error</nc:error-severity>\
+             <nc:error-path>/config/invalid</nc:error-path>\
+             <nc:error-message>value &lt;invalid/&gt; is not allowed</nc:error-message>\
+             </nc:rpc-error>"
+                .to_string()
+        } else if config.contains("<deprecated/>") {
+            "<rpc-error><error-type>application</error-type>\
+             <error-tag>operation-failed</error-tag>\
+             <error-severity>warning</error-severity>\
+             <error-message>deprecated element</error-message></rpc-error><ok/>"
+                .to_string()
+        } else {
+            ok
+        }
+    } else if request.contains("<close-session/>") {
+        return (ok, true);
+    } else {
+        rpc_error("protocol", "operation-not-supported", "unknown operation")
+    };
+    (body, false)
+}
+
+fn rpc_error(error_type: &str, tag: &str, message: &str) -> String {
+    format!(
+        "<rpc-error><error-type>{error_type}</error-type><error-tag>{tag}</error-tag>\
+         <error-severity>error</error-severity><error-message>{message}</error-message>\
+         </rpc-error>"
+    )
+}
+
+/// The text between `start` and the last `end` after it.
+fn between<'a>(s: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let from = s.find(start)? + start.len();
+    let to = s.rfind(end)?;
+    s.get(from..to)
+}
+
+/// The value of a double-quoted attribute.
+fn attr<'a>(s: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{name}=\"");
+    let from = s.find(&pattern)? + pattern.len();
+    let len = s[from..].find('"')?;
+    Some(&s[from..from + len])
+}
+
+/// Frame and send `message`. Chunked messages are split in two chunks.
+async fn write<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    message: &str,
+    chunked: bool,
+) -> std::io::Result<()> {
+    let mut framed = Vec::new();
+    if chunked {
+        let mut mid = message.len() / 2;
+        while !message.is_char_boundary(mid) {
+            mid += 1;
+        }
+        for chunk in [&message[..mid], &message[mid..]] {
+            if !chunk.is_empty() {
+                framed.extend_from_slice(format!("\n#{}\n", chunk.len()).as_bytes());
+                framed.extend_from_slice(chunk.as_bytes());
+            }
+        }
+        framed.extend_from_slice(b"\n##\n");
+    } else {
+        framed.extend_from_slice(message.as_bytes());
+        framed.extend_from_slice(END_OF_MESSAGE);
+    }
+    stream.write_all(&framed).await?;
+    stream.flush().await
+}
+
+/// Reads framed messages from the client.
+#[derive(Default)]
+struct Reader {
+    buf: Vec<u8>,
+}
+
+impl Reader {
+    /// The next message, or `None` at end of input or on a framing error.
+    async fn next<S: AsyncRead + Unpin>(
+        &mut self,
+        stream: &mut S,
+        chunked: bool,
+    ) -> Option<String> {
+        loop {
+            let parsed = if chunked {
+                self.parse_chunked()?
+            } else {
+                self.parse_end_of_message()
+            };
+            if let Some(message) = parsed {
+                return Some(message);
+            }
+            let mut chunk = [0u8; 4096];
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return None,
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+            }
+        }
+    }
+
+    fn parse_end_of_message(&mut self) -> Option<String> {
+        let end = self
+            .buf
+            .windows(END_OF_MESSAGE.len())
+            .position(|w| w == END_OF_MESSAGE)?;
+        let message = String::from_utf8_lossy(&self.buf[..end]).into_owned();
+        self.buf.drain(..end + END_OF_MESSAGE.len());
+        Some(message)
+    }
+
+    /// `Some(None)` if the message is incomplete, `None` if malformed.
+    fn parse_chunked(&mut self) -> Option<Option<String>> {
+        let mut pos = 0;
+        let mut message = Vec::new();
+        loop {
+            let rest = &self.buf[pos..];
+            let Some(newline) = rest.iter().skip(1).position(|&b| b == b'\n') else {
+                return Some(None);
+            };
+            let header = &rest[..newline + 2];
+            let size = header.strip_prefix(b"\n#")?.strip_suffix(b"\n")?;
+            pos += header.len();
+            if size == b"#" {
+                self.buf.drain(..pos);
+                return Some(Some(String::from_utf8_lossy(&message).into_owned()));
+            }
+            let size: usize = std::str::from_utf8(size).ok()?.parse().ok()?;
+            if self.buf.len() < pos + size {
+                return Some(None);
+            }
+            message.extend_from_slice(&self.buf[pos..pos + size]);
+            pos += size;
+        }
+    }
+}
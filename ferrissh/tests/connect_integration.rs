@@ -0,0 +1,151 @@
+//! Integration tests for connection establishment: source address and
+//! interface binding, address racing and the connect timeout.
+//!
+//! Uses the mock SSH server (see `common/mod.rs`), which listens on
+//! `127.0.0.1` only.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test connect_integration
+//! ```
+
+mod common;
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, Instant};
+
+use ferrissh::error::TransportError;
+use ferrissh::{Error, Platform, SessionBuilder};
+use tokio::net::TcpListener;
+
+fn session_builder(host: &str, port: u16) -> SessionBuilder {
+    SessionBuilder::new(host)
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+}
+
+/// The connection comes from the configured source address.
+#[tokio::test]
+async fn test_source_address() {
+    let port = common::mock_server_port().await;
+    let source = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2));
+    let session = session_builder("127.0.0.1", port)
+        .source_address(source)
+        .connect()
+        .await
+        .unwrap();
+
+    let local = session.connection_info().local_addr.unwrap();
+    assert_eq!(local.ip(), source);
+
+    session.close().await.ok();
+}
+
+/// A source address of the wrong family leaves nothing to connect to.
+#[tokio::test]
+async fn test_source_address_family_mismatch() {
+    let port = common::mock_server_port().await;
+    let err = session_builder("127.0.0.1", port)
+        .source_address(IpAddr::V6(Ipv6Addr::LOCALHOST))
+        .connect()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            Error::Transport(TransportError::ConnectionFailed { source, attempts, .. })
+                if source.kind() == std::io::ErrorKind::AddrNotAvailable && attempts.is_empty()
+        ),
+        "got: {err}"
+    );
+}
+
+/// Binding to a missing interface fails the connection.
+#[tokio::test]
+async fn test_bind_interface_missing() {
+    let port = common::mock_server_port().await;
+    let err = session_builder("127.0.0.1", port)
+        .bind_interface("ferrissh-none0")
+        .connect()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            Error::Transport(TransportError::ConnectionFailed { attempts, .. }) if attempts.len() == 1
+        ),
+        "got: {err}"
+    );
+}
+
+/// `localhost` may resolve to `::1` first; the IPv4 address still wins.
+#[tokio::test]
+async fn test_dual_stack_name() {
+    let port = common::mock_server_port().await;
+    let session = session_builder("localhost", port).connect().await.unwrap();
+
+    let peer = session.connection_info().peer_addr.unwrap();
+    assert_eq!(peer.ip(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+
+    session.close().await.ok();
+}
+
+/// Every address tried is reported with its error.
+#[tokio::test]
+async fn test_refused_reports_attempts() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+
+    let err = session_builder("127.0.0.1", port)
+        .connect()
+        .await
+        .unwrap_err();
+    match &err {
+        Error::Transport(TransportError::ConnectionFailed {
+            port: failed_port,
+            attempts,
+            ..
+        }) => {
+            assert_eq!(*failed_port, port);
+            assert_eq!(attempts.len(), 1);
+            assert_eq!(attempts[0].0.port(), port);
+            assert_eq!(attempts[0].1.kind(), std::io::ErrorKind::ConnectionRefused);
+        }
+        _ => panic!("got: {err}"),
+    }
+}
+
+/// A server that never completes the handshake fails within the connect
+/// timeout, not the (longer) operation timeout.
+#[tokio::test]
+async fn test_connect_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            held.push(stream);
+        }
+    });
+
+    let started = Instant::now();
+    let err = session_builder("127.0.0.1", port)
+        .connect_timeout(Duration::from_millis(300))
+        .connect()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::Transport(TransportError::Timeout(timeout)) if timeout == Duration::from_millis(300)
+        ),
+        "got: {err}"
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
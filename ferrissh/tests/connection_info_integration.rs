@@ -0,0 +1,70 @@
+//! Integration tests for connection metadata.
+//!
+//! The mock SSH server (see `common/mod.rs`) identifies itself as
+//! `common::SERVER_ID` and sends `common::BANNER` before authentication.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test connection_info_integration
+//! ```
+
+mod common;
+
+use std::time::Duration;
+
+use ferrissh::{Driver, DriverBuilder, Platform, Protocol, SessionBuilder};
+
+/// Identification string, banner, algorithms and addresses are captured.
+#[tokio::test]
+async fn test_session_connection_info() {
+    let port = common::mock_server_port().await;
+    let session = SessionBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+        .connect()
+        .await
+        .unwrap();
+
+    let info = session.connection_info();
+    assert_eq!(info.protocol, Protocol::Ssh);
+    assert_eq!(info.server_id.as_deref(), Some(common::SERVER_ID));
+    assert_eq!(info.banner.as_deref(), Some(common::BANNER));
+    assert_eq!(&info.algorithms, session.negotiated_algorithms());
+    assert!(!info.algorithms.kex.is_empty());
+
+    let peer = info.peer_addr.expect("peer address");
+    assert_eq!(peer.port(), port);
+    assert!(peer.ip().is_loopback());
+    let local = info.local_addr.expect("local address");
+    assert!(local.ip().is_loopback());
+    assert_ne!(local.port(), 0);
+
+    session.close().await.ok();
+}
+
+/// The driver exposes the info once open.
+#[tokio::test]
+async fn test_driver_connection_info() {
+    let port = common::mock_server_port().await;
+    let mut driver = DriverBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+        .build()
+        .unwrap();
+    assert!(driver.connection_info().is_none());
+
+    driver.open().await.unwrap();
+    let info = driver.connection_info().expect("open driver has info");
+    assert_eq!(info.server_id.as_deref(), Some(common::SERVER_ID));
+
+    driver.close().await.unwrap();
+}
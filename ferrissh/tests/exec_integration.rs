@@ -0,0 +1,130 @@
+//! Integration tests for non-PTY `exec` channels.
+//!
+//! The mock SSH server (see `common/mod.rs`) runs `exec` requests with
+//! `bash -c`, sending stderr as extended data and reporting the exit status
+//! or the signal that killed the command.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test exec_integration
+//! ```
+
+mod common;
+
+use futures_util::StreamExt;
+
+use ferrissh::{ExecChunk, ExitStatus};
+
+/// stdout and stderr come back separately, with no echo or prompt.
+#[tokio::test]
+async fn test_exec_separates_stdout_and_stderr() {
+    let session = common::linux_session().await;
+
+    let result = session
+        .exec("echo to_stdout; echo to_stderr >&2")
+        .await
+        .unwrap();
+    assert_eq!(result.stdout, "to_stdout\n");
+    assert_eq!(result.stderr, "to_stderr\n");
+    assert_eq!(result.exit_status, ExitStatus::Code(0));
+    assert!(result.is_success());
+
+    session.close().await.ok();
+}
+
+/// A non-zero exit status is reported as-is.
+#[tokio::test]
+async fn test_exec_reports_exit_code() {
+    let session = common::linux_session().await;
+
+    let result = session.exec("ls /nonexistent_ferrissh_path").await.unwrap();
+    assert!(result.stdout.is_empty());
+    assert!(result.stderr.contains("No such file or directory"));
+    assert_eq!(result.exit_status.code(), Some(2));
+    assert!(!result.is_success());
+
+    session.close().await.ok();
+}
+
+/// A command killed by a signal reports the signal instead of a code.
+#[tokio::test]
+async fn test_exec_reports_exit_signal() {
+    let session = common::linux_session().await;
+
+    let result = session.exec("kill -TERM $$").await.unwrap();
+    assert!(
+        matches!(&result.exit_status, ExitStatus::Signal { signal, .. } if signal == "TERM"),
+        "got: {:?}",
+        result.exit_status
+    );
+    assert_eq!(result.exit_status.code(), None);
+
+    session.close().await.ok();
+}
+
+/// The streaming variant tags chunks and exposes the exit status on
+/// completion.
+#[tokio::test]
+async fn test_exec_stream_chunks_and_completion() {
+    let session = common::linux_session().await;
+
+    let mut stream = session
+        .exec_stream("seq 1 2000; echo done >&2; exit 3")
+        .await
+        .unwrap();
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    while let Some(chunk) = stream.next_chunk().await.unwrap() {
+        match chunk {
+            ExecChunk::Stdout(data) => stdout.extend_from_slice(&data),
+            ExecChunk::Stderr(data) => stderr.extend_from_slice(&data),
+        }
+    }
+    assert!(stream.is_done());
+    assert_eq!(stream.completion().unwrap().exit_status.code(), Some(3));
+
+    let stdout = String::from_utf8(stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 2000);
+    assert!(stdout.ends_with("2000\n"));
+    assert_eq!(stderr, b"done\n");
+
+    session.close().await.ok();
+}
+
+/// `into_stream()` works with `futures::StreamExt`.
+#[tokio::test]
+async fn test_exec_stream_into_stream() {
+    let session = common::linux_session().await;
+
+    let stream = session.exec_stream("echo a; echo b").await.unwrap();
+    let chunks: Vec<_> = stream.into_stream().collect().await;
+    let stdout: Vec<u8> = chunks
+        .into_iter()
+        .map(Result::unwrap)
+        .flat_map(|chunk| match chunk {
+            ExecChunk::Stdout(data) => data.to_vec(),
+            ExecChunk::Stderr(_) => Vec::new(),
+        })
+        .collect();
+    assert_eq!(stdout, b"a\nb\n");
+
+    session.close().await.ok();
+}
+
+/// Exec channels run alongside a PTY channel on the same session.
+#[tokio::test]
+async fn test_exec_alongside_pty_channel() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let (pty, exec) = tokio::join!(
+        channel.send_command("echo from_pty"),
+        session.exec("echo from_exec")
+    );
+    assert!(pty.unwrap().result.contains("from_pty"));
+    assert_eq!(exec.unwrap().stdout, "from_exec\n");
+
+    channel.close().await.ok();
+    session.close().await.ok();
+}
@@ -0,0 +1,207 @@
+//! Integration tests for local and remote port forwarding.
+//!
+//! The mock SSH server (see `common/mod.rs`) connects `direct-tcpip`
+//! channels to real sockets and serves `tcpip-forward` requests with real
+//! listeners, so each test forwards to a small echo server on localhost.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test forward_integration
+//! ```
+
+mod common;
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use ferrissh::Error;
+use ferrissh::error::TransportError;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Start a TCP server that echoes everything back, returning its port.
+async fn echo_server() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let (mut reader, mut writer) = stream.split();
+                let _ = tokio::io::copy(&mut reader, &mut writer).await;
+            });
+        }
+    });
+    port
+}
+
+/// Send `message` through `addr` and return what comes back.
+async fn round_trip(addr: SocketAddr, message: &[u8]) -> Vec<u8> {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(message).await.unwrap();
+    let mut reply = vec![0; message.len()];
+    tokio::time::timeout(Duration::from_secs(5), stream.read_exact(&mut reply))
+        .await
+        .expect("echo timed out")
+        .unwrap();
+    reply
+}
+
+/// Wait until nothing accepts connections on `addr` any more.
+async fn assert_closes(addr: SocketAddr) {
+    for _ in 0..50 {
+        if TcpStream::connect(addr).await.is_err() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("{addr} still accepts connections");
+}
+
+fn localhost(port: u16) -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], port))
+}
+
+/// Connections to the local listener reach the remote target, several at
+/// once.
+#[tokio::test]
+async fn test_local_forward_round_trip() {
+    let session = common::linux_session().await;
+    let target = echo_server().await;
+
+    let forward = session
+        .forward_local("127.0.0.1:0", "127.0.0.1", target)
+        .await
+        .unwrap();
+    assert!(forward.is_active());
+    assert_eq!(forward.remote_port(), target);
+    assert_ne!(forward.local_addr().port(), 0);
+
+    let addr = forward.local_addr();
+    let (a, b) = tokio::join!(round_trip(addr, b"first"), round_trip(addr, b"second"));
+    assert_eq!(a, b"first");
+    assert_eq!(b, b"second");
+
+    session.close().await.ok();
+}
+
+/// Dropping the handle closes the local listener.
+#[tokio::test]
+async fn test_local_forward_stops_on_drop() {
+    let session = common::linux_session().await;
+    let target = echo_server().await;
+
+    let forward = session
+        .forward_local("127.0.0.1:0", "127.0.0.1", target)
+        .await
+        .unwrap();
+    let addr = forward.local_addr();
+    assert_eq!(round_trip(addr, b"ping").await, b"ping");
+
+    drop(forward);
+    assert_closes(addr).await;
+
+    session.close().await.ok();
+}
+
+/// Closing the session stops its forwards.
+#[tokio::test]
+async fn test_local_forward_stops_with_session() {
+    let session = common::linux_session().await;
+    let target = echo_server().await;
+
+    let forward = session
+        .forward_local("127.0.0.1:0", "127.0.0.1", target)
+        .await
+        .unwrap();
+    session.close().await.ok();
+
+    assert_closes(forward.local_addr()).await;
+    assert!(!forward.is_active());
+}
+
+/// A local address that can't be bound is reported as `ForwardBind`.
+#[tokio::test]
+async fn test_local_forward_bind_error() {
+    let session = common::linux_session().await;
+    let taken = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = taken.local_addr().unwrap().to_string();
+
+    let err = session
+        .forward_local(&addr, "127.0.0.1", 22)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::Transport(TransportError::ForwardBind { address, .. }) if *address == addr),
+        "got: {err}"
+    );
+
+    session.close().await.ok();
+}
+
+/// Connections to the server's listener reach the local target.
+#[tokio::test]
+async fn test_remote_forward_round_trip() {
+    let session = common::linux_session().await;
+    let target = echo_server().await;
+
+    let forward = session
+        .forward_remote("127.0.0.1", 0, "127.0.0.1", target)
+        .await
+        .unwrap();
+    assert!(forward.is_active());
+    assert_ne!(forward.bind_port(), 0);
+
+    let addr = localhost(forward.bind_port());
+    assert_eq!(round_trip(addr, b"hello").await, b"hello");
+    assert_eq!(round_trip(addr, b"again").await, b"again");
+
+    session.close().await.ok();
+}
+
+/// Cancelling, or dropping, the handle makes the server stop listening.
+#[tokio::test]
+async fn test_remote_forward_cancel_and_drop() {
+    let session = common::linux_session().await;
+    let target = echo_server().await;
+
+    let forward = session
+        .forward_remote("127.0.0.1", 0, "127.0.0.1", target)
+        .await
+        .unwrap();
+    let addr = localhost(forward.bind_port());
+    assert_eq!(round_trip(addr, b"x").await, b"x");
+    forward.cancel().await.unwrap();
+    assert_closes(addr).await;
+
+    let forward = session
+        .forward_remote("127.0.0.1", 0, "127.0.0.1", target)
+        .await
+        .unwrap();
+    let addr = localhost(forward.bind_port());
+    drop(forward);
+    assert_closes(addr).await;
+
+    session.close().await.ok();
+}
+
+/// A forward the server refuses is reported as `ForwardRejected`.
+#[tokio::test]
+async fn test_remote_forward_rejected() {
+    let session = common::linux_session().await;
+
+    let err = session
+        .forward_remote("203.0.113.1", 0, "127.0.0.1", 1)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            Error::Transport(TransportError::ForwardRejected { .. })
+        ),
+        "got: {err}"
+    );
+    assert!(session.is_alive());
+
+    session.close().await.ok();
+}
@@ -0,0 +1,138 @@
+//! Integration tests for pinned and custom host key verification.
+//!
+//! The mock server (see `common/mod.rs`) generates a fresh host key per
+//! test process, so its fingerprint is captured with a recording verifier.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test host_key_integration
+//! ```
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ferrissh::error::TransportError;
+use ferrissh::transport::{HostKeyVerifier, PublicKey, sha256_fingerprint};
+use ferrissh::{Driver, DriverBuilder, Error, HostKeyVerification, Platform};
+
+fn driver(port: u16, verification: HostKeyVerification) -> ferrissh::GenericDriver {
+    DriverBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .host_key_verification(verification)
+        .build()
+        .expect("driver build should succeed")
+}
+
+/// Records every key it sees and answers with a fixed decision.
+#[derive(Default)]
+struct Recorder {
+    reject: Option<&'static str>,
+    seen: Mutex<Vec<(String, u16, String)>>,
+}
+
+impl HostKeyVerifier for Recorder {
+    fn verify(&self, host: &str, port: u16, key: &PublicKey) -> Result<(), String> {
+        self.seen
+            .lock()
+            .unwrap()
+            .push((host.to_string(), port, sha256_fingerprint(key)));
+        match self.reject {
+            Some(reason) => Err(reason.to_string()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Connect once through a recorder to learn the mock server's fingerprint.
+async fn server_fingerprint(port: u16) -> String {
+    let recorder = Arc::new(Recorder::default());
+    let mut driver = driver(port, HostKeyVerification::Custom(recorder.clone()));
+    driver.open().await.expect("recording verifier accepts");
+    driver.close().await.ok();
+    let seen = recorder.seen.lock().unwrap();
+    seen[0].2.clone()
+}
+
+/// A custom verifier receives the host, port and key, and can accept.
+#[tokio::test]
+async fn test_custom_verifier_accepts() {
+    let port = common::mock_server_port().await;
+    let recorder = Arc::new(Recorder::default());
+
+    let mut driver = driver(port, HostKeyVerification::Custom(recorder.clone()));
+    driver.open().await.expect("verifier accepts the key");
+    let response = driver.send_command("echo custom_ok").await.unwrap();
+    assert!(response.result.contains("custom_ok"));
+    driver.close().await.ok();
+
+    let seen = recorder.seen.lock().unwrap();
+    assert_eq!(seen.len(), 1);
+    assert_eq!(seen[0].0, "127.0.0.1");
+    assert_eq!(seen[0].1, port);
+    assert!(seen[0].2.starts_with("SHA256:"));
+}
+
+/// A custom verifier's rejection reason is surfaced.
+#[tokio::test]
+async fn test_custom_verifier_rejects() {
+    let port = common::mock_server_port().await;
+    let recorder = Arc::new(Recorder {
+        reject: Some("host not in inventory"),
+        ..Default::default()
+    });
+
+    let mut driver = driver(port, HostKeyVerification::Custom(recorder));
+    let err = driver.open().await.expect_err("verifier rejects the key");
+    assert!(
+        matches!(
+            err,
+            Error::Transport(TransportError::HostKeyRejected { ref reason, .. })
+                if reason == "host not in inventory"
+        ),
+        "got: {err}"
+    );
+}
+
+/// A pinned fingerprint (with or without the `SHA256:` prefix) is accepted.
+#[tokio::test]
+async fn test_pinned_fingerprint_accepts() {
+    let port = common::mock_server_port().await;
+    let fingerprint = server_fingerprint(port).await;
+    let bare = fingerprint.trim_start_matches("SHA256:").to_string();
+
+    for pins in [vec![fingerprint.clone()], vec!["SHA256:other".into(), bare]] {
+        let mut driver = driver(port, HostKeyVerification::Pinned(pins));
+        driver.open().await.expect("pinned fingerprint matches");
+        driver.close().await.ok();
+    }
+}
+
+/// A key whose fingerprint is not pinned is rejected with its fingerprint.
+#[tokio::test]
+async fn test_pinned_fingerprint_mismatch() {
+    let port = common::mock_server_port().await;
+    let fingerprint = server_fingerprint(port).await;
+
+    let mut driver = driver(
+        port,
+        HostKeyVerification::Pinned(vec![
+            "SHA256:47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU".into(),
+        ]),
+    );
+    let err = driver.open().await.expect_err("unpinned key is rejected");
+    assert!(
+        matches!(
+            err,
+            Error::Transport(TransportError::HostKeyRejected { fingerprint: ref fp, .. })
+                if *fp == fingerprint
+        ),
+        "got: {err}"
+    );
+}
@@ -0,0 +1,154 @@
+//! Integration tests for interrupting running commands.
+//!
+//! The mock SSH server (see `common/mod.rs`) runs each command as its own
+//! process and kills it on Ctrl-C (or an SSH `signal` request), printing
+//! `^C` and a fresh prompt like a real shell.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test interrupt_integration
+//! ```
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use ferrissh::Error;
+use ferrissh::error::{ChannelError, TransportError};
+use ferrissh::transport::Sig;
+
+/// A command that timed out can be interrupted, and the channel reused.
+#[tokio::test]
+async fn test_interrupt_after_timeout() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    channel.set_timeout(Duration::from_millis(500));
+    let result = channel.send_command("sleep 30").await;
+    assert!(
+        matches!(result, Err(Error::Channel(ChannelError::PatternTimeout(_)))),
+        "sleep should time out"
+    );
+    channel.set_timeout(Duration::from_secs(10));
+
+    let started = Instant::now();
+    channel.interrupt().await.unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    let response = channel.send_command("echo alive").await.unwrap();
+    assert_eq!(response.result.to_string(), "alive");
+
+    session.close().await.ok();
+}
+
+/// Cancelling a stream stops the remote command instead of waiting for it.
+#[tokio::test]
+async fn test_cancel_stream_interrupts_command() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    {
+        let mut stream = channel
+            .send_command_stream("yes tick | head -c 5000; sleep 30")
+            .await
+            .unwrap();
+        assert!(stream.next_chunk().await.unwrap().is_some());
+
+        let started = Instant::now();
+        stream.cancel().await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(stream.is_done());
+        assert!(stream.completion().is_none());
+    }
+
+    let response = channel.send_command("echo after_cancel").await.unwrap();
+    assert_eq!(response.result.to_string(), "after_cancel");
+
+    session.close().await.ok();
+}
+
+/// Cancelling a stream whose command already finished does not interrupt
+/// anything.
+#[tokio::test]
+async fn test_cancel_finished_stream() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    {
+        let mut stream = channel.send_command_stream("echo quick").await.unwrap();
+        // Let the output and the prompt arrive before cancelling.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        stream.cancel().await.unwrap();
+    }
+
+    let response = channel.send_command("echo next").await.unwrap();
+    assert_eq!(response.result.to_string(), "next");
+
+    session.close().await.ok();
+}
+
+/// `send_control` writes the control character; `resync` waits for the
+/// prompt it produces.
+#[tokio::test]
+async fn test_send_control_and_resync() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    assert!(matches!(
+        channel.send_control('1').await,
+        Err(Error::Channel(ChannelError::InvalidControlKey('1')))
+    ));
+
+    channel.set_timeout(Duration::from_millis(300));
+    assert!(channel.send_command("sleep 30").await.is_err());
+    channel.set_timeout(Duration::from_secs(10));
+
+    channel.send_control('C').await.unwrap();
+    channel.resync().await.unwrap();
+
+    let response = channel.send_command("echo resynced").await.unwrap();
+    assert_eq!(response.result.to_string(), "resynced");
+
+    session.close().await.ok();
+}
+
+/// An SSH `signal` request stops the running command.
+#[tokio::test]
+async fn test_signal_request() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    channel.set_timeout(Duration::from_millis(300));
+    assert!(channel.send_command("sleep 30").await.is_err());
+    channel.set_timeout(Duration::from_secs(10));
+
+    channel.signal(Sig::INT).await.unwrap();
+    channel.resync().await.unwrap();
+
+    let response = channel.send_command("echo signalled").await.unwrap();
+    assert_eq!(response.result.to_string(), "signalled");
+
+    session.close().await.ok();
+}
+
+/// russh cannot send SSH `break` requests.
+#[tokio::test]
+async fn test_break_unsupported_over_ssh() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let err = channel.send_break().await.unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            Error::Transport(TransportError::Unsupported { feature, .. }) if feature == "break"
+        ),
+        "got: {err}"
+    );
+
+    channel.close().await.unwrap();
+    assert!(matches!(channel.interrupt().await, Err(Error::Driver(_))));
+
+    session.close().await.ok();
+}
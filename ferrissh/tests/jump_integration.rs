@@ -0,0 +1,203 @@
+//! Integration tests for jump host (`ProxyJump`) chaining.
+//!
+//! The mock SSH server (see `common/mod.rs`) forwards `direct-tcpip`
+//! channels, so it doubles as the jump host and the target.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test jump_integration
+//! ```
+
+mod common;
+
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+use ferrissh::error::TransportError;
+use ferrissh::{
+    DisconnectReason, Driver, DriverBuilder, Error, JumpHost, Platform, SessionBuilder,
+};
+
+fn jump(port: u16) -> JumpHost {
+    JumpHost::new("127.0.0.1")
+        .port(port)
+        .danger_disable_host_key_verification()
+}
+
+fn target_builder(port: u16) -> DriverBuilder {
+    DriverBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+}
+
+/// Start a TCP relay to `upstream` that drops its single connection when
+/// the returned sender fires.
+async fn spawn_relay(upstream: u16) -> (u16, oneshot::Sender<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (kill_tx, kill_rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let (mut client, _) = listener.accept().await.unwrap();
+        let mut server = tokio::net::TcpStream::connect(("127.0.0.1", upstream))
+            .await
+            .unwrap();
+        tokio::select! {
+            _ = tokio::io::copy_bidirectional(&mut client, &mut server) => {}
+            _ = kill_rx => {}
+        }
+    });
+
+    (port, kill_tx)
+}
+
+// =============================================================================
+// Tunnelled connections
+// =============================================================================
+
+/// A driver connects through a single jump host and runs commands.
+#[tokio::test]
+async fn test_single_jump_host() {
+    let port = common::mock_server_port().await;
+
+    let mut driver = target_builder(port).jump_host(jump(port)).build().unwrap();
+    driver
+        .open()
+        .await
+        .expect("tunnelled connect should succeed");
+
+    let response = driver.send_command("echo via_jump").await.unwrap();
+    assert!(response.result.contains("via_jump"));
+
+    driver.close().await.ok();
+}
+
+/// Several jump hosts are traversed in order.
+#[tokio::test]
+async fn test_chained_jump_hosts() {
+    let port = common::mock_server_port().await;
+
+    let mut driver = target_builder(port)
+        .jump_host(jump(port).username("hop1"))
+        .jump_host(jump(port).username("hop2").password("hop2"))
+        .build()
+        .unwrap();
+    driver.open().await.expect("chained connect should succeed");
+
+    let response = driver.send_command("echo via_chain").await.unwrap();
+    assert!(response.result.contains("via_chain"));
+
+    driver.close().await.ok();
+}
+
+/// Channels opened on a tunnelled session work unchanged.
+#[tokio::test]
+async fn test_session_channels_over_jump_host() {
+    let port = common::mock_server_port().await;
+
+    let session = SessionBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+        .jump_host(jump(port))
+        .connect()
+        .await
+        .expect("tunnelled session should connect");
+
+    let mut ch1 = session.open_channel().await.unwrap();
+    let mut ch2 = session.open_channel().await.unwrap();
+
+    let r1 = ch1.send_command("echo chan_one").await.unwrap();
+    let r2 = ch2.send_command("echo chan_two").await.unwrap();
+    assert!(r1.result.contains("chan_one"));
+    assert!(r2.result.contains("chan_two"));
+
+    ch1.close().await.ok();
+    ch2.close().await.ok();
+    session.close().await.ok();
+}
+
+// =============================================================================
+// Failures
+// =============================================================================
+
+/// An unreachable jump host is reported against that hop.
+#[tokio::test]
+async fn test_unreachable_jump_host() {
+    let port = common::mock_server_port().await;
+    let dead = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let dead_port = dead.local_addr().unwrap().port();
+    drop(dead);
+
+    let mut driver = target_builder(port)
+        .jump_host(jump(dead_port))
+        .build()
+        .unwrap();
+    let err = driver.open().await.expect_err("dead jump host should fail");
+    assert!(
+        matches!(
+            err,
+            Error::Transport(TransportError::JumpHost { port, .. }) if port == dead_port
+        ),
+        "got: {err}"
+    );
+}
+
+/// A target the jump host cannot reach fails with `ConnectionFailed`.
+#[tokio::test]
+async fn test_unreachable_target_behind_jump_host() {
+    let port = common::mock_server_port().await;
+    let dead = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let dead_port = dead.local_addr().unwrap().port();
+    drop(dead);
+
+    let mut driver = target_builder(dead_port)
+        .jump_host(jump(port))
+        .build()
+        .unwrap();
+    let err = driver.open().await.expect_err("dead target should fail");
+    assert!(
+        matches!(
+            err,
+            Error::Transport(TransportError::ConnectionFailed { port, .. }) if port == dead_port
+        ),
+        "got: {err}"
+    );
+}
+
+/// Losing the jump host connection names that hop in the disconnect reason.
+#[tokio::test]
+async fn test_jump_host_disconnect_reason() {
+    let port = common::mock_server_port().await;
+    let (relay_port, kill) = spawn_relay(port).await;
+
+    let mut driver = target_builder(port)
+        .jump_host(jump(relay_port))
+        .build()
+        .unwrap();
+    driver.open().await.unwrap();
+
+    let disconnected = driver.disconnected();
+    kill.send(()).unwrap();
+
+    let reason = tokio::time::timeout(Duration::from_secs(10), disconnected)
+        .await
+        .expect("disconnect should be detected");
+    assert!(
+        matches!(
+            reason,
+            DisconnectReason::JumpHost { port, .. } if port == relay_port
+        ),
+        "got: {reason:?}"
+    );
+}
@@ -0,0 +1,228 @@
+//! Integration tests for the NETCONF client.
+//!
+//! Uses the mock SSH server's `netconf` subsystem (see `common/netconf.rs`),
+//! which keeps its datastores in memory per connection and speaks
+//! base:1.1 (chunked framing) unless the user is `netconf-1.0`.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test netconf_integration
+//! ```
+
+mod common;
+
+use ferrissh::error::ChannelError;
+use ferrissh::netconf::{
+    self, Datastore, DefaultOperation, EditConfigOptions, ErrorSeverity, ErrorTag, ErrorType,
+    Framing, RpcError,
+};
+use ferrissh::{Error, Session};
+
+async fn connect_as(username: &str) -> Session {
+    common::linux_session_builder()
+        .await
+        .username(username)
+        .connect()
+        .await
+        .expect("connect should succeed")
+}
+
+async fn connect() -> Session {
+    connect_as("test").await
+}
+
+/// The `<rpc-error>`s of a failed operation.
+fn rpc_errors(err: Error) -> Vec<RpcError> {
+    match err {
+        Error::Channel(ChannelError::NetconfRpc { errors }) => errors,
+        other => panic!("expected rpc-error, got: {other}"),
+    }
+}
+
+/// Hellos are exchanged and chunked framing is chosen when both sides
+/// support base:1.1.
+#[tokio::test]
+async fn test_hello_chunked() {
+    let session = connect().await;
+    let client = session.netconf().await.unwrap();
+
+    assert_eq!(client.framing(), Framing::Chunked);
+    assert!(client.session_id().is_some());
+    assert!(client.supports(netconf::BASE_1_1));
+    assert!(client.supports(netconf::CANDIDATE));
+    // Advertised with a `?module=` query.
+    assert!(client.supports(netconf::WRITABLE_RUNNING));
+    assert!(!client.supports(netconf::STARTUP));
+
+    client.close().await.unwrap();
+    session.close().await.ok();
+}
+
+/// A base:1.0-only server keeps end-of-message framing.
+#[tokio::test]
+async fn test_end_of_message_framing() {
+    let session = connect_as("netconf-1.0").await;
+    let mut client = session.netconf().await.unwrap();
+
+    assert_eq!(client.framing(), Framing::EndOfMessage);
+    assert!(!client.supports(netconf::BASE_1_1));
+
+    client
+        .edit_config(Datastore::Running, "<hostname>r1</hostname>")
+        .await
+        .unwrap();
+    let config = client.get_config(Datastore::Running, None).await.unwrap();
+    assert_eq!(config, "<hostname>r1</hostname>");
+
+    client.close().await.unwrap();
+    session.close().await.ok();
+}
+
+/// `get` returns the `<data>` contents.
+#[tokio::test]
+async fn test_get_with_filter() {
+    let session = connect().await;
+    let mut client = session.netconf().await.unwrap();
+
+    let data = client
+        .get(Some("<interfaces><interface/></interfaces>"))
+        .await
+        .unwrap();
+    assert_eq!(data, "<interfaces><interface/></interfaces>");
+    assert_eq!(client.get(None).await.unwrap(), "");
+
+    client.close().await.unwrap();
+    session.close().await.ok();
+}
+
+/// The candidate workflow: edit, validate, commit, discard.
+#[tokio::test]
+async fn test_candidate_commit_and_discard() {
+    let session = connect().await;
+    let mut client = session.netconf().await.unwrap();
+
+    client.lock(Datastore::Candidate).await.unwrap();
+    client
+        .edit_config(
+            Datastore::Candidate,
+            "<system><host-name>edge1</host-name></system>",
+        )
+        .await
+        .unwrap();
+    client.validate(Datastore::Candidate).await.unwrap();
+    assert_eq!(
+        client.get_config(Datastore::Running, None).await.unwrap(),
+        ""
+    );
+
+    client.commit().await.unwrap();
+    assert_eq!(
+        client.get_config(Datastore::Running, None).await.unwrap(),
+        "<system><host-name>edge1</host-name></system>"
+    );
+
+    client
+        .edit_config_with(
+            Datastore::Candidate,
+            "<system/>",
+            &EditConfigOptions::new().default_operation(DefaultOperation::Replace),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        client.get_config(Datastore::Candidate, None).await.unwrap(),
+        "<system/>"
+    );
+    client.discard_changes().await.unwrap();
+    assert_eq!(
+        client.get_config(Datastore::Candidate, None).await.unwrap(),
+        "<system><host-name>edge1</host-name></system>"
+    );
+
+    client.unlock(Datastore::Candidate).await.unwrap();
+    client.close().await.unwrap();
+    session.close().await.ok();
+}
+
+/// A lock held by another session is reported as `lock-denied`, and is
+/// released when that session closes.
+#[tokio::test]
+async fn test_lock_denied() {
+    let session = connect().await;
+    let mut first = session.netconf().await.unwrap();
+    let mut second = session.netconf().await.unwrap();
+
+    first.lock(Datastore::Candidate).await.unwrap();
+    let errors = rpc_errors(second.lock(Datastore::Candidate).await.unwrap_err());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].error_type, ErrorType::Protocol);
+    assert_eq!(errors[0].tag, ErrorTag::LockDenied);
+    assert_eq!(
+        errors[0].info.as_deref(),
+        Some(format!("<session-id>{}</session-id>", first.session_id().unwrap()).as_str())
+    );
+
+    let errors = rpc_errors(
+        second
+            .edit_config(Datastore::Candidate, "<x/>")
+            .await
+            .unwrap_err(),
+    );
+    assert_eq!(errors[0].tag, ErrorTag::InUse);
+
+    first.close().await.unwrap();
+    second.lock(Datastore::Candidate).await.unwrap();
+
+    second.close().await.unwrap();
+    session.close().await.ok();
+}
+
+/// Validation errors are typed; warnings do not fail the operation.
+#[tokio::test]
+async fn test_validate_errors_and_warnings() {
+    let session = connect().await;
+    let mut client = session.netconf().await.unwrap();
+
+    client
+        .edit_config(Datastore::Candidate, "<deprecated/>")
+        .await
+        .unwrap();
+    client.validate(Datastore::Candidate).await.unwrap();
+
+    client
+        .edit_config(Datastore::Candidate, "<invalid/>")
+        .await
+        .unwrap();
+    let err = client.validate(Datastore::Candidate).await.unwrap_err();
+    assert!(err.to_string().contains("invalid-value"), "got: {err}");
+    let errors = rpc_errors(err);
+    assert_eq!(errors[0].error_type, ErrorType::Application);
+    assert_eq!(errors[0].tag, ErrorTag::InvalidValue);
+    assert_eq!(errors[0].severity, ErrorSeverity::Error);
+    assert_eq!(errors[0].path.as_deref(), Some("/config/invalid"));
+    assert_eq!(
+        errors[0].message.as_deref(),
+        Some("value <invalid/> is not allowed")
+    );
+
+    client.close().await.unwrap();
+    session.close().await.ok();
+}
+
+/// Arbitrary RPCs go through `rpc`; unknown ones fail with
+/// `operation-not-supported`.
+#[tokio::test]
+async fn test_unsupported_rpc() {
+    let session = connect().await;
+    let mut client = session.netconf().await.unwrap();
+
+    let errors = rpc_errors(client.rpc("<get-software-information/>").await.unwrap_err());
+    assert_eq!(errors[0].tag, ErrorTag::OperationNotSupported);
+
+    // The session is still usable afterwards.
+    assert_eq!(client.get(Some("<a/>")).await.unwrap(), "<a/>");
+
+    client.close().await.unwrap();
+    session.close().await.ok();
+}
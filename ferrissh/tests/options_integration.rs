@@ -0,0 +1,146 @@
+//! Integration tests for per-command options.
+//!
+//! Uses the mock SSH server (see `common/mod.rs`), whose prompt is
+//! `user@mock:~$ `. Commands that stop short of the prompt (`printf`
+//! followed by `sleep`) stand in for confirmations.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test options_integration
+//! ```
+
+mod common;
+
+use std::time::Duration;
+
+use ferrissh::channel::LiteralMatcher;
+use ferrissh::error::ChannelError;
+use ferrissh::{CommandOptions, Error};
+use regex::bytes::Regex;
+
+/// A command's timeout applies to that command only.
+#[tokio::test]
+async fn test_command_timeout() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let options = CommandOptions::new().timeout(Duration::from_millis(200));
+    let err = channel
+        .send_command_with("sleep 1", &options)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::Channel(ChannelError::PatternTimeout(_))),
+        "got: {err:?}"
+    );
+    channel.interrupt().await.unwrap();
+
+    let response = channel.send_command("sleep 0.5; echo done").await.unwrap();
+    assert_eq!(response.result.to_string(), "done");
+
+    session.close().await.ok();
+}
+
+/// An expected pattern ends the command before the prompt, and leaves the
+/// privilege level alone.
+#[tokio::test]
+async fn test_expect_pattern() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let options = CommandOptions::new().expect(Regex::new(r"\[confirm\]\s*$").unwrap());
+    let response = channel
+        .send_command_with("printf 'Proceed? [confirm]'; sleep 0.3", &options)
+        .await
+        .unwrap();
+    assert_eq!(response.prompt, "[confirm]");
+    assert_eq!(channel.current_privilege(), Some("user"));
+
+    // Without the expected output, the prompt still ends the command.
+    channel.resync().await.unwrap();
+    let response = channel
+        .send_command_with("echo ok", &options)
+        .await
+        .unwrap();
+    assert_eq!(response.result.to_string(), "ok");
+    assert_eq!(response.prompt, "$");
+
+    session.close().await.ok();
+}
+
+/// `expect_only` ignores the channel's prompt patterns.
+#[tokio::test]
+async fn test_expect_only() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let options = CommandOptions::new().expect_only(LiteralMatcher::new("done>"));
+    let response = channel
+        .send_command_with(
+            r"printf 'a$\n'; sleep 0.3; printf 'done> '; sleep 0.3",
+            &options,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.result.to_string(), "a$");
+    assert_eq!(response.prompt, "done>");
+
+    channel.resync().await.unwrap();
+    session.close().await.ok();
+}
+
+/// Failure patterns and normalization can be overridden.
+#[tokio::test]
+async fn test_failure_and_normalize_overrides() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let response = channel
+        .send_command("echo command not found")
+        .await
+        .unwrap();
+    assert!(!response.is_success());
+
+    let options = CommandOptions::new().failed_when_contains(["boom"]);
+    let response = channel
+        .send_command_with("echo command not found", &options)
+        .await
+        .unwrap();
+    assert!(response.is_success());
+    let response = channel
+        .send_command_with("echo boom", &options)
+        .await
+        .unwrap();
+    assert_eq!(response.failure_message.as_deref(), Some("boom"));
+
+    let options = CommandOptions::new().normalize(false);
+    let response = channel
+        .send_command_with("echo raw", &options)
+        .await
+        .unwrap();
+    let result = response.result.to_string();
+    assert!(result.starts_with("echo raw\n"), "got: {result:?}");
+    assert!(result.ends_with("user@mock:~$ "), "got: {result:?}");
+
+    session.close().await.ok();
+}
+
+/// Output that looks like another level's prompt can be kept from
+/// changing the privilege level.
+#[tokio::test]
+async fn test_update_privilege_off() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let options = CommandOptions::new().update_privilege(false);
+    let response = channel
+        .send_command_with("printf 'root@mock:~# '; sleep 0.3", &options)
+        .await
+        .unwrap();
+    assert_eq!(response.prompt, "#");
+    assert_eq!(channel.current_privilege(), Some("user"));
+
+    channel.resync().await.unwrap();
+    session.close().await.ok();
+}
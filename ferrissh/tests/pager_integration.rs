@@ -0,0 +1,93 @@
+//! Integration tests for automatic pager handling.
+//!
+//! The mock SSH server (see `common/mod.rs`) has a `paged` command that
+//! prints three pages separated by `more`-style `--More--(NN%)` prompts,
+//! erasing each answered prompt with a carriage return and line clear or
+//! with backspaces. Pages 2 and 3 start with an indented line.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test pager_integration
+//! ```
+
+mod common;
+
+use std::time::Duration;
+
+use ferrissh::{Platform, PlatformDefinition, Session};
+
+const ALL_PAGES: &str =
+    "line 1\nline 2\nline 3\n  line 4\nline 5\nline 6\n  line 7\nline 8\nline 9";
+
+async fn connect(platform: Platform) -> Session {
+    common::linux_session_builder()
+        .await
+        .platform(platform)
+        .connect()
+        .await
+        .expect("connect should succeed")
+}
+
+/// Every pager prompt is answered and no trace of it is left in the output.
+#[tokio::test]
+async fn test_send_command_pages_through() {
+    let session = connect(Platform::Linux).await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let response = channel.send_command("paged").await.unwrap();
+    assert_eq!(response.result.to_string(), ALL_PAGES);
+
+    // The channel is back in step for the next command.
+    let response = channel.send_command("echo after").await.unwrap();
+    assert_eq!(response.result.to_string(), "after");
+
+    session.close().await.ok();
+}
+
+/// Streaming answers pager prompts too.
+#[tokio::test]
+async fn test_stream_pages_through() {
+    let session = connect(Platform::Linux).await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let mut output = Vec::new();
+    {
+        let mut stream = channel.send_command_stream("paged").await.unwrap();
+        while let Some(chunk) = stream.next_chunk().await.unwrap() {
+            output.extend_from_slice(&chunk);
+        }
+        assert_eq!(stream.completion().unwrap().prompt, "user@mock:~$");
+    }
+    assert_eq!(String::from_utf8(output).unwrap(), ALL_PAGES);
+
+    session.close().await.ok();
+}
+
+/// The platform's pager response is what gets sent.
+#[tokio::test]
+async fn test_custom_pager_response() {
+    let platform = PlatformDefinition::from(Platform::Linux).with_pager_response("q");
+    let session = connect(Platform::Custom(Box::new(platform))).await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    // `q` quits after the first page.
+    let response = channel.send_command("paged").await.unwrap();
+    assert_eq!(response.result.to_string(), "line 1\nline 2\nline 3");
+
+    session.close().await.ok();
+}
+
+/// Without pager patterns, the prompt is left alone and the read times out.
+#[tokio::test]
+async fn test_no_pager_patterns() {
+    let mut platform = PlatformDefinition::from(Platform::Linux);
+    platform.pager_patterns.clear();
+    let session = connect(Platform::Custom(Box::new(platform))).await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    channel.set_timeout(Duration::from_millis(500));
+    assert!(channel.send_command("paged").await.is_err());
+
+    session.close().await.ok();
+}
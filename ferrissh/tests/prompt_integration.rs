@@ -0,0 +1,204 @@
+//! Integration tests for prompt matchers.
+//!
+//! Uses the mock SSH server (see `common/mod.rs`), whose prompt is
+//! `user@mock:~$ ` (`root@mock:~# ` after `sudo -i`).
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test prompt_integration
+//! ```
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use ferrissh::Session;
+use ferrissh::channel::{LiteralMatcher, PromptMatcher};
+
+/// Prints a line ending in `$`, pauses, then finishes it.
+const DOLLAR_OUTPUT: &str = "printf 'cost: 5$ '; sleep 0.2; printf 'more\\n'";
+
+async fn connect_learning() -> Session {
+    common::linux_session_builder()
+        .await
+        .learn_prompt(true)
+        .connect()
+        .await
+        .expect("connect should succeed")
+}
+
+/// A hand-written matcher: any line starting with `<user>@mock:`. Only
+/// implements `find_match`, so the prompt is the whole line.
+struct MockHost;
+
+impl PromptMatcher for MockHost {
+    fn find_match(&self, data: &[u8]) -> Option<usize> {
+        let start = data
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |pos| pos + 1);
+        let line = &data[start..];
+        let host = line.windows(6).position(|w| w == b"@mock:")?;
+        (host > 0 && line.trim_ascii_end().ends_with(b"$")).then_some(data.len())
+    }
+}
+
+/// Added matchers are tried first, and the prompt they match is the one
+/// reported.
+#[tokio::test]
+async fn test_literal_prompt_matcher() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+    let patterns = channel.prompt_matchers().len();
+
+    let response = channel.send_command("echo hello").await.unwrap();
+    assert_eq!(response.prompt, "$");
+
+    channel.add_prompt_matcher(LiteralMatcher::new("user@mock:~$"));
+    assert_eq!(channel.prompt_matchers().len(), patterns + 1);
+
+    let response = channel.send_command("echo hello").await.unwrap();
+    assert_eq!(response.result.to_string(), "hello");
+    assert_eq!(response.prompt, "user@mock:~$");
+    assert_eq!(channel.current_privilege(), Some("user"));
+
+    channel.clear_prompt_matchers();
+    assert_eq!(channel.prompt_matchers().len(), patterns);
+
+    session.close().await.ok();
+}
+
+/// Custom matchers work for streams too.
+#[tokio::test]
+async fn test_custom_prompt_matcher_stream() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+    channel.add_prompt_matcher(MockHost);
+
+    let mut output = Vec::new();
+    {
+        let mut stream = channel.send_command_stream("seq 1 3").await.unwrap();
+        while let Some(chunk) = stream.next_chunk().await.unwrap() {
+            output.extend_from_slice(&chunk);
+        }
+        assert_eq!(stream.completion().unwrap().prompt, "user@mock:~$");
+    }
+    assert_eq!(String::from_utf8(output).unwrap(), "1\n2\n3");
+
+    session.close().await.ok();
+}
+
+/// The prompt learned when the channel opens is matched first, so output
+/// that only ends with `$` does not end the command.
+#[tokio::test]
+async fn test_learned_prompt() {
+    let session = connect_learning().await;
+    let mut channel = session.open_channel().await.unwrap();
+    assert_eq!(channel.learned_prompt().unwrap().prompt(), b"user@mock:~$");
+
+    let response = channel.send_command(DOLLAR_OUTPUT).await.unwrap();
+    assert_eq!(response.result.to_string(), "cost: 5$ more");
+    assert_eq!(response.prompt, "user@mock:~$");
+
+    channel.forget_prompt();
+    assert!(channel.learned_prompt().is_none());
+    let response = channel.send_command("echo hello").await.unwrap();
+    assert_eq!(response.result.to_string(), "hello");
+
+    session.close().await.ok();
+}
+
+/// Output ending in `$` that pauses long enough to pass for a prompt ends
+/// the command, but does not replace the learned prompt.
+#[tokio::test]
+async fn test_learned_prompt_survives_fallback_match() {
+    let session = connect_learning().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let response = channel
+        .send_command("printf 'cost: 5$ '; sleep 0.8; printf 'more\\n'")
+        .await
+        .unwrap();
+    assert_eq!(response.prompt, "$");
+    assert_eq!(channel.learned_prompt().unwrap().prompt(), b"user@mock:~$");
+    assert_eq!(channel.current_privilege(), Some("user"));
+
+    channel.resync().await.unwrap();
+    let response = channel.send_command("echo hello").await.unwrap();
+    assert_eq!(response.result.to_string(), "hello");
+    assert_eq!(response.prompt, "user@mock:~$");
+
+    session.close().await.ok();
+}
+
+/// Changing privilege level learns the new level's prompt.
+#[tokio::test]
+async fn test_learned_prompt_follows_privilege() {
+    let session = connect_learning().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    channel.acquire_privilege("root").await.unwrap();
+    assert_eq!(channel.learned_prompt().unwrap().prompt(), b"root@mock:~#");
+    let response = channel.send_command("whoami").await.unwrap();
+    assert_eq!(response.prompt, "root@mock:~#");
+
+    channel.acquire_privilege("user").await.unwrap();
+    assert_eq!(channel.learned_prompt().unwrap().prompt(), b"user@mock:~$");
+    assert_eq!(channel.current_privilege(), Some("user"));
+
+    session.close().await.ok();
+}
+
+/// Streams wait for the learned prompt too.
+#[tokio::test]
+async fn test_learned_prompt_stream() {
+    let session = connect_learning().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let mut output = Vec::new();
+    {
+        let mut stream = channel.send_command_stream(DOLLAR_OUTPUT).await.unwrap();
+        while let Some(chunk) = stream.next_chunk().await.unwrap() {
+            output.extend_from_slice(&chunk);
+        }
+        assert_eq!(stream.completion().unwrap().prompt, "user@mock:~$");
+    }
+    assert_eq!(String::from_utf8(output).unwrap(), "cost: 5$ more");
+
+    session.close().await.ok();
+}
+
+/// Cancelling a stream stalled on output that ends in `$` interrupts the
+/// command rather than taking the output for its prompt.
+#[tokio::test]
+async fn test_learned_prompt_stream_cancel() {
+    let session = connect_learning().await;
+    let mut channel = session.open_channel().await.unwrap();
+    let marker = common::scratch_dir("prompt").join("finished");
+    let command = format!(
+        "printf 'cost: 5$ '; sleep 1; touch {}",
+        common::remote(&marker)
+    );
+
+    {
+        let mut stream = channel.send_command_stream(&command).await.unwrap();
+        // Read the output, but stop before it settles into a prompt.
+        let pending = tokio::time::timeout(Duration::from_millis(300), stream.next_chunk()).await;
+        assert!(pending.is_err(), "stream should still be waiting");
+
+        let started = Instant::now();
+        stream.cancel().await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(stream.completion().is_none());
+    }
+
+    let response = channel.send_command("echo after_cancel").await.unwrap();
+    assert_eq!(response.result.to_string(), "after_cancel");
+
+    // The interrupted command never gets to finish.
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(!marker.exists(), "command should have been interrupted");
+
+    session.close().await.ok();
+}
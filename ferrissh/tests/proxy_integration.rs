@@ -0,0 +1,320 @@
+//! Integration tests for SOCKS5 and HTTP CONNECT proxy support.
+//!
+//! Runs minimal local proxy stand-ins in front of the mock SSH server
+//! (see `common/mod.rs`).
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test proxy_integration
+//! ```
+
+mod common;
+
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use ferrissh::error::TransportError;
+use ferrissh::transport::ProxyConfig;
+use ferrissh::{Driver, DriverBuilder, Error, JumpHost, Platform};
+
+/// `alice:secret`, Basic-encoded.
+const BASIC_CREDENTIALS: &str = "YWxpY2U6c2VjcmV0";
+
+fn proxied_driver(port: u16, proxy: ProxyConfig) -> ferrissh::GenericDriver {
+    DriverBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+        .proxy(proxy)
+        .build()
+        .expect("driver build should succeed")
+}
+
+/// Start a SOCKS5 stand-in. With `credentials`, only username/password
+/// auth with those values is accepted.
+async fn spawn_socks5(credentials: Option<(&'static str, &'static str)>) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        while let Ok((mut client, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut header = [0u8; 2];
+                client.read_exact(&mut header).await?;
+                let mut methods = vec![0u8; header[1] as usize];
+                client.read_exact(&mut methods).await?;
+
+                if let Some((user, pass)) = credentials {
+                    if !methods.contains(&0x02) {
+                        return client.write_all(&[0x05, 0xFF]).await;
+                    }
+                    client.write_all(&[0x05, 0x02]).await?;
+
+                    let mut ver_len = [0u8; 2];
+                    client.read_exact(&mut ver_len).await?;
+                    let mut username = vec![0u8; ver_len[1] as usize];
+                    client.read_exact(&mut username).await?;
+                    let mut len = [0u8; 1];
+                    client.read_exact(&mut len).await?;
+                    let mut password = vec![0u8; len[0] as usize];
+                    client.read_exact(&mut password).await?;
+
+                    if username != user.as_bytes() || password != pass.as_bytes() {
+                        return client.write_all(&[0x01, 0x01]).await;
+                    }
+                    client.write_all(&[0x01, 0x00]).await?;
+                } else {
+                    client.write_all(&[0x05, 0x00]).await?;
+                }
+
+                let mut request = [0u8; 4];
+                client.read_exact(&mut request).await?;
+                let host = match request[3] {
+                    0x01 => {
+                        let mut ip = [0u8; 4];
+                        client.read_exact(&mut ip).await?;
+                        std::net::Ipv4Addr::from(ip).to_string()
+                    }
+                    _ => {
+                        let mut len = [0u8; 1];
+                        client.read_exact(&mut len).await?;
+                        let mut name = vec![0u8; len[0] as usize];
+                        client.read_exact(&mut name).await?;
+                        String::from_utf8_lossy(&name).into_owned()
+                    }
+                };
+                let target_port = client.read_u16().await?;
+
+                let Ok(mut upstream) = TcpStream::connect((host.as_str(), target_port)).await
+                else {
+                    return client
+                        .write_all(&[0x05, 0x05, 0, 0x01, 0, 0, 0, 0, 0, 0])
+                        .await;
+                };
+                client
+                    .write_all(&[0x05, 0x00, 0, 0x01, 0, 0, 0, 0, 0, 0])
+                    .await?;
+                tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+                Ok::<_, std::io::Error>(())
+            });
+        }
+    });
+
+    port
+}
+
+/// Start an HTTP CONNECT stand-in. With `require_auth`, requests without
+/// Basic credentials for `alice:secret` get a 407.
+async fn spawn_http_proxy(require_auth: bool) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        while let Ok((mut client, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                while !request.ends_with(b"\r\n\r\n") {
+                    request.push(client.read_u8().await?);
+                }
+                let request = String::from_utf8_lossy(&request).into_owned();
+
+                let expected = format!("Proxy-Authorization: Basic {BASIC_CREDENTIALS}\r\n");
+                if require_auth && !request.contains(&expected) {
+                    return client
+                        .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+                        .await;
+                }
+
+                let authority = request
+                    .strip_prefix("CONNECT ")
+                    .and_then(|rest| rest.split(' ').next())
+                    .unwrap_or_default()
+                    .to_string();
+                let Ok(mut upstream) = TcpStream::connect(authority.as_str()).await else {
+                    return client.write_all(b"HTTP/1.1 502 Bad Gateway\r\n\r\n").await;
+                };
+                client
+                    .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                    .await?;
+                tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+                Ok::<_, std::io::Error>(())
+            });
+        }
+    });
+
+    port
+}
+
+async fn assert_echo(driver: &mut ferrissh::GenericDriver, marker: &str) {
+    driver.open().await.expect("proxied connect should succeed");
+    let response = driver
+        .send_command(&format!("echo {marker}"))
+        .await
+        .unwrap();
+    assert!(response.result.contains(marker));
+    driver.close().await.ok();
+}
+
+// =============================================================================
+// SOCKS5
+// =============================================================================
+
+/// A session runs over an unauthenticated SOCKS5 proxy.
+#[tokio::test]
+async fn test_socks5_proxy() {
+    let port = common::mock_server_port().await;
+    let proxy_port = spawn_socks5(None).await;
+
+    let mut driver = proxied_driver(port, ProxyConfig::socks5("127.0.0.1", proxy_port));
+    assert_echo(&mut driver, "socks_ok").await;
+}
+
+/// Username/password auth is performed when the proxy requests it.
+#[tokio::test]
+async fn test_socks5_proxy_auth() {
+    let port = common::mock_server_port().await;
+    let proxy_port = spawn_socks5(Some(("alice", "secret"))).await;
+
+    let mut driver = proxied_driver(
+        port,
+        ProxyConfig::socks5("127.0.0.1", proxy_port).with_auth("alice", "secret"),
+    );
+    assert_echo(&mut driver, "socks_auth_ok").await;
+}
+
+/// Wrong or missing SOCKS5 credentials surface as a proxy auth failure.
+#[tokio::test]
+async fn test_socks5_proxy_auth_rejected() {
+    let port = common::mock_server_port().await;
+    let proxy_port = spawn_socks5(Some(("alice", "secret"))).await;
+
+    for proxy in [
+        ProxyConfig::socks5("127.0.0.1", proxy_port).with_auth("alice", "wrong"),
+        ProxyConfig::socks5("127.0.0.1", proxy_port),
+    ] {
+        let mut driver = proxied_driver(port, proxy);
+        let err = driver
+            .open()
+            .await
+            .expect_err("bad credentials should fail");
+        assert!(
+            matches!(
+                err,
+                Error::Transport(TransportError::ProxyAuthenticationFailed { .. })
+            ),
+            "got: {err}"
+        );
+    }
+}
+
+/// A target the proxy cannot reach surfaces the SOCKS5 reply.
+#[tokio::test]
+async fn test_socks5_proxy_target_unreachable() {
+    let dead = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let dead_port = dead.local_addr().unwrap().port();
+    drop(dead);
+    let proxy_port = spawn_socks5(None).await;
+
+    let mut driver = proxied_driver(dead_port, ProxyConfig::socks5("127.0.0.1", proxy_port));
+    let err = driver.open().await.expect_err("dead target should fail");
+    assert!(
+        matches!(err, Error::Transport(TransportError::Proxy { ref message, .. }) if message.contains("connection refused")),
+        "got: {err}"
+    );
+}
+
+/// The proxy carries the connection to the first jump host.
+#[tokio::test]
+async fn test_socks5_proxy_with_jump_host() {
+    let port = common::mock_server_port().await;
+    let proxy_port = spawn_socks5(None).await;
+
+    let mut driver = DriverBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+        .proxy(ProxyConfig::socks5("127.0.0.1", proxy_port))
+        .jump_host(
+            JumpHost::new("127.0.0.1")
+                .port(port)
+                .danger_disable_host_key_verification(),
+        )
+        .build()
+        .unwrap();
+    assert_echo(&mut driver, "socks_jump_ok").await;
+}
+
+// =============================================================================
+// HTTP CONNECT
+// =============================================================================
+
+/// A session runs over an HTTP CONNECT proxy.
+#[tokio::test]
+async fn test_http_connect_proxy() {
+    let port = common::mock_server_port().await;
+    let proxy_port = spawn_http_proxy(false).await;
+
+    let mut driver = proxied_driver(port, ProxyConfig::http_connect("127.0.0.1", proxy_port));
+    assert_echo(&mut driver, "http_ok").await;
+}
+
+/// Basic credentials are sent to the HTTP proxy.
+#[tokio::test]
+async fn test_http_connect_proxy_auth() {
+    let port = common::mock_server_port().await;
+    let proxy_port = spawn_http_proxy(true).await;
+
+    let mut driver = proxied_driver(
+        port,
+        ProxyConfig::http_connect("127.0.0.1", proxy_port).with_auth("alice", "secret"),
+    );
+    assert_echo(&mut driver, "http_auth_ok").await;
+}
+
+/// A 407 response surfaces as a proxy auth failure.
+#[tokio::test]
+async fn test_http_connect_proxy_auth_required() {
+    let port = common::mock_server_port().await;
+    let proxy_port = spawn_http_proxy(true).await;
+
+    let mut driver = proxied_driver(port, ProxyConfig::http_connect("127.0.0.1", proxy_port));
+    let err = driver
+        .open()
+        .await
+        .expect_err("missing credentials should fail");
+    assert!(
+        matches!(
+            err,
+            Error::Transport(TransportError::ProxyAuthenticationFailed { .. })
+        ),
+        "got: {err}"
+    );
+}
+
+/// An unreachable proxy is reported as a proxy connection failure.
+#[tokio::test]
+async fn test_proxy_unreachable() {
+    let port = common::mock_server_port().await;
+    let dead = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let dead_port = dead.local_addr().unwrap().port();
+    drop(dead);
+
+    let mut driver = proxied_driver(port, ProxyConfig::http_connect("127.0.0.1", dead_port));
+    let err = driver.open().await.expect_err("dead proxy should fail");
+    assert!(
+        matches!(
+            err,
+            Error::Transport(TransportError::ProxyConnectionFailed { port, .. }) if port == dead_port
+        ),
+        "got: {err}"
+    );
+}
@@ -0,0 +1,275 @@
+//! Integration tests for automatic reconnect.
+//!
+//! Connections go through a local TCP relay in front of the mock SSH server
+//! (see `common/mod.rs`) so tests can cut them, or take the "device" down
+//! entirely, on demand.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test reconnect_integration
+//! ```
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+use ferrissh::error::DriverError;
+use ferrissh::{
+    Driver, DriverBuilder, Error, Platform, ReconnectEvent, ReconnectPolicy, SessionBuilder,
+    SessionState,
+};
+
+/// A TCP relay to the mock server whose connections can be cut.
+struct Relay {
+    port: u16,
+    kill: Arc<watch::Sender<u64>>,
+    accept: JoinHandle<()>,
+}
+
+impl Relay {
+    async fn spawn(upstream: u16) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let kill = Arc::new(watch::channel(0u64).0);
+
+        let accept_kill = kill.clone();
+        let accept = tokio::spawn(async move {
+            loop {
+                let Ok((mut client, _)) = listener.accept().await else {
+                    break;
+                };
+                let mut killed = accept_kill.subscribe();
+                tokio::spawn(async move {
+                    let mut server = tokio::net::TcpStream::connect(("127.0.0.1", upstream))
+                        .await
+                        .unwrap();
+                    tokio::select! {
+                        _ = tokio::io::copy_bidirectional(&mut client, &mut server) => {}
+                        _ = killed.changed() => {}
+                    }
+                });
+            }
+        });
+
+        Self { port, kill, accept }
+    }
+
+    /// Drop every open connection; new ones are still accepted.
+    fn drop_connections(&self) {
+        self.kill.send_modify(|generation| *generation += 1);
+    }
+
+    /// Drop every open connection and stop listening.
+    async fn shut_down(self) {
+        self.drop_connections();
+        self.accept.abort();
+        let _ = self.accept.await;
+    }
+}
+
+fn fast_policy() -> ReconnectPolicy {
+    ReconnectPolicy::new()
+        .initial_backoff(Duration::from_millis(10))
+        .max_backoff(Duration::from_millis(50))
+        .jitter(0.0)
+}
+
+fn driver_builder(port: u16) -> DriverBuilder {
+    DriverBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+}
+
+fn session_builder(port: u16) -> SessionBuilder {
+    SessionBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .danger_disable_host_key_verification()
+}
+
+fn drain(events: &mut broadcast::Receiver<ReconnectEvent>) -> Vec<ReconnectEvent> {
+    std::iter::from_fn(|| events.try_recv().ok()).collect()
+}
+
+/// The next command after a connection drop reconnects, reopens the
+/// channel and returns it to the privilege level it was at.
+#[tokio::test]
+async fn test_driver_reconnects_and_restores_privilege() {
+    let relay = Relay::spawn(common::mock_server_port().await).await;
+    let mut driver = driver_builder(relay.port)
+        .reconnect(fast_policy())
+        .build()
+        .unwrap();
+    let mut events = driver.reconnect_events();
+    driver.open().await.unwrap();
+
+    driver.acquire_privilege("root").await.unwrap();
+    assert_eq!(driver.current_privilege(), Some("root"));
+    let first_connected = driver.connected_since().unwrap();
+
+    relay.drop_connections();
+    driver.disconnected().await;
+
+    let response = driver.send_command("echo after_reconnect").await.unwrap();
+    assert!(response.result.contains("after_reconnect"));
+    assert_eq!(driver.current_privilege(), Some("root"));
+    assert_eq!(driver.state(), SessionState::Ready);
+    assert!(driver.connected_since().unwrap() > first_connected);
+
+    let events = drain(&mut events);
+    assert!(
+        matches!(
+            events.as_slice(),
+            [
+                ReconnectEvent::Disconnected { .. },
+                ReconnectEvent::Attempt { attempt: 1, .. },
+                ReconnectEvent::Reconnected { attempts: 1 },
+                ReconnectEvent::ChannelReopened { privilege: Some(p) },
+            ] if p == "root"
+        ),
+        "got: {events:?}"
+    );
+
+    driver.close().await.ok();
+}
+
+/// Channels sharing a session reconnect onto one new connection.
+#[tokio::test]
+async fn test_session_channels_share_one_reconnect() {
+    let relay = Relay::spawn(common::mock_server_port().await).await;
+    let session = session_builder(relay.port)
+        .reconnect(fast_policy())
+        .connect()
+        .await
+        .unwrap();
+    let mut events = session.reconnect_events();
+    let mut ch1 = session.open_channel().await.unwrap();
+    let mut ch2 = session.open_channel().await.unwrap();
+
+    relay.drop_connections();
+    session.disconnected().await;
+
+    let r1 = ch1.send_command("echo one").await.unwrap();
+    let r2 = ch2.send_command("echo two").await.unwrap();
+    assert!(r1.result.contains("one"));
+    assert!(r2.result.contains("two"));
+    assert_eq!(
+        ch1.session().connected_since(),
+        ch2.session().connected_since()
+    );
+    assert!(!session.is_alive());
+
+    let events = drain(&mut events);
+    let reconnects = events
+        .iter()
+        .filter(|e| matches!(e, ReconnectEvent::Reconnected { .. }))
+        .count();
+    let reopened = events
+        .iter()
+        .filter(|e| matches!(e, ReconnectEvent::ChannelReopened { .. }))
+        .count();
+    assert_eq!(reconnects, 1, "got: {events:?}");
+    assert_eq!(reopened, 2, "got: {events:?}");
+
+    ch1.close().await.ok();
+    ch2.close().await.ok();
+    ch1.session().close().await.ok();
+}
+
+/// When the device stays down, reconnect gives up after `max_attempts`.
+#[tokio::test]
+async fn test_reconnect_gives_up_after_max_attempts() {
+    let relay = Relay::spawn(common::mock_server_port().await).await;
+    let mut driver = driver_builder(relay.port)
+        .reconnect(fast_policy().max_attempts(2))
+        .build()
+        .unwrap();
+    let mut events = driver.reconnect_events();
+    driver.open().await.unwrap();
+
+    relay.shut_down().await;
+    driver.disconnected().await;
+
+    let err = driver.send_command("echo unreachable").await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::Driver(DriverError::ReconnectFailed { attempts: 2, .. })
+        ),
+        "got: {err}"
+    );
+    assert_eq!(driver.state(), SessionState::Dead);
+
+    let events = drain(&mut events);
+    assert!(
+        matches!(
+            events.as_slice(),
+            [
+                ReconnectEvent::Disconnected { .. },
+                ReconnectEvent::Attempt { attempt: 1, .. },
+                ReconnectEvent::AttemptFailed { attempt: 1, .. },
+                ReconnectEvent::Attempt { attempt: 2, .. },
+                ReconnectEvent::AttemptFailed { attempt: 2, .. },
+                ReconnectEvent::GaveUp { attempts: 2 },
+            ]
+        ),
+        "got: {events:?}"
+    );
+
+    driver.close().await.ok();
+}
+
+/// A session closed by the caller is not reconnected.
+#[tokio::test]
+async fn test_closed_session_is_not_reconnected() {
+    let port = common::mock_server_port().await;
+    let session = session_builder(port)
+        .reconnect(fast_policy())
+        .connect()
+        .await
+        .unwrap();
+    let mut events = session.reconnect_events();
+    let mut channel = session.open_channel().await.unwrap();
+
+    session.close().await.unwrap();
+
+    let err = channel.send_command("echo closed").await.unwrap_err();
+    assert!(
+        matches!(err, Error::Driver(DriverError::NotConnected)),
+        "got: {err}"
+    );
+    assert!(drain(&mut events).is_empty());
+}
+
+/// Without a policy a lost connection stays dead until `reconnect()`.
+#[tokio::test]
+async fn test_no_policy_stays_dead_until_explicit_reconnect() {
+    let relay = Relay::spawn(common::mock_server_port().await).await;
+    let mut driver = driver_builder(relay.port).build().unwrap();
+    driver.open().await.unwrap();
+
+    relay.drop_connections();
+    driver.disconnected().await;
+
+    assert!(driver.send_command("echo dead").await.is_err());
+    assert_eq!(driver.state(), SessionState::Dead);
+
+    driver.reconnect().await.unwrap();
+    let response = driver.send_command("echo alive").await.unwrap();
+    assert!(response.result.contains("alive"));
+
+    driver.close().await.ok();
+}
@@ -0,0 +1,290 @@
+//! Integration tests for the SCP client.
+//!
+//! The mock SSH server (see `common/mod.rs`) runs `scp` exec requests with
+//! the system `scp` binary wired to the channel, so both ends of each
+//! transfer are scratch directories under the system temp dir.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test scp_integration
+//! ```
+
+mod common;
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use ferrissh::error::ChannelError;
+use ferrissh::{Error, TransferOptions};
+
+/// A timestamp well in the past, to tell preserved times from fresh ones.
+fn old_time() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000)
+}
+
+fn set_old_mtime(path: &Path) {
+    let file = std::fs::File::open(path).unwrap();
+    file.set_modified(old_time()).unwrap();
+}
+
+fn mode(path: &Path) -> u32 {
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o7777
+}
+
+fn mtime(path: &Path) -> SystemTime {
+    std::fs::metadata(path).unwrap().modified().unwrap()
+}
+
+/// Build `root/{a.txt, sub/b.bin, sub/deeper/c.txt}` with distinct modes.
+fn make_tree(root: &Path) {
+    std::fs::create_dir_all(root.join("sub/deeper")).unwrap();
+    std::fs::write(root.join("a.txt"), b"alpha\n").unwrap();
+    std::fs::write(root.join("sub/b.bin"), vec![0xA5; 100_000]).unwrap();
+    std::fs::write(root.join("sub/deeper/c.txt"), b"gamma\n").unwrap();
+    std::fs::set_permissions(root.join("a.txt"), PermissionsExt::from_mode(0o600)).unwrap();
+    std::fs::set_permissions(root.join("sub"), PermissionsExt::from_mode(0o750)).unwrap();
+    set_old_mtime(&root.join("sub/deeper/c.txt"));
+}
+
+fn assert_same_tree(a: &Path, b: &Path) {
+    for file in ["a.txt", "sub/b.bin", "sub/deeper/c.txt"] {
+        assert_eq!(
+            std::fs::read(a.join(file)).unwrap(),
+            std::fs::read(b.join(file)).unwrap(),
+            "{file}"
+        );
+    }
+}
+
+/// A single-file upload with `preserve` keeps the mode and mtime and
+/// reports progress.
+#[tokio::test]
+async fn test_scp_upload_file_preserves_mode_and_time() {
+    let session = common::linux_session().await;
+    let dir = common::scratch_dir("scp");
+
+    let content: Vec<u8> = (0..150_000u32).map(|i| (i % 253) as u8).collect();
+    let local = dir.join("image.bin");
+    std::fs::write(&local, &content).unwrap();
+    std::fs::set_permissions(&local, PermissionsExt::from_mode(0o640)).unwrap();
+    set_old_mtime(&local);
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let recorder = progress.clone();
+    let options = TransferOptions::new().on_progress(move |p| {
+        recorder.lock().unwrap().push(p.transferred);
+    });
+
+    let target = dir.join("uploaded.bin");
+    let report = session
+        .scp()
+        .preserve(true)
+        .upload(&local, common::remote(&target), &options)
+        .await
+        .unwrap();
+
+    assert_eq!(report.bytes, content.len() as u64);
+    assert_eq!(report.files, 1);
+    assert!(!report.verified);
+    assert_eq!(std::fs::read(&target).unwrap(), content);
+    assert_eq!(mode(&target), 0o640);
+    assert_eq!(mtime(&target), old_time());
+
+    let progress = progress.lock().unwrap().clone();
+    assert_eq!(progress.first(), Some(&0));
+    assert_eq!(progress.last(), Some(&(content.len() as u64)));
+
+    session.close().await.ok();
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// A single-file download into an existing directory keeps the name.
+#[tokio::test]
+async fn test_scp_download_file_into_directory() {
+    let session = common::linux_session().await;
+    let dir = common::scratch_dir("scp");
+
+    let source = dir.join("running.conf");
+    std::fs::write(&source, b"hostname r1\n").unwrap();
+    std::fs::set_permissions(&source, PermissionsExt::from_mode(0o604)).unwrap();
+    set_old_mtime(&source);
+    let dest = dir.join("backup");
+    std::fs::create_dir(&dest).unwrap();
+
+    let report = session
+        .scp()
+        .preserve(true)
+        .download(common::remote(&source), &dest, &TransferOptions::new())
+        .await
+        .unwrap();
+
+    let copied = dest.join("running.conf");
+    assert_eq!(report.files, 1);
+    assert_eq!(std::fs::read(&copied).unwrap(), b"hostname r1\n");
+    assert_eq!(mode(&copied), 0o604);
+    assert_eq!(mtime(&copied), old_time());
+
+    session.close().await.ok();
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// Recursive uploads recreate the tree, modes and times included.
+#[tokio::test]
+async fn test_scp_upload_directory_recursive() {
+    let session = common::linux_session().await;
+    let dir = common::scratch_dir("scp");
+    let tree = dir.join("tree");
+    make_tree(&tree);
+
+    let target = dir.join("copy");
+    let report = session
+        .scp()
+        .recursive(true)
+        .preserve(true)
+        .upload(&tree, common::remote(&target), &TransferOptions::new())
+        .await
+        .unwrap();
+
+    assert_eq!(report.files, 3);
+    assert_eq!(report.bytes, 6 + 100_000 + 6);
+    assert_same_tree(&tree, &target);
+    assert_eq!(mode(&target.join("a.txt")), 0o600);
+    assert_eq!(mode(&target.join("sub")), 0o750);
+    assert_eq!(mtime(&target.join("sub/deeper/c.txt")), old_time());
+
+    session.close().await.ok();
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// Recursive downloads recreate the tree, modes and times included.
+#[tokio::test]
+async fn test_scp_download_directory_recursive() {
+    let session = common::linux_session().await;
+    let dir = common::scratch_dir("scp");
+    let tree = dir.join("tree");
+    make_tree(&tree);
+
+    let target = dir.join("copy");
+    let report = session
+        .scp()
+        .recursive(true)
+        .preserve(true)
+        .download(common::remote(&tree), &target, &TransferOptions::new())
+        .await
+        .unwrap();
+
+    assert_eq!(report.files, 3);
+    assert_same_tree(&tree, &target);
+    assert_eq!(mode(&target.join("a.txt")), 0o600);
+    assert_eq!(mode(&target.join("sub")), 0o750);
+    assert_eq!(mtime(&target.join("sub/deeper/c.txt")), old_time());
+
+    session.close().await.ok();
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// Remote failures come back as `ScpRemote`, local misuse as `Transfer`.
+#[tokio::test]
+async fn test_scp_errors() {
+    let session = common::linux_session().await;
+    let dir = common::scratch_dir("scp");
+
+    let err = session
+        .scp()
+        .download(
+            common::remote(&dir.join("missing")),
+            dir.join("x"),
+            &TransferOptions::new(),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::Channel(ChannelError::ScpRemote { message }) if message.contains("No such file")),
+        "got: {err}"
+    );
+
+    let err = session
+        .scp()
+        .download(common::remote(&dir), dir.join("y"), &TransferOptions::new())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::Channel(ChannelError::ScpRemote { .. })),
+        "got: {err}"
+    );
+
+    let err = session
+        .scp()
+        .upload(
+            &dir,
+            common::remote(&dir.join("z")),
+            &TransferOptions::new(),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::Channel(ChannelError::Transfer { .. })),
+        "got: {err}"
+    );
+
+    let err = session
+        .scp()
+        .upload(
+            dir.join("missing"),
+            common::remote(&dir.join("z")),
+            &TransferOptions::new(),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::Channel(ChannelError::LocalIo { .. })),
+        "got: {err}"
+    );
+
+    session.close().await.ok();
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// A download accepts only the file asked for: extra files and other
+/// names from the server are refused before anything is written.
+#[tokio::test]
+async fn test_scp_download_refuses_unrequested_files() {
+    let session = common::linux_session_builder()
+        .await
+        .username("scp-hostile")
+        .connect()
+        .await
+        .expect("connect should succeed");
+    let dir = common::scratch_dir("scp");
+
+    // A second file after the one requested
+    let err = session
+        .scp()
+        .download("/srv/requested", &dir, &TransferOptions::new())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::Channel(ChannelError::ScpProtocol { .. })),
+        "got: {err}"
+    );
+    assert_eq!(std::fs::read(dir.join("requested")).unwrap(), b"abc");
+    assert!(!dir.join(".bashrc").exists());
+
+    // A file under another name than the one requested
+    let err = session
+        .scp()
+        .download("/srv/wanted", &dir, &TransferOptions::new())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::Channel(ChannelError::ScpProtocol { .. })),
+        "got: {err}"
+    );
+    assert!(!dir.join("wanted").exists());
+
+    session.close().await.ok();
+    std::fs::remove_dir_all(&dir).ok();
+}
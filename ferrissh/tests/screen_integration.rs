@@ -0,0 +1,81 @@
+//! Integration tests for screen emulation.
+//!
+//! Uses the mock SSH server (see `common/mod.rs`), running `printf` to
+//! produce output that redraws itself with carriage returns and cursor
+//! movement.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test screen_integration
+//! ```
+
+mod common;
+
+/// A progress counter rewritten in place, then a table whose first row is
+/// redrawn by moving the cursor up.
+const REDRAW: &str =
+    r"printf 'copy 10%%\rcopy 55%%\rcopy 100%%\neth0 0\neth1 0\n\033[2A\033[2Keth0 7\n\033[1B'";
+
+/// With screen emulation on, the output is what a terminal would show.
+#[tokio::test]
+async fn test_screen_emulation_renders_redraws() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+    assert!(!channel.screen_emulation());
+
+    channel.set_screen_emulation(true);
+    let response = channel.send_command(REDRAW).await.unwrap();
+    assert_eq!(response.result.to_string(), "copy 100%\neth0 7\neth1 0");
+
+    // Full-screen redraws keep only the last frame.
+    let response = channel
+        .send_command(r"printf '\033[H\033[2Jload 0.10\n\033[H\033[2Jload 0.25\n'")
+        .await
+        .unwrap();
+    assert_eq!(response.result.to_string(), "load 0.25");
+
+    // Output longer than the screen goes through the scrollback.
+    let response = channel.send_command("seq 1 100").await.unwrap();
+    let expected: Vec<String> = (1..=100).map(|n| n.to_string()).collect();
+    assert_eq!(response.result.to_string(), expected.join("\n"));
+
+    session.close().await.ok();
+}
+
+/// `send_command_rendered` emulates a screen for one command only.
+#[tokio::test]
+async fn test_screen_emulation_per_command() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    let response = channel.send_command_rendered(REDRAW).await.unwrap();
+    assert_eq!(response.result.to_string(), "copy 100%\neth0 7\neth1 0");
+    assert!(!channel.screen_emulation());
+
+    // Stripping alone keeps every frame.
+    let response = channel.send_command(REDRAW).await.unwrap();
+    let result = response.result.to_string();
+    assert!(
+        result.starts_with("copy 10%copy 55%copy 100%"),
+        "got: {result:?}"
+    );
+
+    session.close().await.ok();
+}
+
+/// Pager prompts are answered and erased on the screen as well.
+#[tokio::test]
+async fn test_screen_emulation_with_pager() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    channel.set_screen_emulation(true);
+    let response = channel.send_command("paged").await.unwrap();
+    assert_eq!(
+        response.result.to_string(),
+        "line 1\nline 2\nline 3\n  line 4\nline 5\nline 6\n  line 7\nline 8\nline 9"
+    );
+
+    session.close().await.ok();
+}
@@ -0,0 +1,193 @@
+//! Integration tests for the SFTP client.
+//!
+//! The mock SSH server (see `common/mod.rs`) serves the `sftp` subsystem
+//! straight from the local filesystem, so "remote" paths here are paths in
+//! a scratch directory under the system temp dir.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test sftp_integration
+//! ```
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ferrissh::error::{ChannelError, TransportError};
+use ferrissh::{Error, FileKind, TransferOptions};
+
+/// Uploading and downloading round-trips the content, reports progress
+/// and verifies checksums.
+#[tokio::test]
+async fn test_sftp_upload_download_round_trip() {
+    let session = common::linux_session().await;
+    let sftp = session.sftp().await.unwrap();
+    let dir = common::scratch_dir("sftp");
+
+    let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+    let local = dir.join("local.bin");
+    std::fs::write(&local, &content).unwrap();
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let recorder = progress.clone();
+    let options = TransferOptions::new()
+        .verify_checksum(true)
+        .on_progress(move |p| recorder.lock().unwrap().push(p.clone()));
+
+    let uploaded = dir.join("uploaded.bin");
+    let report = sftp
+        .upload(&local, common::remote(&uploaded), &options)
+        .await
+        .unwrap();
+    assert_eq!(report.bytes, content.len() as u64);
+    assert!(report.verified);
+    assert_eq!(std::fs::read(&uploaded).unwrap(), content);
+
+    let progress = progress.lock().unwrap().clone();
+    assert!(progress.len() > 2, "got: {progress:?}");
+    let last = progress.last().unwrap();
+    assert_eq!(last.transferred, content.len() as u64);
+    assert_eq!(last.total, content.len() as u64);
+    assert_eq!(last.path, local.display().to_string());
+
+    let downloaded = dir.join("downloaded.bin");
+    let back = sftp
+        .download(common::remote(&uploaded), &downloaded, &options)
+        .await
+        .unwrap();
+    assert_eq!(back.sha256, report.sha256);
+    assert_eq!(std::fs::read(&downloaded).unwrap(), content);
+    assert_eq!(
+        sftp.checksum(common::remote(&uploaded)).await.unwrap(),
+        report.sha256
+    );
+
+    sftp.close().await.unwrap();
+    session.close().await.ok();
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// Directory listing and file management operations.
+#[tokio::test]
+async fn test_sftp_manage_files() {
+    let session = common::linux_session().await;
+    let sftp = session.sftp().await.unwrap();
+    let dir = common::scratch_dir("sftp");
+
+    let sub = dir.join("configs");
+    sftp.mkdir(common::remote(&sub)).await.unwrap();
+    std::fs::write(sub.join("a.conf"), b"hostname r1\n").unwrap();
+
+    let stat = sftp.stat(common::remote(&sub)).await.unwrap();
+    assert!(stat.is_dir());
+
+    let entries = sftp.list_dir(common::remote(&sub)).await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name, "a.conf");
+    assert_eq!(entries[0].stat.kind, FileKind::File);
+    assert_eq!(entries[0].stat.size, Some(12));
+
+    let renamed = sub.join("b.conf");
+    sftp.rename(
+        common::remote(&sub.join("a.conf")),
+        common::remote(&renamed),
+    )
+    .await
+    .unwrap();
+    assert!(sftp.exists(common::remote(&renamed)).await.unwrap());
+    assert!(
+        !sftp
+            .exists(common::remote(&sub.join("a.conf")))
+            .await
+            .unwrap()
+    );
+
+    sftp.remove(common::remote(&renamed)).await.unwrap();
+    sftp.remove_dir(common::remote(&sub)).await.unwrap();
+    assert!(!sub.exists());
+
+    sftp.close().await.unwrap();
+    session.close().await.ok();
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// Remote failures surface as `Transfer` errors, local ones as `LocalIo`.
+#[tokio::test]
+async fn test_sftp_errors() {
+    let session = common::linux_session().await;
+    let sftp = session.sftp().await.unwrap();
+    let dir = common::scratch_dir("sftp");
+
+    let missing = dir.join("missing.txt");
+    let err = sftp.stat(common::remote(&missing)).await.unwrap_err();
+    assert!(
+        matches!(&err, Error::Channel(ChannelError::Transfer { path, .. }) if path == common::remote(&missing)),
+        "got: {err}"
+    );
+
+    let err = sftp
+        .upload(
+            &missing,
+            common::remote(&dir.join("x")),
+            &TransferOptions::new(),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::Channel(ChannelError::LocalIo { path, .. }) if path == &missing),
+        "got: {err}"
+    );
+
+    sftp.close().await.unwrap();
+    session.close().await.ok();
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+/// A server that never answers the subsystem request times out.
+#[tokio::test]
+async fn test_sftp_subsystem_timeout() {
+    let session = common::linux_session_builder()
+        .await
+        .username("mute-subsystem")
+        .timeout(Duration::from_millis(500))
+        .connect()
+        .await
+        .expect("connect should succeed");
+
+    let Err(err) = session.sftp().await else {
+        panic!("sftp should time out");
+    };
+    assert!(
+        matches!(err, Error::Transport(TransportError::Timeout(_))),
+        "got: {err}"
+    );
+
+    session.close().await.ok();
+}
+
+/// SFTP shares the connection with PTY channels.
+#[tokio::test]
+async fn test_sftp_alongside_pty_channel() {
+    let session = common::linux_session().await;
+    let mut channel = session.open_channel().await.unwrap();
+    let sftp = session.sftp().await.unwrap();
+    let dir = common::scratch_dir("sftp");
+
+    let file = dir.join("written-by-shell.txt");
+    channel
+        .send_command(&format!("echo from_shell > {}", file.display()))
+        .await
+        .unwrap();
+    let local = dir.join("copy.txt");
+    sftp.download(common::remote(&file), &local, &TransferOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&local).unwrap(), "from_shell\n");
+
+    sftp.close().await.unwrap();
+    channel.close().await.ok();
+    session.close().await.ok();
+    std::fs::remove_dir_all(&dir).ok();
+}
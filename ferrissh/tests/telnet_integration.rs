@@ -0,0 +1,313 @@
+//! Integration tests for the Telnet transport.
+//!
+//! Runs against a small in-process Telnet server that negotiates NAWS, echo
+//! and suppress-go-ahead, asks for a username and password (`test`), then
+//! runs each line with `bash -c` behind a `user@mock:~$ ` prompt.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test telnet_integration
+//! ```
+
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ferrissh::error::{ChannelError, TransportError};
+use ferrissh::{Driver, DriverBuilder, Error, Platform, Protocol, Session, SessionBuilder};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const IAC: u8 = 255;
+const WILL: u8 = 251;
+const DO: u8 = 253;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const ECHO: u8 = 1;
+const SGA: u8 = 3;
+const TTYPE: u8 = 24;
+const NAWS: u8 = 31;
+
+const PROMPT: &[u8] = b"user@mock:~$ ";
+
+/// What the last client reported about its terminal.
+#[derive(Debug, Default)]
+struct Terminal {
+    /// Window size from NAWS.
+    size: Option<(u16, u16)>,
+    /// Terminal type from `TERMINAL-TYPE IS`.
+    term: Option<String>,
+}
+
+type Reported = Arc<Mutex<Terminal>>;
+
+/// Start the mock Telnet server, returning its port and what clients
+/// report about their terminal.
+async fn mock_telnet_server() -> (u16, Reported) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let terminal = Reported::default();
+    let reported = terminal.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve(stream, reported.clone()));
+        }
+    });
+    (port, terminal)
+}
+
+enum Stage {
+    Username,
+    Password,
+    Shell,
+}
+
+/// Serve one connection: log in, then run commands until it closes.
+async fn serve(mut stream: TcpStream, reported: Reported) {
+    let mut greeting = vec![
+        IAC, DO, NAWS, IAC, WILL, ECHO, IAC, WILL, SGA, IAC, DO, TTYPE,
+    ];
+    // TERMINAL-TYPE SEND
+    greeting.extend_from_slice(&[IAC, SB, TTYPE, 1, IAC, SE]);
+    greeting.extend_from_slice(b"\r\nmock login banner\r\nUsername: ");
+    if stream.write_all(&greeting).await.is_err() {
+        return;
+    }
+
+    let mut stage = Stage::Username;
+    let mut input = Vec::new();
+    let mut buf = [0u8; 1024];
+    loop {
+        let n = match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+        strip_commands(&buf[..n], &mut input, &reported);
+
+        while let Some(end) = input.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = input.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line)
+                .trim_end_matches(['\r', '\n'])
+                .to_string();
+            let reply = match stage {
+                Stage::Username => {
+                    stage = Stage::Password;
+                    format!("{line}\r\nPassword: ").into_bytes()
+                }
+                Stage::Password if line == "test" => {
+                    stage = Stage::Shell;
+                    [b"\r\nLast login: never\r\n".as_slice(), PROMPT].concat()
+                }
+                Stage::Password => {
+                    stage = Stage::Username;
+                    b"\r\nLogin incorrect\r\nUsername: ".to_vec()
+                }
+                Stage::Shell => run(&line).await,
+            };
+            if stream.write_all(&reply).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Echo `line`, run it and follow the output with a prompt.
+async fn run(line: &str) -> Vec<u8> {
+    let mut reply = format!("{line}\r\n").into_bytes();
+    if !line.trim().is_empty() {
+        let output = tokio::process::Command::new("bash")
+            .arg("-c")
+            .arg(line)
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .unwrap();
+        for output in [output.stdout, output.stderr] {
+            let text = String::from_utf8_lossy(&output).replace('\n', "\r\n");
+            reply.extend_from_slice(text.as_bytes());
+        }
+    }
+    reply.extend_from_slice(PROMPT);
+    reply
+}
+
+/// Copy data bytes to `out`, dropping Telnet commands and recording the
+/// window size and terminal type.
+///
+/// Commands are assumed not to straddle reads, which holds for this
+/// client's small writes on localhost.
+fn strip_commands(data: &[u8], out: &mut Vec<u8>, reported: &Reported) {
+    let mut i = 0;
+    while i < data.len() {
+        match data[i] {
+            IAC if data.get(i + 1) == Some(&IAC) => {
+                out.push(IAC);
+                i += 2;
+            }
+            IAC if data.get(i + 1) == Some(&SB) => {
+                let end = data[i..]
+                    .windows(2)
+                    .position(|w| w == [IAC, SE])
+                    .map_or(data.len(), |p| i + p + 2);
+                let params = &data[i + 2..end.saturating_sub(2).max(i + 2)];
+                let mut reported = reported.lock().unwrap();
+                match params {
+                    [NAWS, w1, w2, h1, h2] => {
+                        reported.size = Some((
+                            u16::from_be_bytes([*w1, *w2]),
+                            u16::from_be_bytes([*h1, *h2]),
+                        ));
+                    }
+                    // TERMINAL-TYPE IS <type>
+                    [TTYPE, 0, term @ ..] => {
+                        reported.term = Some(String::from_utf8_lossy(term).into_owned());
+                    }
+                    _ => {}
+                }
+                i = end;
+            }
+            IAC => i += 3,
+            // Telnet end of line is CR LF or CR NUL.
+            0 => i += 1,
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+}
+
+fn session_builder(port: u16) -> SessionBuilder {
+    SessionBuilder::new("127.0.0.1")
+        .protocol(Protocol::Telnet)
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+}
+
+async fn connect() -> Session {
+    let (port, _) = mock_telnet_server().await;
+    session_builder(port)
+        .connect()
+        .await
+        .expect("connect should succeed")
+}
+
+/// The driver logs in and runs commands exactly as it does over SSH.
+#[tokio::test]
+async fn test_telnet_driver_send_command() {
+    let (port, _) = mock_telnet_server().await;
+    let mut driver = DriverBuilder::new("127.0.0.1")
+        .protocol(Protocol::Telnet)
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap();
+    driver.open().await.expect("telnet login should succeed");
+
+    let response = driver.send_command("echo over_telnet").await.unwrap();
+    assert_eq!(response.result.to_string(), "over_telnet");
+
+    let response = driver.send_command("printf 'a\\nb\\n'").await.unwrap();
+    assert_eq!(response.result.lines().collect::<Vec<_>>(), ["a", "b"]);
+
+    let response = driver
+        .send_command("ls /nonexistent_ferrissh")
+        .await
+        .unwrap();
+    assert!(!response.is_success());
+
+    driver.close().await.unwrap();
+}
+
+/// The terminal size is reported with NAWS, and again on resize; the
+/// terminal type when the server asks for it. The text before the login
+/// prompt is kept as the banner.
+#[tokio::test]
+async fn test_telnet_reports_terminal() {
+    let (port, reported) = mock_telnet_server().await;
+    let session = session_builder(port)
+        .terminal_size(132, 40)
+        .terminal_type("vt100")
+        .connect()
+        .await
+        .unwrap();
+
+    assert_eq!(reported.lock().unwrap().size, Some((132, 40)));
+    assert_eq!(reported.lock().unwrap().term.as_deref(), Some("vt100"));
+    assert_eq!(session.protocol(), Protocol::Telnet);
+
+    let info = session.connection_info();
+    assert_eq!(info.banner.as_deref(), Some("mock login banner"));
+    assert_eq!(info.server_id, None);
+    assert_eq!(info.peer_addr.map(|addr| addr.port()), Some(port));
+
+    let mut channel = session.open_channel().await.unwrap();
+    channel.resize(200, 50).await.unwrap();
+    // Any command round trip means the server has read the update.
+    channel.send_command("true").await.unwrap();
+    assert_eq!(reported.lock().unwrap().size, Some((200, 50)));
+
+    session.close().await.ok();
+}
+
+/// A rejected password is reported as an authentication failure.
+#[tokio::test]
+async fn test_telnet_wrong_password() {
+    let (port, _) = mock_telnet_server().await;
+    let err = SessionBuilder::new("127.0.0.1")
+        .protocol(Protocol::Telnet)
+        .port(port)
+        .username("test")
+        .password("wrong")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .connect()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            Error::Transport(TransportError::AuthenticationFailed { user, .. }) if user == "test"
+        ),
+        "got: {err}"
+    );
+}
+
+/// A Telnet connection carries one shell; SSH-only features are refused.
+#[tokio::test]
+async fn test_telnet_single_shell_and_unsupported_features() {
+    let session = connect().await;
+
+    let mut channel = session.open_channel().await.unwrap();
+    let response = channel.send_command("echo first").await.unwrap();
+    assert_eq!(response.result.to_string(), "first");
+
+    assert!(matches!(
+        session.open_channel().await,
+        Err(Error::Channel(ChannelError::PtyOpenFailed))
+    ));
+
+    let err = session.exec("true").await.unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            Error::Transport(TransportError::Unsupported { feature, protocol })
+                if feature == "exec" && protocol == "Telnet"
+        ),
+        "got: {err}"
+    );
+    assert!(matches!(
+        session.sftp().await,
+        Err(Error::Transport(TransportError::Unsupported { .. }))
+    ));
+
+    session.close().await.ok();
+    assert!(!session.is_alive());
+}
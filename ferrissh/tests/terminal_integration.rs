@@ -0,0 +1,109 @@
+//! Integration tests for per-channel terminal settings and resizing.
+//!
+//! The mock SSH server (see `common/mod.rs`) runs shell commands with the
+//! channel's `env` requests applied, plus `TERM`, `COLUMNS` and `LINES`
+//! from its PTY and `window-change` requests, and the PTY modes listed in
+//! `MOCK_PTY_MODES`.
+//!
+//! # Running
+//!
+//! ```bash
+//! cargo test --test terminal_integration
+//! ```
+
+mod common;
+
+use std::time::Duration;
+
+use ferrissh::transport::Pty;
+use ferrissh::{Driver, DriverBuilder, Platform, Session};
+
+async fn connect() -> Session {
+    common::linux_session_builder()
+        .await
+        .terminal_size(120, 30)
+        .connect()
+        .await
+        .expect("connect should succeed")
+}
+
+/// Builder settings apply to the driver's channel.
+#[tokio::test]
+async fn test_builder_terminal_settings() {
+    let port = common::mock_server_port().await;
+    let mut driver = DriverBuilder::new("127.0.0.1")
+        .port(port)
+        .username("test")
+        .password("test")
+        .platform(Platform::Linux)
+        .timeout(Duration::from_secs(10))
+        .terminal_type("vt100")
+        .terminal_mode(Pty::ECHO, 0)
+        .env("FERRISSH_SITE", "lab")
+        .danger_disable_host_key_verification()
+        .build()
+        .unwrap();
+    driver.open().await.unwrap();
+
+    let response = driver
+        .send_command("echo $TERM $FERRISSH_SITE $MOCK_PTY_MODES")
+        .await
+        .unwrap();
+    assert_eq!(
+        response.result.to_string(),
+        format!("vt100 lab {}=0", Pty::ECHO as u8)
+    );
+
+    driver.close().await.unwrap();
+}
+
+/// Each channel can have its own terminal type, size and environment.
+#[tokio::test]
+async fn test_per_channel_terminal_options() {
+    let session = connect().await;
+
+    let mut default = session.open_channel().await.unwrap();
+    let terminal = session
+        .terminal_options()
+        .term("vt220")
+        .size(200, 50)
+        .env("LANG", "C")
+        .env("FERRISSH_ROLE", "second");
+    let mut custom = session.open_channel_with(terminal.clone()).await.unwrap();
+    assert_eq!(custom.terminal(), &terminal);
+
+    let response = default
+        .send_command("echo $TERM $COLUMNS $LINES ${FERRISSH_ROLE:-none}")
+        .await
+        .unwrap();
+    assert_eq!(response.result.to_string(), "xterm 120 30 none");
+
+    let response = custom
+        .send_command("echo $TERM $COLUMNS $LINES $LANG $FERRISSH_ROLE")
+        .await
+        .unwrap();
+    assert_eq!(response.result.to_string(), "vt220 200 50 C second");
+
+    session.close().await.ok();
+}
+
+/// `resize()` sends `window-change` and is remembered by the channel.
+#[tokio::test]
+async fn test_resize_sends_window_change() {
+    let session = connect().await;
+    let mut channel = session.open_channel().await.unwrap();
+
+    channel.resize(250, 60).await.unwrap();
+    assert_eq!(
+        (channel.terminal().width, channel.terminal().height),
+        (250, 60)
+    );
+
+    let response = channel.send_command("echo $COLUMNS $LINES").await.unwrap();
+    assert_eq!(response.result.to_string(), "250 60");
+
+    channel.close().await.unwrap();
+    assert!(channel.resize(80, 24).await.is_err());
+
+    session.close().await.ok();
+}